@@ -0,0 +1,14 @@
+// src/audio/mod.rs
+// Host audio output: the sample queue, dynamic rate control, and the cpal device backend with the `audio` feature
+
+mod rate;
+mod ring;
+
+#[cfg(feature = "audio")]
+mod output;
+
+pub use rate::DynamicRate;
+pub use ring::SampleRing;
+
+#[cfg(feature = "audio")]
+pub use output::{output_devices, AudioError, AudioOutput, DEFAULT_LATENCY};
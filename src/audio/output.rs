@@ -0,0 +1,196 @@
+// src/audio/output.rs
+// cpal output stream fed from a SampleRing, with device selection and underrun logging
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SampleRate, SizedSample};
+use log::{error, info, warn};
+use thiserror::Error;
+
+use super::SampleRing;
+
+/// Audio queued ahead of the device when none is asked for
+pub const DEFAULT_LATENCY: Duration = Duration::from_millis(100);
+
+#[derive(Error, Debug)]
+pub enum AudioError {
+    #[error("no audio output device named \"{0}\"")]
+    NoSuchDevice(String),
+    #[error("no default audio output device")]
+    NoDefaultDevice,
+    #[error("failed to list audio devices: {0}")]
+    Devices(#[from] cpal::DevicesError),
+    #[error("failed to query the audio device: {0}")]
+    SupportedConfigs(#[from] cpal::SupportedStreamConfigsError),
+    #[error("failed to query the audio device: {0}")]
+    DefaultConfig(#[from] cpal::DefaultStreamConfigError),
+    #[error("audio device only takes unsupported {0} samples")]
+    UnsupportedFormat(SampleFormat),
+    #[error("failed to open the audio stream: {0}")]
+    BuildStream(#[from] cpal::BuildStreamError),
+    #[error("failed to start the audio stream: {0}")]
+    PlayStream(#[from] cpal::PlayStreamError),
+}
+
+/// Names of the default host's output devices, for `AudioOutput::open`
+pub fn output_devices() -> Result<Vec<String>, AudioError> {
+    let host = cpal::default_host();
+    Ok(host.output_devices()?.filter_map(|device| device.name().ok()).collect())
+}
+
+/// A playing output stream. Mono samples queued with `queue` go to every
+/// channel of the device; stereo pairs go to its first two, or are mixed
+/// down on a mono device. The stream stops when this is dropped.
+pub struct AudioOutput {
+    _stream: cpal::Stream,
+    ring: Arc<SampleRing>,
+    sample_rate: u32,
+    device_name: String,
+}
+
+impl AudioOutput {
+    /// Open `device` by name, or the host's default output, running at
+    /// `sample_rate` if the device takes it, for `channels` of queued audio
+    /// (1, or 2 for interleaved left/right). Up to `latency` of audio is
+    /// queued ahead of the device, and the queue starts half full of
+    /// silence so the first frames have room to arrive.
+    pub fn open(
+        device: Option<&str>,
+        sample_rate: u32,
+        channels: u16,
+        latency: Duration,
+    ) -> Result<Self, AudioError> {
+        let host = cpal::default_host();
+        let device = match device {
+            Some(name) => host
+                .output_devices()?
+                .find(|device| device.name().is_ok_and(|found| found == name))
+                .ok_or_else(|| AudioError::NoSuchDevice(name.to_string()))?,
+            None => host.default_output_device().ok_or(AudioError::NoDefaultDevice)?,
+        };
+        let device_name = device.name().unwrap_or_else(|_| "unknown device".to_string());
+
+        // The requested rate if any config allows it, preferring float
+        // samples; otherwise whatever the device defaults to
+        let wanted = SampleRate(sample_rate);
+        let supported = device
+            .supported_output_configs()?
+            .filter(|range| range.min_sample_rate() <= wanted && wanted <= range.max_sample_rate())
+            .max_by_key(|range| {
+                let fits = range.channels() >= channels && range.channels() <= 2.max(channels);
+                (range.sample_format() == SampleFormat::F32, fits, range.channels() <= 2)
+            });
+        let supported = match supported {
+            Some(range) => range.with_sample_rate(wanted),
+            None => device.default_output_config()?,
+        };
+        let format = supported.sample_format();
+        let config = supported.config();
+        if config.sample_rate != wanted {
+            warn!("{} doesn't support {}Hz; using {}Hz", device_name, sample_rate, config.sample_rate.0);
+        }
+
+        let channels = channels.max(1) as usize;
+        let capacity = (config.sample_rate.0 as f64 * latency.as_secs_f64()) as usize * channels;
+        let ring = Arc::new(SampleRing::with_channels(capacity.max(channels), channels));
+        ring.push(&vec![0.0; (capacity / 2).next_multiple_of(channels)]);
+
+        let stream = match format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, ring.clone())?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, ring.clone())?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, ring.clone())?,
+            format => return Err(AudioError::UnsupportedFormat(format)),
+        };
+        stream.play()?;
+        info!(
+            "Audio output on {}: {}Hz, {} channel(s), {} samples",
+            device_name, config.sample_rate.0, config.channels, format
+        );
+
+        Ok(Self {
+            _stream: stream,
+            ring,
+            sample_rate: config.sample_rate.0,
+            device_name,
+        })
+    }
+
+    /// The rate the device settled on; the emulator's output must match
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// Queue samples for playback, typically a frame's worth from
+    /// `Nes::take_samples`, interleaved if the output was opened for stereo
+    pub fn queue(&self, samples: &[f32]) {
+        let dropped = self.ring.push(samples);
+        if dropped > 0 {
+            warn!("Audio queue full; dropped {} samples", dropped);
+        }
+    }
+
+    /// Samples waiting to be played, counting each channel's
+    pub fn queued(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Channels per queued frame, as opened
+    pub fn channels(&self) -> usize {
+        self.ring.channels()
+    }
+
+    pub fn underruns(&self) -> u64 {
+        self.ring.underruns()
+    }
+}
+
+// The device callback pops one queued frame per device frame and converts
+// it to the device's sample type. Underruns are logged once each as they
+// start. Device channels past a stereo pair are left silent.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    ring: Arc<SampleRing>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let source_channels = ring.channels();
+    let mut queued = Vec::new();
+    let mut logged = 0;
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            queued.resize(data.len() / channels * source_channels, 0.0);
+            if ring.pop(&mut queued) < queued.len() && ring.underruns() != logged {
+                logged = ring.underruns();
+                warn!("Audio underrun ({} so far)", logged);
+            }
+            for (frame, source) in data.chunks_mut(channels).zip(queued.chunks(source_channels)) {
+                match (source, &mut *frame) {
+                    (&[sample], frame) => frame.fill(T::from_sample(sample)),
+                    (&[left, right], [mono]) => *mono = T::from_sample((left + right) / 2.0),
+                    (&[left, right], [first, second, rest @ ..]) => {
+                        *first = T::from_sample(left);
+                        *second = T::from_sample(right);
+                        rest.fill(T::EQUILIBRIUM);
+                    }
+                    _ => frame.fill(T::EQUILIBRIUM),
+                }
+            }
+        },
+        |err| error!("Audio stream error: {}", err),
+        None,
+    )
+}
@@ -0,0 +1,32 @@
+// src/audio/rate.rs
+// Dynamic rate control: small resample-ratio tweaks that hold the output queue half full
+
+/// Steers the emulator's output rate from how full the audio queue is.
+/// A frame loop paced by vsync runs the console a little fast or slow
+/// against the audio device's clock; left alone the queue drains into
+/// crackling underruns or grows into latency. Feeding the queue level back
+/// into `Apu::set_rate_adjust` after every frame keeps it near half full
+/// with a pitch change too small to hear.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DynamicRate {
+    /// Largest stretch either way; 0.005 is about a twelfth of a semitone
+    pub max_delta: f64,
+}
+
+impl Default for DynamicRate {
+    fn default() -> Self {
+        Self { max_delta: 0.005 }
+    }
+}
+
+impl DynamicRate {
+    /// The rate adjustment for `queued` of `capacity` samples waiting:
+    /// `max_delta` when empty, 0 at half full, `-max_delta` when full
+    pub fn adjust(&self, queued: usize, capacity: usize) -> f64 {
+        if capacity == 0 {
+            return 0.0;
+        }
+        let fill = (queued as f64 / capacity as f64).min(1.0);
+        self.max_delta * (1.0 - 2.0 * fill)
+    }
+}
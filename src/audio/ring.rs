@@ -0,0 +1,112 @@
+// src/audio/ring.rs
+// Bounded sample queue between the emulation thread and the host's audio callback
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Samples in flight to the audio device, mono or interleaved across
+/// `channels`. The emulation thread pushes a frame's worth at a time; the
+/// device callback pops what it needs. Capacity and counts are in samples,
+/// kept to whole multiples of the channel count.
+pub struct SampleRing {
+    state: Mutex<RingState>,
+}
+
+struct RingState {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    channels: usize,
+    underruns: u64,
+    starved: bool,  // The last pop came up short
+    last: Vec<f32>, // Sample per channel, held through an underrun rather than dropping to 0
+}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_channels(capacity, 1)
+    }
+
+    /// Interleaved frames of `channels` samples each, e.g. 2 for left/right
+    pub fn with_channels(capacity: usize, channels: usize) -> Self {
+        assert!(channels > 0, "a sample ring needs at least one channel");
+        let capacity = capacity - capacity % channels;
+        Self {
+            state: Mutex::new(RingState {
+                samples: VecDeque::with_capacity(capacity),
+                capacity,
+                channels,
+                underruns: 0,
+                starved: false,
+                last: vec![0.0; channels],
+            }),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.state().capacity
+    }
+
+    pub fn channels(&self) -> usize {
+        self.state().channels
+    }
+
+    /// Samples queued and not yet played
+    pub fn len(&self) -> usize {
+        self.state().samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queue `samples`. A full ring drops its oldest samples to make room,
+    /// so latency stays bounded when the device falls behind, a whole frame
+    /// at a time. Returns how many were dropped.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let mut state = self.state();
+        let excess = (state.samples.len() + samples.len()).saturating_sub(state.capacity);
+        let excess = excess.next_multiple_of(state.channels);
+        let from_queue = excess.min(state.samples.len());
+        state.samples.drain(..from_queue);
+        let skip = (excess - from_queue).min(samples.len());
+        state.samples.extend(&samples[skip..]);
+        excess
+    }
+
+    /// Fill `out` from the queue and return how many samples were real. On
+    /// an underrun the rest holds the last frame played, which is quieter
+    /// than a jump to silence.
+    pub fn pop(&self, out: &mut [f32]) -> usize {
+        let mut state = self.state();
+        let channels = state.channels;
+        let count = out.len().min(state.samples.len());
+        let count = count - count % channels;
+        for (sample, queued) in out.iter_mut().zip(state.samples.drain(..count)) {
+            *sample = queued;
+        }
+        if count > 0 {
+            state.last.copy_from_slice(&out[count - channels..count]);
+        }
+        for (sample, &last) in out[count..].iter_mut().zip(state.last.iter().cycle()) {
+            *sample = last;
+        }
+
+        let starved = count < out.len();
+        if starved && !state.starved {
+            state.underruns += 1;
+        }
+        state.starved = starved;
+        count
+    }
+
+    /// Times the queue has run dry, counting a run of short pops once
+    pub fn underruns(&self) -> u64 {
+        self.state().underruns
+    }
+
+    // The device callback never panics while holding the lock, but a
+    // poisoned queue is still just samples
+    fn state(&self) -> MutexGuard<'_, RingState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,208 @@
+// src/frontend/keymap.rs
+// Keyboard keys bound to player 1's controller buttons and console hotkeys, independent of the windowing library
+
+use crate::nes::apu::Channel;
+use crate::nes::controller::InputState;
+
+/// A keyboard key by position, as the frontends can bind it. Letters are
+/// lowercase `'a'..='z'` and digits `0..=9`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Letter(char),
+    Digit(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+}
+
+impl Key {
+    /// Key by its config name: a letter or digit, an arrow (`up`, `down`,
+    /// `left`, `right`), `enter`, `space`, `tab`, `backspace`, or `lshift`,
+    /// `rshift`, `lctrl`, `rctrl`, `lalt`, `ralt`
+    pub fn named(name: &str) -> Option<Key> {
+        let name = name.to_ascii_lowercase();
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return match c {
+                'a'..='z' => Some(Key::Letter(c)),
+                '0'..='9' => Some(Key::Digit(c as u8 - b'0')),
+                _ => None,
+            };
+        }
+        let key = match name.as_str() {
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "enter" | "return" => Key::Enter,
+            "space" => Key::Space,
+            "tab" => Key::Tab,
+            "backspace" => Key::Backspace,
+            "lshift" => Key::LeftShift,
+            "rshift" => Key::RightShift,
+            "lctrl" => Key::LeftCtrl,
+            "rctrl" => Key::RightCtrl,
+            "lalt" => Key::LeftAlt,
+            "ralt" => Key::RightAlt,
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+/// Console actions on the keyboard besides the controller: the Vs. System
+/// coin slots and service button, and muting or soloing sound channels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Hotkey {
+    Coin1,
+    Coin2,
+    Service,
+    Mute(Channel),
+    Solo(Channel),
+    UnmuteAll,
+}
+
+impl Hotkey {
+    /// Hotkey by its config name: `coin1`, `coin2`, `service`, `unmute`, or
+    /// `mute-` or `solo-` and a channel name, e.g. `mute-triangle`
+    pub fn named(name: &str) -> Option<Hotkey> {
+        let name = name.to_ascii_lowercase();
+        if let Some(channel) = name.strip_prefix("mute-") {
+            return Channel::named(channel).map(Hotkey::Mute);
+        }
+        if let Some(channel) = name.strip_prefix("solo-") {
+            return Channel::named(channel).map(Hotkey::Solo);
+        }
+        let hotkey = match name.as_str() {
+            "coin1" => Hotkey::Coin1,
+            "coin2" => Hotkey::Coin2,
+            "service" => Hotkey::Service,
+            "unmute" => Hotkey::UnmuteAll,
+            _ => return None,
+        };
+        Some(hotkey)
+    }
+}
+
+/// Something a key can be bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Button(InputState),
+    Hotkey(Hotkey),
+}
+
+/// Which key holds each of the eight buttons, and which fires each hotkey.
+/// Defaults to the arrows for the D-pad, Z for B, X for A, Enter for Start
+/// and right Shift for Select, with coins on 5 and 6 and service on 9. 1-4
+/// mute and Q, W, E, R solo pulse 1, pulse 2, triangle and noise, and 0
+/// clears them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyMap {
+    keys: [Key; 8], // By button bit, in `InputState` order
+    hotkeys: Vec<(Hotkey, Key)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            keys: [
+                Key::Letter('x'),
+                Key::Letter('z'),
+                Key::RightShift,
+                Key::Enter,
+                Key::Up,
+                Key::Down,
+                Key::Left,
+                Key::Right,
+            ],
+            hotkeys: vec![
+                (Hotkey::Coin1, Key::Digit(5)),
+                (Hotkey::Coin2, Key::Digit(6)),
+                (Hotkey::Service, Key::Digit(9)),
+                (Hotkey::Mute(Channel::Pulse1), Key::Digit(1)),
+                (Hotkey::Mute(Channel::Pulse2), Key::Digit(2)),
+                (Hotkey::Mute(Channel::Triangle), Key::Digit(3)),
+                (Hotkey::Mute(Channel::Noise), Key::Digit(4)),
+                (Hotkey::Solo(Channel::Pulse1), Key::Letter('q')),
+                (Hotkey::Solo(Channel::Pulse2), Key::Letter('w')),
+                (Hotkey::Solo(Channel::Triangle), Key::Letter('e')),
+                (Hotkey::Solo(Channel::Noise), Key::Letter('r')),
+                (Hotkey::UnmuteAll, Key::Digit(0)),
+            ],
+        }
+    }
+}
+
+impl KeyMap {
+    /// Button by its config name: `a`, `b`, `select`, `start`, `up`,
+    /// `down`, `left` or `right`
+    pub fn button_named(name: &str) -> Option<InputState> {
+        InputState::from_name(&name.to_ascii_uppercase())
+    }
+
+    /// A button or hotkey by its config name
+    pub fn binding_named(name: &str) -> Option<Binding> {
+        Self::button_named(name)
+            .map(Binding::Button)
+            .or_else(|| Hotkey::named(name).map(Binding::Hotkey))
+    }
+
+    /// The key bound to `button`, the lowest if it's several flags
+    pub fn key(&self, button: InputState) -> Option<Key> {
+        self.keys.get(button.bits().trailing_zeros() as usize).copied()
+    }
+
+    /// Bind `button` to `key`. A key may hold several buttons.
+    pub fn set(&mut self, button: InputState, key: Key) {
+        for bit in button.iter() {
+            self.keys[bit.bits().trailing_zeros() as usize] = key;
+        }
+    }
+
+    /// The key that fires `hotkey`, if it has one
+    pub fn hotkey(&self, hotkey: Hotkey) -> Option<Key> {
+        self.hotkeys.iter().find(|&&(bound, _)| bound == hotkey).map(|&(_, key)| key)
+    }
+
+    /// Bind `hotkey` to `key`, in place of any key it had
+    pub fn set_hotkey(&mut self, hotkey: Hotkey, key: Key) {
+        self.hotkeys.retain(|&(bound, _)| bound != hotkey);
+        self.hotkeys.push((hotkey, key));
+    }
+
+    /// Bind a button or hotkey to `key`
+    pub fn bind(&mut self, binding: Binding, key: Key) {
+        match binding {
+            Binding::Button(button) => self.set(button, key),
+            Binding::Hotkey(hotkey) => self.set_hotkey(hotkey, key),
+        }
+    }
+
+    /// Each button with its key
+    pub fn bindings(&self) -> impl Iterator<Item = (InputState, Key)> + '_ {
+        self.keys.iter().enumerate().map(|(bit, &key)| (InputState::from_bits_truncate(1 << bit), key))
+    }
+
+    /// The buttons held, given which keys are down
+    pub fn buttons(&self, pressed: impl Fn(Key) -> bool) -> InputState {
+        self.bindings()
+            .filter(|&(_, key)| pressed(key))
+            .fold(InputState::empty(), |held, (button, _)| held | button)
+    }
+
+    /// The hotkeys whose keys are down
+    pub fn hotkeys(&self, pressed: impl Fn(Key) -> bool) -> Vec<Hotkey> {
+        self.hotkeys.iter().filter(|&&(_, key)| pressed(key)).map(|&(hotkey, _)| hotkey).collect()
+    }
+}
@@ -0,0 +1,117 @@
+// src/frontend/mod.rs
+// Windowed frontends that run a `Nes` in real time: SDL2 with the `sdl` feature, winit and wgpu with `pixels`
+
+mod keymap;
+#[cfg(feature = "pixels")]
+mod pixels;
+#[cfg(feature = "sdl")]
+mod sdl;
+
+use std::time::Duration;
+
+use log::info;
+use thiserror::Error;
+
+use crate::nes::clock::Region;
+use crate::nes::cpu::CpuError;
+use crate::nes::Nes;
+
+pub use keymap::{Binding, Hotkey, Key, KeyMap};
+#[cfg(feature = "pixels")]
+pub use self::pixels::run_pixels;
+#[cfg(feature = "sdl")]
+pub use sdl::run_sdl;
+
+/// Window and run settings shared by the frontends
+#[derive(Clone, Debug)]
+pub struct FrontendOptions {
+    /// Window size as a multiple of the frame, 3 by default
+    pub scale: u32,
+    pub fullscreen: bool,
+    /// Audio device by name, or the default output
+    pub audio_device: Option<String>,
+    /// Recover from a jammed CPU by resetting instead of stopping
+    pub reset_on_jam: bool,
+    /// Player 1's controller and the hotkeys on the keyboard
+    pub keys: KeyMap,
+}
+
+impl Default for FrontendOptions {
+    fn default() -> Self {
+        Self {
+            scale: 3,
+            fullscreen: false,
+            audio_device: None,
+            reset_on_jam: false,
+            keys: KeyMap::default(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FrontendError {
+    /// The window, renderer or input system failed to start
+    #[error("video: {0}")]
+    Video(String),
+    #[error(transparent)]
+    Cpu(#[from] CpuError),
+}
+
+/// The hotkeys held last frame, so that holding one down acts only once
+#[derive(Clone, Debug, Default)]
+pub struct HotkeyState {
+    held: Vec<Hotkey>,
+}
+
+impl HotkeyState {
+    /// Act on the hotkeys held this frame: a coin drops and a mute or solo
+    /// toggles when its key goes down, and the service button is held for as
+    /// long as its key is
+    pub fn update(&mut self, nes: &mut Nes, held: Vec<Hotkey>) {
+        let bus = &mut nes.cpu.bus;
+        for &hotkey in held.iter().filter(|hotkey| !self.held.contains(hotkey)) {
+            match hotkey {
+                Hotkey::Coin1 | Hotkey::Coin2 => {
+                    if let Some(vs) = &mut bus.vs {
+                        vs.insert_coin((hotkey == Hotkey::Coin2) as usize);
+                    }
+                }
+                Hotkey::Service => {}
+                Hotkey::Mute(channel) => {
+                    bus.apu.set_muted(channel, !bus.apu.muted(channel));
+                    info!("{:?} {}", channel, if bus.apu.muted(channel) { "muted" } else { "unmuted" });
+                }
+                Hotkey::Solo(channel) => {
+                    bus.apu.set_solo(channel, !bus.apu.soloed(channel));
+                    info!("{:?} solo {}", channel, if bus.apu.soloed(channel) { "on" } else { "off" });
+                }
+                Hotkey::UnmuteAll => {
+                    bus.apu.unmute_all();
+                    info!("All channels unmuted");
+                }
+            }
+        }
+        if let Some(vs) = &mut bus.vs {
+            vs.set_service(held.contains(&Hotkey::Service));
+        }
+        self.held = held;
+    }
+}
+
+/// Real time the console takes to draw one frame, a little under 1/60s on
+/// NTSC and 1/50s on PAL
+pub fn frame_period(region: Region) -> Duration {
+    let dots = region.scanlines() as f64 * 341.0;
+    Duration::from_secs_f64(dots * region.ppu_divider() as f64 / region.master_hz() as f64)
+}
+
+/// Play `nes` in whichever frontend is built in, SDL2 first
+#[cfg(feature = "sdl")]
+pub fn run(nes: &mut Nes, options: &FrontendOptions) -> Result<(), FrontendError> {
+    run_sdl(nes, options)
+}
+
+#[cfg(all(feature = "pixels", not(feature = "sdl")))]
+pub fn run(nes: &mut Nes, options: &FrontendOptions) -> Result<(), FrontendError> {
+    run_pixels(nes, options)
+}
@@ -0,0 +1,237 @@
+// src/frontend/pixels.rs
+// winit window with a wgpu frame buffer through `pixels`, for builds that don't link SDL2
+
+use std::collections::HashSet;
+use std::time::Instant;
+
+use log::{error, info};
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, Event, KeyEvent, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Fullscreen, WindowBuilder};
+
+use super::{frame_period, FrontendError, FrontendOptions, HotkeyState, Key};
+use crate::nes::cpu::CpuError;
+use crate::nes::ppu::PixelFormat;
+use crate::nes::Nes;
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+];
+
+const DIGITS: [KeyCode; 10] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+fn video_error(err: impl ToString) -> FrontendError {
+    FrontendError::Video(err.to_string())
+}
+
+/// Play `nes` in a window until it's closed or Escape is pressed, the same
+/// way `run_sdl` does. Sound goes through the `audio` feature's device
+/// output when that's built in; otherwise this runs silent.
+pub fn run_pixels(nes: &mut Nes, options: &FrontendOptions) -> Result<(), FrontendError> {
+    let event_loop = EventLoop::new().map_err(video_error)?;
+    let window = WindowBuilder::new()
+        .with_title("alphaNES")
+        .with_inner_size(LogicalSize::new(256 * options.scale, 240 * options.scale))
+        .with_min_inner_size(LogicalSize::new(256, 240))
+        .build(&event_loop)
+        .map_err(video_error)?;
+    if options.fullscreen {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    let size = window.inner_size();
+    let mut pixels = Pixels::new(256, 240, SurfaceTexture::new(size.width, size.height, &window))
+        .map_err(video_error)?;
+
+    #[cfg(feature = "audio")]
+    let audio = audio::open(nes, options.audio_device.as_deref());
+    #[cfg(feature = "audio")]
+    let rate_control = crate::audio::DynamicRate::default();
+    let mut samples = Vec::new();
+
+    let mut pressed = HashSet::new();
+    let mut hotkeys = HotkeyState::default();
+    let mut result = Ok(());
+    let period = frame_period(nes.region());
+    let mut next_frame = Instant::now();
+    event_loop
+        .run(|event, target| match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => target.exit(),
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(code),
+                            state,
+                            ..
+                        },
+                    ..
+                } => {
+                    if code == KeyCode::Escape {
+                        target.exit();
+                    }
+                    if state == ElementState::Pressed {
+                        pressed.insert(code);
+                    } else {
+                        pressed.remove(&code);
+                    }
+                }
+                WindowEvent::Resized(size) => {
+                    if let Err(err) = pixels.resize_surface(size.width, size.height) {
+                        result = Err(video_error(err));
+                        target.exit();
+                    }
+                }
+                WindowEvent::RedrawRequested => {
+                    // The buffer follows the frame, which the NTSC filter widens
+                    let frame = nes.frame(PixelFormat::Rgba8888);
+                    let (width, height) = (frame.width as u32, frame.height as u32);
+                    if pixels.texture().size() != wgpu_size(width, height) {
+                        if let Err(err) = pixels.resize_buffer(width, height) {
+                            result = Err(video_error(err));
+                            return target.exit();
+                        }
+                    }
+                    pixels.frame_mut().copy_from_slice(frame.data);
+                    if let Err(err) = pixels.render() {
+                        result = Err(video_error(err));
+                        target.exit();
+                    }
+                }
+                _ => {}
+            },
+            Event::AboutToWait => {
+                let now = Instant::now();
+                if now >= next_frame {
+                    let down = |key: Key| keycode(key).is_some_and(|code| pressed.contains(&code));
+                    nes.set_input(0, options.keys.buttons(down));
+                    hotkeys.update(nes, options.keys.hotkeys(down));
+                    match nes.run_frame() {
+                        Ok(()) => {}
+                        Err(CpuError::Break(event)) => info!("Debugger: {}", event),
+                        Err(err @ CpuError::Jammed { .. }) if options.reset_on_jam => {
+                            error!("{}", err);
+                            info!("Resetting after CPU jam");
+                            nes.reset();
+                        }
+                        Err(err) => {
+                            result = Err(err.into());
+                            return target.exit();
+                        }
+                    }
+                    window.request_redraw();
+
+                    samples.clear();
+                    nes.take_samples(&mut samples);
+                    #[cfg(feature = "audio")]
+                    if let Some(audio) = &audio {
+                        audio.queue(&samples);
+                        nes.set_rate_adjust(rate_control.adjust(audio.queued(), audio.capacity()));
+                    }
+
+                    // Catch up after a stall rather than racing to make it back
+                    next_frame += period;
+                    if now > next_frame + period {
+                        next_frame = now;
+                    }
+                }
+                target.set_control_flow(ControlFlow::WaitUntil(next_frame));
+            }
+            _ => {}
+        })
+        .map_err(video_error)?;
+    result
+}
+
+fn keycode(key: Key) -> Option<KeyCode> {
+    let code = match key {
+        Key::Letter(letter) => return LETTERS.get((letter as usize).wrapping_sub('a' as usize)).copied(),
+        Key::Digit(digit) => return DIGITS.get(digit as usize).copied(),
+        Key::Up => KeyCode::ArrowUp,
+        Key::Down => KeyCode::ArrowDown,
+        Key::Left => KeyCode::ArrowLeft,
+        Key::Right => KeyCode::ArrowRight,
+        Key::Enter => KeyCode::Enter,
+        Key::Space => KeyCode::Space,
+        Key::Tab => KeyCode::Tab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::LeftShift => KeyCode::ShiftLeft,
+        Key::RightShift => KeyCode::ShiftRight,
+        Key::LeftCtrl => KeyCode::ControlLeft,
+        Key::RightCtrl => KeyCode::ControlRight,
+        Key::LeftAlt => KeyCode::AltLeft,
+        Key::RightAlt => KeyCode::AltRight,
+    };
+    Some(code)
+}
+
+fn wgpu_size(width: u32, height: u32) -> pixels::wgpu::Extent3d {
+    pixels::wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    }
+}
+
+#[cfg(feature = "audio")]
+mod audio {
+    use log::error;
+
+    use crate::audio::{AudioOutput, DEFAULT_LATENCY};
+    use crate::nes::Nes;
+
+    // Device output matching the APU's channels, with the APU moved to
+    // whatever rate the device settled on
+    pub(super) fn open(nes: &mut Nes, device: Option<&str>) -> Option<AudioOutput> {
+        let apu = &nes.cpu.bus.apu;
+        let channels = if apu.stereo() { 2 } else { 1 };
+        match AudioOutput::open(device, apu.sample_rate(), channels, DEFAULT_LATENCY) {
+            Ok(output) => {
+                nes.set_sample_rate(output.sample_rate());
+                Some(output)
+            }
+            Err(err) => {
+                error!("Audio disabled: {}", err);
+                None
+            }
+        }
+    }
+}
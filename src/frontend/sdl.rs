@@ -0,0 +1,189 @@
+// src/frontend/sdl.rs
+// SDL2 window, audio queue and keyboard driving a `Nes` frame by frame
+
+use std::thread;
+use std::time::Instant;
+
+use log::{error, info, warn};
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::event::Event;
+use sdl2::keyboard::{KeyboardState, Keycode, Scancode};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Texture;
+
+use super::{frame_period, FrontendError, FrontendOptions, HotkeyState, Key, KeyMap};
+use crate::audio::DynamicRate;
+use crate::nes::controller::InputState;
+use crate::nes::cpu::CpuError;
+use crate::nes::ppu::PixelFormat;
+use crate::nes::Nes;
+
+// Audio queued ahead of the device. Rate control aims for half of it; past
+// all of it the queue is dropped and restarted.
+const QUEUE_MS: u32 = 100;
+
+/// Play `nes` in a window until it's closed or Escape is pressed. Frames
+/// are paced to the console's refresh rate, with audio through SDL at
+/// whatever rate the device takes and the output mono or stereo as the
+/// APU is set up.
+pub fn run_sdl(nes: &mut Nes, options: &FrontendOptions) -> Result<(), FrontendError> {
+    let sdl = sdl2::init().map_err(FrontendError::Video)?;
+    let video = sdl.video().map_err(FrontendError::Video)?;
+    let mut window = video.window("alphaNES", 256 * options.scale, 240 * options.scale);
+    window.position_centered().resizable();
+    if options.fullscreen {
+        window.fullscreen_desktop();
+    }
+    let window = window.build().map_err(|err| FrontendError::Video(err.to_string()))?;
+    let mut canvas = window.into_canvas().build().map_err(|err| FrontendError::Video(err.to_string()))?;
+    // Letterboxed to the console's picture, whatever the window's shape
+    canvas.set_logical_size(256, 240).map_err(|err| FrontendError::Video(err.to_string()))?;
+    let texture_creator = canvas.texture_creator();
+    let mut texture: Option<Texture> = None;
+    let mut events = sdl.event_pump().map_err(FrontendError::Video)?;
+
+    // Sound is optional: a machine without a device still plays silently
+    let audio = open_audio(&sdl, nes, options.audio_device.as_deref());
+    if let Some(audio) = &audio {
+        nes.set_sample_rate(audio.spec().freq as u32);
+        audio.resume();
+    }
+    let rate_control = DynamicRate::default();
+    let mut samples = Vec::new();
+
+    let mut hotkeys = HotkeyState::default();
+    let period = frame_period(nes.region());
+    let mut next_frame = Instant::now();
+    'running: loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running,
+                _ => {}
+            }
+        }
+        let keyboard = events.keyboard_state();
+        nes.set_input(0, buttons(&options.keys, &keyboard));
+        hotkeys.update(nes, options.keys.hotkeys(|key| pressed(key, &keyboard)));
+
+        match nes.run_frame() {
+            Ok(()) => {}
+            Err(CpuError::Break(event)) => info!("Debugger: {}", event),
+            Err(err @ CpuError::Jammed { .. }) if options.reset_on_jam => {
+                error!("{}", err);
+                info!("Resetting after CPU jam");
+                nes.reset();
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        // The frame is 256 pixels wide, or wider through the NTSC filter,
+        // and stretched to the same picture either way
+        let frame = nes.frame(PixelFormat::Rgba8888);
+        let (width, height) = (frame.width as u32, frame.height as u32);
+        if texture.as_ref().is_none_or(|texture| {
+            let query = texture.query();
+            (query.width, query.height) != (width, height)
+        }) {
+            let created = texture_creator
+                .create_texture_streaming(PixelFormatEnum::RGBA32, width, height)
+                .map_err(|err| FrontendError::Video(err.to_string()))?;
+            texture = Some(created);
+        }
+        if let Some(texture) = &mut texture {
+            texture
+                .update(None, frame.data, frame.width * 4)
+                .map_err(|err| FrontendError::Video(err.to_string()))?;
+            canvas.clear();
+            canvas.copy(texture, None, None).map_err(FrontendError::Video)?;
+            canvas.present();
+        }
+
+        samples.clear();
+        nes.take_samples(&mut samples);
+        if let Some(audio) = &audio {
+            queue_samples(audio, &samples, nes, &rate_control);
+        }
+
+        // Catch up after a stall rather than racing to make it back
+        next_frame += period;
+        let now = Instant::now();
+        match next_frame.checked_duration_since(now) {
+            Some(delay) => thread::sleep(delay),
+            None if now - next_frame > period => next_frame = now,
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+fn buttons(keys: &KeyMap, keyboard: &KeyboardState) -> InputState {
+    keys.buttons(|key| pressed(key, keyboard))
+}
+
+fn pressed(key: Key, keyboard: &KeyboardState) -> bool {
+    scancode(key).is_some_and(|code| keyboard.is_scancode_pressed(code))
+}
+
+fn scancode(key: Key) -> Option<Scancode> {
+    let code = match key {
+        Key::Letter(letter) => return Scancode::from_name(&letter.to_ascii_uppercase().to_string()),
+        Key::Digit(digit) => return Scancode::from_name(&digit.to_string()),
+        Key::Up => Scancode::Up,
+        Key::Down => Scancode::Down,
+        Key::Left => Scancode::Left,
+        Key::Right => Scancode::Right,
+        Key::Enter => Scancode::Return,
+        Key::Space => Scancode::Space,
+        Key::Tab => Scancode::Tab,
+        Key::Backspace => Scancode::Backspace,
+        Key::LeftShift => Scancode::LShift,
+        Key::RightShift => Scancode::RShift,
+        Key::LeftCtrl => Scancode::LCtrl,
+        Key::RightCtrl => Scancode::RCtrl,
+        Key::LeftAlt => Scancode::LAlt,
+        Key::RightAlt => Scancode::RAlt,
+    };
+    Some(code)
+}
+
+fn open_audio(sdl: &sdl2::Sdl, nes: &Nes, device: Option<&str>) -> Option<AudioQueue<f32>> {
+    let channels = if nes.cpu.bus.apu.stereo() { 2 } else { 1 };
+    let desired = AudioSpecDesired {
+        freq: Some(nes.cpu.bus.apu.sample_rate() as i32),
+        channels: Some(channels),
+        samples: Some(1024),
+    };
+    let opened = sdl.audio().and_then(|audio| audio.open_queue(device, &desired));
+    match opened {
+        Ok(queue) => {
+            let spec = queue.spec();
+            info!("Audio output: {}Hz, {} channel(s)", spec.freq, spec.channels);
+            Some(queue)
+        }
+        Err(err) => {
+            error!("Audio disabled: {}", err);
+            None
+        }
+    }
+}
+
+// Queue a frame's samples and steer the output rate to keep the queue
+// about half full
+fn queue_samples(audio: &AudioQueue<f32>, samples: &[f32], nes: &mut Nes, rate_control: &DynamicRate) {
+    let spec = audio.spec();
+    let capacity = (spec.freq as u32 * QUEUE_MS / 1000) as usize * spec.channels as usize;
+    let queued = audio.size() as usize / size_of::<f32>();
+    if queued > capacity {
+        warn!("Audio queue full; dropped {} samples", queued);
+        audio.clear();
+    }
+    if let Err(err) = audio.queue_audio(samples) {
+        warn!("Failed to queue audio: {}", err);
+    }
+    let queued = audio.size() as usize / size_of::<f32>();
+    nes.set_rate_adjust(rate_control.adjust(queued, capacity));
+}
@@ -1 +1,3 @@
-pub mod cpu;
\ No newline at end of file
+pub mod audio;
+pub mod frontend;
+pub mod nes;
@@ -1,170 +1,638 @@
 // src/main.rs
-use log::{debug, info, warn};
-use nes::cpu::{Bus, Cpu2A03};
-
-const RAM_SIZE: usize = 2048; // 2KB NES RAM
-
-struct NesBus {
-    ram: [u8; RAM_SIZE],
-    prg_rom: Vec<u8>,      // Cartridge program ROM
-    ppu_registers: [u8; 8],// PPU register placeholder
-    frame_counter: usize,  // For simulating NMIs
-    cycles: usize,         // Global cycle counter
-}
-
-impl NesBus {
-    fn new() -> Self {
-        // Initialize with dummy PRG ROM (test program)
-        let mut prg_rom = vec![0; 0x8000];
-        
-        // Simple test program:
-        // Reset handler: LDA #$FF, STA $0000, JMP $8000
-        prg_rom[0] = 0xA9; // LDA Immediate
-        prg_rom[1] = 0xFF;
-        prg_rom[2] = 0x8D; // STA Absolute
-        prg_rom[3] = 0x00;
-        prg_rom[4] = 0x00;
-        prg_rom[5] = 0x4C; // JMP Absolute
-        prg_rom[6] = 0x00;
-        prg_rom[7] = 0x80;
-
-        Self {
-            ram: [0; RAM_SIZE],
-            prg_rom,
-            ppu_registers: [0; 8],
-            frame_counter: 0,
-            cycles: 0,
-        }
-    }
-
-    fn handle_ppu(&mut self, cycles: usize) {
-        // Simulate PPU operation (3 PPU cycles per CPU cycle)
-        let ppu_cycles = cycles * 3;
-        self.frame_counter += ppu_cycles;
-        
-        // Generate NMI every ~29780 cycles (60Hz frame rate)
-        if self.frame_counter >= 29780 {
-            self.frame_counter -= 29780;
-            // Normally this would trigger the NMI in the CPU
-        }
-    }
-
-    fn handle_apu(&mut self, cycles: usize) {
-        // Simulate APU operation (placeholder)
-        let _ = cycles;
-    }
-}
-
-impl Bus for NesBus {
-    fn read(&mut self, addr: u16) -> u8 {
-        match addr {
-            // RAM (mirrored every 2KB)
-            0x0000..=0x1FFF => {
-                let mirrored_addr = addr as usize % 0x0800;
-                self.ram[mirrored_addr]
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+#[cfg(feature = "audio")]
+use alphanes::audio::{AudioOutput, DynamicRate, DEFAULT_LATENCY};
+#[cfg(feature = "audio")]
+use alphanes::nes::apu::DEFAULT_SAMPLE_RATE;
+#[cfg(any(feature = "sdl", feature = "pixels"))]
+use alphanes::frontend;
+use alphanes::frontend::{Binding, FrontendOptions, Key, KeyMap};
+use alphanes::nes::apu::{Channel, Filter, Ultrasonic};
+use alphanes::nes::cart::{self, Nsf, Rom};
+use alphanes::nes::clock::Region;
+use alphanes::nes::cpu::{self, CpuError};
+use alphanes::nes::nsf::NsfPlayer;
+use alphanes::nes::ppu::{Palette, NAMETABLE_VIEW_HEIGHT, NAMETABLE_VIEW_WIDTH, PATTERN_VIEW_SIZE};
+use alphanes::nes::Nes;
+use clap::{value_parser, Args, Parser, Subcommand, ValueEnum};
+use log::{debug, error, info, warn, LevelFilter};
+
+// Line-based commands from stdin, collected on a background thread
+fn stdin_commands() -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if tx.send(line.trim().to_lowercase()).is_err() {
+                break;
             }
-            
-            // PPU registers (mirrored every 8 bytes)
-            0x2000..=0x3FFF => {
-                let reg = (addr - 0x2000) % 8;
-                self.ppu_registers[reg as usize]
+        }
+    });
+    rx
+}
+
+// Binary PPM of `0x00RRGGBB` pixels, for debug view dumps
+fn write_ppm(path: &Path, width: usize, height: usize, pixels: &[u32]) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+    for pixel in pixels {
+        file.write_all(&pixel.to_be_bytes()[1..])?;
+    }
+    file.flush()
+}
+
+// Sound output on `device`, or the default one, for mono or stereo
+// samples. Without the `audio` feature there is nothing to open.
+#[cfg(feature = "audio")]
+fn open_audio(device: Option<&str>, channels: u16) -> Option<AudioOutput> {
+    match AudioOutput::open(device, DEFAULT_SAMPLE_RATE, channels, DEFAULT_LATENCY) {
+        Ok(output) => Some(output),
+        Err(err) => {
+            error!("Audio disabled: {}", err);
+            None
+        }
+    }
+}
+
+// The channel a mute or solo command names, by number for the 2A03's own
+// four or by name for any, e.g. `2` or `vrc6`
+fn parse_channel(name: &str) -> Option<Channel> {
+    match name.parse::<usize>() {
+        Ok(number) => Channel::INTERNAL.get(number.checked_sub(1)?).copied(),
+        Err(_) => Channel::named(name),
+    }
+}
+
+// `--pan` value: a channel name and a position from -1.0 (left) to 1.0 (right)
+fn parse_pan(value: &str) -> Result<(Channel, f32), String> {
+    let (name, position) = value.split_once('=').ok_or("expected <channel>=<position>, e.g. pulse1=-0.5")?;
+    let channel = Channel::named(name).ok_or_else(|| format!("unknown channel {}", name))?;
+    let position = position
+        .parse::<f32>()
+        .ok()
+        .filter(|pan| (-1.0..=1.0).contains(pan))
+        .ok_or_else(|| format!("position {} isn't between -1.0 and 1.0", position))?;
+    Ok((channel, position))
+}
+
+// `--key` value: a controller button or hotkey and the key that holds it
+fn parse_key(value: &str) -> Result<(Binding, Key), String> {
+    let (name, key) = value.split_once('=').ok_or("expected <button>=<key>, e.g. a=s")?;
+    let binding = KeyMap::binding_named(name).ok_or_else(|| {
+        format!(
+            "unknown button {}; expected a, b, select, start, up, down, left, right, coin1, coin2, service, \
+             mute-<channel>, solo-<channel> or unmute",
+            name
+        )
+    })?;
+    let key = Key::named(key).ok_or_else(|| format!("unknown key {}", key))?;
+    Ok((binding, key))
+}
+
+// Audio-only player loop for .nsf files. Reads track controls from stdin:
+// `n` next, `p` previous, `q` quit. `m1`-`m4` mute and `s1`-`s4` solo
+// pulse 1, pulse 2, triangle and noise, and `m` or `s` with a channel name
+// (`mvrc6`, `sfds`) any channel; `u` clears them. Any pans switch the output
+// to stereo.
+fn run_nsf(
+    path: &str,
+    image: &[u8],
+    audio_device: Option<&str>,
+    filters: &[Filter],
+    pans: &[(Channel, f32)],
+    ultrasonic: Ultrasonic,
+) {
+    let nsf = match Nsf::from_bytes(image) {
+        Ok(nsf) => nsf,
+        Err(err) => {
+            error!("Failed to load {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+    info!(
+        "NSF: \"{}\" by {} ({}), {} songs",
+        nsf.header.song_name, nsf.header.artist, nsf.header.copyright, nsf.header.total_songs
+    );
+
+    let rx = stdin_commands();
+    let mut player = NsfPlayer::new(nsf);
+    player.cpu.bus.apu.set_filters(filters);
+    player.cpu.bus.apu.triangle.set_ultrasonic(ultrasonic);
+    if !pans.is_empty() {
+        player.cpu.bus.apu.set_stereo(true);
+        for &(channel, pan) in pans {
+            player.cpu.bus.apu.set_pan(channel, pan);
+        }
+    }
+    info!("Playing song {}/{}", player.current_song(), player.header().total_songs);
+
+    #[cfg(feature = "audio")]
+    let audio = open_audio(audio_device, if pans.is_empty() { 1 } else { 2 });
+    #[cfg(feature = "audio")]
+    if let Some(audio) = &audio {
+        player.cpu.bus.apu.set_sample_rate(audio.sample_rate());
+    }
+    #[cfg(feature = "audio")]
+    let rate_control = DynamicRate::default();
+    #[cfg(not(feature = "audio"))]
+    if audio_device.is_some() {
+        warn!("Built without the audio feature; --audio-device ignored");
+    }
+    let mut samples = Vec::new();
+
+    let period = player.frame_period();
+    let mut next_frame = Instant::now();
+    loop {
+        while let Ok(command) = rx.try_recv() {
+            let apu = &mut player.cpu.bus.apu;
+            if let Some(channel) = command.strip_prefix('m').and_then(parse_channel) {
+                apu.set_muted(channel, !apu.muted(channel));
+                info!("{:?} {}", channel, if apu.muted(channel) { "muted" } else { "unmuted" });
+                continue;
             }
-            
-            // APU and I/O registers
-            0x4000..=0x4017 => {
-                warn!("APU/I/O read from {:04X} not implemented", addr);
-                0
+            if let Some(channel) = command.strip_prefix('s').and_then(parse_channel) {
+                apu.set_solo(channel, !apu.soloed(channel));
+                info!("{:?} solo {}", channel, if apu.soloed(channel) { "on" } else { "off" });
+                continue;
             }
-            
-            // Cartridge space (PRG ROM)
-            0x4020..=0xFFFF => {
-                let mut effective_addr = addr as usize - 0x4020;
-                if effective_addr >= self.prg_rom.len() {
-                    effective_addr %= self.prg_rom.len();
+            match command.as_str() {
+                "n" => player.next_track(),
+                "p" => player.previous_track(),
+                "u" => {
+                    apu.unmute_all();
+                    info!("All channels unmuted");
+                    continue;
                 }
-                self.prg_rom[effective_addr]
-            }
-            
-            _ => {
-                warn!("Unhandled read from {:04X}", addr);
-                0
+                "q" => return,
+                _ => continue,
             }
+            info!("Playing song {}/{}", player.current_song(), player.header().total_songs);
+        }
+
+        player.play_frame();
+        samples.clear();
+        player.take_samples(&mut samples);
+        #[cfg(feature = "audio")]
+        if let Some(audio) = &audio {
+            audio.queue(&samples);
+            let adjust = rate_control.adjust(audio.queued(), audio.capacity());
+            player.cpu.bus.apu.set_rate_adjust(adjust);
+        }
+
+        next_frame += period;
+        if let Some(delay) = next_frame.checked_duration_since(Instant::now()) {
+            thread::sleep(delay);
         }
     }
+}
 
-    fn write(&mut self, addr: u16, data: u8) {
-        match addr {
-            // RAM
-            0x0000..=0x1FFF => {
-                let mirrored_addr = addr as usize % 0x0800;
-                self.ram[mirrored_addr] = data;
-            }
-            
-            // PPU registers
-            0x2000..=0x3FFF => {
-                let reg = (addr - 0x2000) % 8;
-                self.ppu_registers[reg as usize] = data;
-                debug!("PPU write {:02X} to {:04X}", data, addr);
+// Windowless run for builds without a frontend: logs frames, and with
+// --trace prints every instruction, until the CPU jams, the --frames limit
+// is reached or `q` is entered
+fn run_headless(nes: &mut Nes, trace: bool, reset_on_jam: bool, frames: Option<u64>) {
+    // Controls from stdin: `q` quits, leaving the caller to write save RAM.
+    // On a Vs. System `c`/`c2` insert a coin, `s`/`r` press and release service
+    if let Some(vs) = &nes.cpu.bus.vs {
+        info!("Vs. System board with {:?} PPU; enter `c` or `c2` to insert a coin", vs.ppu());
+    }
+    info!("Enter `q` to quit");
+    let commands = stdin_commands();
+
+    loop {
+        while let Ok(command) = commands.try_recv() {
+            match (command.as_str(), &mut nes.cpu.bus.vs) {
+                ("q", _) => return,
+                ("c", Some(vs)) => vs.insert_coin(0),
+                ("c2", Some(vs)) => vs.insert_coin(1),
+                ("s", Some(vs)) => {
+                    vs.set_service(true);
+                    info!("Service button held; enter `r` to release");
+                }
+                ("r", Some(vs)) => vs.set_service(false),
+                _ => {}
             }
-            
-            // APU and I/O
-            0x4000..=0x4017 => {
-                debug!("APU/I/O write {:02X} to {:04X}", data, addr);
+        }
+
+        // nestest.log-format trace on stdout
+        if trace {
+            let ppu = (nes.cpu.bus.ppu.scanline, nes.cpu.bus.ppu.cycle);
+            println!("{}", cpu::trace_line(&mut nes.cpu, ppu));
+        }
+
+        // Execute one instruction with the PPU and mapper clocked alongside.
+        // A crashed game jams the CPU; report it rather than spin forever
+        let frame_complete = match nes.step() {
+            Ok((_, frame_complete)) => frame_complete,
+            Err(CpuError::Break(event)) => {
+                info!("Debugger: {}", event);
+                continue;
             }
-            
-            // Cartridge space
-            0x4020..=0xFFFF => {
-                warn!("Cartridge write {:02X} to {:04X} ignored", data, addr);
+            Err(err @ CpuError::Jammed { .. }) => {
+                error!("{}", err);
+                if !reset_on_jam {
+                    return;
+                }
+                info!("Resetting after CPU jam");
+                nes.reset();
+                continue;
             }
-            
-            _ => {
-                warn!("Unhandled write {:02X} to {:04X}", data, addr);
+        };
+
+        if frame_complete {
+            let cpu = &nes.cpu;
+            debug!(
+                "Frame {} | Cycles: {} | PC: {:04X} A: {:02X} X: {:02X} Y: {:02X} SP: {:02X}",
+                nes.frame, cpu.cycles(), cpu.pc, cpu.a, cpu.x, cpu.y, cpu.sp
+            );
+            if frames.is_some_and(|limit| nes.frame >= limit) {
+                info!("Ran {} frames, exiting", nes.frame);
+                return;
             }
         }
     }
 }
 
+fn list_audio_devices() {
+    #[cfg(feature = "audio")]
+    match alphanes::audio::output_devices() {
+        Ok(devices) => devices.iter().for_each(|name| println!("{}", name)),
+        Err(err) => error!("{}", err),
+    }
+    #[cfg(not(feature = "audio"))]
+    error!("Built without the audio feature");
+}
+
+// The package description doubles as the --help blurb
+#[derive(Parser)]
+#[command(name = "alphaNES", version, about)]
+struct Cli {
+    /// Log verbosity: off, error, warn, info, debug or trace. Overrides RUST_LOG.
+    #[arg(long, global = true, value_name = "LEVEL")]
+    log_level: Option<LevelFilter>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a cartridge or NSF
+    Run(RunArgs),
+    /// Print what a ROM's header says and exit
+    Info {
+        /// .nes or .nsf, or a .zip/.gz holding one (archive.zip#entry picks a file)
+        rom: String,
+        /// IPS or BPS patch to apply first
+        #[arg(long, value_name = "FILE")]
+        patch: Option<PathBuf>,
+    },
+    /// List the audio devices --audio-device takes
+    AudioDevices,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// .nes or .nsf, or a .zip/.gz holding one (archive.zip#entry picks a file)
+    rom: String,
+    /// IPS or BPS patch to apply. Without one, a .bps/.ips beside the ROM is used.
+    #[arg(long, value_name = "FILE")]
+    patch: Option<PathBuf>,
+    /// Built-in palette by name, or a 64- or 512-color .pal file
+    #[arg(long, value_name = "NAME|FILE")]
+    palette: Option<String>,
+    /// Force NTSC or PAL timing over the ROM header
+    #[arg(long, value_enum)]
+    region: Option<RegionArg>,
+    /// Window size as a multiple of 256x240
+    #[arg(long, default_value_t = FrontendOptions::default().scale, value_parser = value_parser!(u32).range(1..=16))]
+    scale: u32,
+    /// Start in fullscreen
+    #[arg(long)]
+    fullscreen: bool,
+    /// Write the nametables to a PPM image on exit
+    #[arg(long, value_name = "OUT.PPM")]
+    dump_nametables: Option<PathBuf>,
+    /// Write the pattern tables to a PPM image on exit
+    #[arg(long, value_name = "OUT.PPM")]
+    dump_patterns: Option<PathBuf>,
+    /// Skip the PPU's power-on warm-up period
+    #[arg(long)]
+    no_ppu_warm_up: bool,
+    /// Reset instead of stopping when the CPU jams
+    #[arg(long)]
+    reset_on_jam: bool,
+    /// Print a nestest.log-style line per instruction, without a window
+    #[arg(long)]
+    trace: bool,
+    /// Stop after this many frames when running without a window
+    #[arg(long, value_name = "N")]
+    frames: Option<u64>,
+    /// Audio output device by name (see `audio-devices`)
+    #[arg(long, value_name = "NAME")]
+    audio_device: Option<String>,
+    /// Output filtering, after the console's analog path
+    #[arg(long, value_enum, default_value_t = FilterArg::Nes)]
+    audio_filter: FilterArg,
+    /// Stereo position of a channel from -1.0 (left) to 1.0 (right), e.g.
+    /// pulse1=-0.5. Repeatable; any pan switches the output to stereo.
+    #[arg(long, value_name = "CHANNEL=POSITION", value_parser = parse_pan)]
+    pan: Vec<(Channel, f32)>,
+    /// What the triangle does at ultrasonic periods
+    #[arg(long, value_enum, default_value_t = UltrasonicArg::Accurate)]
+    ultrasonic_triangle: UltrasonicArg,
+    /// Bind a controller button or hotkey to a key, e.g. a=s, start=space,
+    /// coin1=c or mute-vrc6=7. Repeatable; unbound buttons keep the defaults
+    /// of arrows, Z (B), X (A), Enter (Start) and right Shift (Select), the
+    /// coin1, coin2 and service hotkeys 5, 6 and 9, mute-<channel> on 1-4
+    /// and solo-<channel> on Q-R for the 2A03's four channels, and unmute
+    /// on 0.
+    #[arg(long, value_name = "BUTTON=KEY", value_parser = parse_key)]
+    key: Vec<(Binding, Key)>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RegionArg {
+    Ntsc,
+    Pal,
+}
+
+impl From<RegionArg> for Region {
+    fn from(region: RegionArg) -> Self {
+        match region {
+            RegionArg::Ntsc => Region::Ntsc,
+            RegionArg::Pal => Region::Pal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FilterArg {
+    Nes,
+    Famicom,
+    #[value(name = "none")]
+    Off,
+}
+
+impl FilterArg {
+    fn filters(self) -> &'static [Filter] {
+        match self {
+            FilterArg::Nes => &Filter::NES,
+            FilterArg::Famicom => &Filter::FAMICOM,
+            FilterArg::Off => &[],
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum UltrasonicArg {
+    Accurate,
+    Silence,
+    Fade,
+}
+
+impl From<UltrasonicArg> for Ultrasonic {
+    fn from(mode: UltrasonicArg) -> Self {
+        match mode {
+            UltrasonicArg::Accurate => Ultrasonic::Accurate,
+            UltrasonicArg::Silence => Ultrasonic::Silence,
+            UltrasonicArg::Fade => Ultrasonic::Fade,
+        }
+    }
+}
+
 fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let cli = Cli::parse();
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if let Some(level) = cli.log_level {
+        logger.filter_level(level);
+    }
+    logger.init();
+
+    match cli.command {
+        Command::Run(args) => run(args),
+        Command::Info { rom, patch } => print_info(&rom, patch),
+        Command::AudioDevices => list_audio_devices(),
+    }
+}
+
+// Read the ROM, unpacking archives in memory, and apply its patch. Returns
+// the image and the outer file's path, which the .sav file follows.
+fn load_image(rom_path: &str, patch: Option<PathBuf>) -> (PathBuf, Vec<u8>) {
+    let (file_path, inner_path) = cart::split_inner_path(rom_path);
+    let mut image = match cart::read_image(&file_path, inner_path.as_deref()) {
+        Ok(image) => image,
+        Err(err) => {
+            error!("Failed to load {}: {}", rom_path, err);
+            process::exit(1);
+        }
+    };
+
+    // Patches apply to the raw image, so they may rewrite the header too.
+    // Without --patch, a .bps/.ips beside the ROM is soft-patched in.
+    let patch_path = patch.or_else(|| {
+        let found = cart::adjacent_patch(&file_path);
+        if let Some(path) = &found {
+            info!("Found soft patch {} next to the ROM", path.display());
+        }
+        found
+    });
+    if let Some(patch_path) = patch_path {
+        let patched = fs::read(&patch_path)
+            .map_err(|err| err.to_string())
+            .and_then(|patch| cart::apply_patch(&image, &patch).map_err(|err| err.to_string()));
+        match patched {
+            Ok(patched) => {
+                info!("Applied patch {}", patch_path.display());
+                image = patched;
+            }
+            Err(err) => {
+                error!("Failed to apply patch {}: {}", patch_path.display(), err);
+                process::exit(1);
+            }
+        }
+    }
+    (file_path, image)
+}
+
+fn load_rom(rom_path: &str, image: &[u8]) -> Rom {
+    match Rom::from_bytes(image) {
+        Ok(rom) => rom,
+        Err(err) => {
+            error!("Failed to load {}: {}", rom_path, err);
+            process::exit(1);
+        }
+    }
+}
+
+// What the header (and the ROM database) says about a cartridge
+fn rom_summary(rom_path: &str, rom: &Rom) -> Vec<String> {
+    let mut lines = vec![format!(
+        "Loaded {} ({:?}, {:?}): {}KB PRG, {}KB CHR, {}KB work RAM, mapper {}.{}, {:?}",
+        rom_path,
+        rom.format,
+        rom.region,
+        rom.prg_rom.len() / 1024,
+        rom.chr_rom.len() / 1024,
+        rom.work_ram_size() / 1024,
+        rom.mapper,
+        rom.submapper,
+        rom.console_type
+    )];
+    if let Some(correction) = &rom.db_correction {
+        lines.push(format!("Header corrected from ROM database: {}", correction));
+    }
+    if rom.battery {
+        lines.push("Cartridge has battery-backed RAM".to_string());
+    }
+    if rom.trainer.is_some() {
+        lines.push("Loading 512-byte trainer at $7000".to_string());
+    }
+    lines
+}
+
+fn print_info(rom_path: &str, patch: Option<PathBuf>) {
+    let (_, image) = load_image(rom_path, patch);
+    if Nsf::is_nsf(&image) {
+        match Nsf::from_bytes(&image) {
+            Ok(nsf) => {
+                let header = &nsf.header;
+                println!("NSF: \"{}\" by {} ({})", header.song_name, header.artist, header.copyright);
+                println!("{} songs, starting at {}", header.total_songs, header.starting_song);
+                println!("Load ${:04X}, init ${:04X}, play ${:04X}", header.load_addr, header.init_addr, header.play_addr);
+            }
+            Err(err) => {
+                error!("Failed to load {}: {}", rom_path, err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    for line in rom_summary(rom_path, &load_rom(rom_path, &image)) {
+        println!("{}", line);
+    }
+}
+
+fn run(args: RunArgs) {
     info!("NES emulator starting...");
+    let rom_path = args.rom.as_str();
+    let (file_path, image) = load_image(rom_path, args.patch);
 
-    let mut bus = NesBus::new();
-    
-    // Set reset vector to start of PRG ROM
-    bus.write(0xFFFC, 0x00);
-    bus.write(0xFFFD, 0x80);
-    
-    let mut cpu = Cpu2A03::new(bus);
-    cpu.reset();
+    if Nsf::is_nsf(&image) {
+        let ultrasonic = args.ultrasonic_triangle.into();
+        run_nsf(rom_path, &image, args.audio_device.as_deref(), args.audio_filter.filters(), &args.pan, ultrasonic);
+        return;
+    }
 
-    loop {
-        // Execute CPU instruction
-        let cycles = cpu.step();
-        
-        // Update global cycle counter
-        cpu.bus.cycles += cycles;
-        
-        // Simulate other components
-        cpu.bus.handle_ppu(cycles);
-        cpu.bus.handle_apu(cycles);
-        
-        // Handle periodic NMIs (VBlank simulation)
-        if cpu.bus.frame_counter >= 29780 {
-            cpu.trigger_nmi();
-        }
-        
-        // Basic execution control
-        if cpu.bus.cycles > 100_000 {
-            info!("Cycle limit reached, exiting");
-            break;
-        }
-        
-        // Example: Print CPU state every 1000 cycles
-        if cpu.bus.cycles % 1000 == 0 {
-            debug!(
-                "Cycles: {} | PC: {:04X} A: {:02X} X: {:02X} Y: {:02X} SP: {:02X}",
-                cpu.bus.cycles, cpu.pc, cpu.a, cpu.x, cpu.y, cpu.sp
-            );
+    let rom = load_rom(rom_path, &image);
+    for line in rom_summary(rom_path, &rom) {
+        info!("{}", line);
+    }
+
+    let mut nes = match Nes::new(rom) {
+        Ok(nes) => nes,
+        Err(err) => {
+            error!("Failed to load {}: {}", rom_path, err);
+            process::exit(1);
+        }
+    };
+
+    if let Some(region) = args.region {
+        let region = Region::from(region);
+        info!("Forcing {:?} timing", region);
+        nes.set_region(region);
+    }
+    nes.cpu.bus.ppu.warm_up = !args.no_ppu_warm_up;
+
+    let apu = &mut nes.cpu.bus.apu;
+    apu.set_filters(args.audio_filter.filters());
+    apu.triangle.set_ultrasonic(args.ultrasonic_triangle.into());
+    if !args.pan.is_empty() {
+        apu.set_stereo(true);
+        for &(channel, pan) in &args.pan {
+            apu.set_pan(channel, pan);
         }
     }
+
+    // A built-in palette by name, or a 64- or 512-color .pal, replaces the
+    // generated NTSC palette
+    if let Some(palette_arg) = args.palette {
+        let palette = Palette::named(&palette_arg).map(Ok).unwrap_or_else(|| {
+            fs::read(&palette_arg)
+                .map_err(|err| err.to_string())
+                .and_then(|data| Palette::from_pal(&data).map_err(|err| err.to_string()))
+        });
+        match palette {
+            Ok(palette) => {
+                info!("Using palette {}", palette_arg);
+                nes.cpu.bus.ppu.palette = palette;
+            }
+            Err(err) => {
+                error!("Failed to load palette {}: {}", palette_arg, err);
+                error!("Built-in palettes: {}", Palette::NAMES.join(", "));
+                process::exit(1);
+            }
+        }
+    }
+
+    let save_path = cart::save_path(&file_path);
+    match cart::load_battery_ram(&nes.cpu.bus.cart, &save_path) {
+        Ok(true) => info!("Loaded save RAM from {}", save_path.display()),
+        Ok(false) => {}
+        Err(err) => warn!("Failed to read save RAM from {}: {}", save_path.display(), err),
+    }
+
+    // Play in a window when built with a frontend; --trace stays on the console
+    let mut keys = KeyMap::default();
+    for &(binding, key) in &args.key {
+        keys.bind(binding, key);
+    }
+    let options = FrontendOptions {
+        scale: args.scale,
+        fullscreen: args.fullscreen,
+        audio_device: args.audio_device,
+        reset_on_jam: args.reset_on_jam,
+        keys,
+    };
+    #[cfg(any(feature = "sdl", feature = "pixels"))]
+    if args.trace {
+        run_headless(&mut nes, args.trace, options.reset_on_jam, args.frames);
+    } else if let Err(err) = frontend::run(&mut nes, &options) {
+        error!("{}", err);
+    }
+    #[cfg(not(any(feature = "sdl", feature = "pixels")))]
+    run_headless(&mut nes, args.trace, options.reset_on_jam, args.frames);
+
+    if let Some(path) = args.dump_nametables {
+        let view = nes.cpu.bus.ppu.nametable_view();
+        match write_ppm(&path, NAMETABLE_VIEW_WIDTH, NAMETABLE_VIEW_HEIGHT, &view) {
+            Ok(()) => info!("Wrote nametables to {}", path.display()),
+            Err(err) => error!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+
+    // Both pattern tables side by side, in the first background palette
+    if let Some(path) = args.dump_patterns {
+        let ppu = &nes.cpu.bus.ppu;
+        let tables = [ppu.pattern_table_view(0, 0), ppu.pattern_table_view(1, 0)];
+        let view: Vec<u32> = (0..PATTERN_VIEW_SIZE)
+            .flat_map(|row| tables.iter().flat_map(move |table| &table[row * PATTERN_VIEW_SIZE..][..PATTERN_VIEW_SIZE]))
+            .copied()
+            .collect();
+        match write_ppm(&path, PATTERN_VIEW_SIZE * 2, PATTERN_VIEW_SIZE, &view) {
+            Ok(()) => info!("Wrote pattern tables to {}", path.display()),
+            Err(err) => error!("Failed to write {}: {}", path.display(), err),
+        }
+    }
+
+    match cart::save_battery_ram(&nes.cpu.bus.cart, &save_path) {
+        Ok(true) => info!("Wrote save RAM to {}", save_path.display()),
+        Ok(false) => {}
+        Err(err) => error!("Failed to write save RAM to {}: {}", save_path.display(), err),
+    }
 }
@@ -0,0 +1,128 @@
+// src/nes/apu/blip.rs
+// Band-limited step synthesis: output level changes at CPU clock timing, rendered as samples without aliasing
+
+use std::f64::consts::PI;
+
+// Kernel taps per step and fractional sample positions it's tabulated at
+const WIDTH: usize = 32;
+const PHASES: usize = 64;
+
+// Cutoff as a fraction of the sample rate, a little under Nyquist so the
+// window's transition band stays clear of it
+const CUTOFF: f64 = 0.45;
+
+/// Turns level changes stamped in source clocks into samples. Each change
+/// is added as a windowed-sinc impulse at its exact sub-sample position,
+/// and reading sums the impulses back into a band-limited step, so pulse
+/// edges and noise between samples come out smooth instead of folding back
+/// as aliases the way picking the level at each sample does. Output lags
+/// the input by `WIDTH / 2` samples.
+pub struct BlipBuffer {
+    factor: f64,                       // Samples per source clock
+    offset: f64,                       // Sample position of the current frame's clock 0
+    deltas: Vec<f32>,                  // Impulses not yet summed into samples
+    integrator: f32,                   // Level at the last sample read
+    capacity: usize,                   // Samples kept waiting before the oldest are dropped
+    kernel: Box<[[f32; WIDTH]; PHASES + 1]>,
+}
+
+impl BlipBuffer {
+    /// Source clocked at `clock_rate` Hz to samples at `sample_rate`
+    pub fn new(clock_rate: f64, sample_rate: u32) -> Self {
+        let mut buffer = Self {
+            factor: 0.0,
+            offset: 0.0,
+            deltas: Vec::new(),
+            integrator: 0.0,
+            capacity: 0,
+            kernel: Box::new(kernel()),
+        };
+        buffer.set_rates(clock_rate, sample_rate);
+        buffer
+    }
+
+    /// Change either rate. Samples already waiting are kept.
+    pub fn set_rates(&mut self, clock_rate: f64, sample_rate: u32) {
+        self.factor = sample_rate as f64 / clock_rate;
+        // About a second of audio for a frontend that stops draining
+        self.capacity = sample_rate as usize;
+    }
+
+    /// The level steps by `delta` at `time` clocks into the current frame
+    pub fn add_delta(&mut self, time: u32, delta: f32) {
+        let position = self.offset + time as f64 * self.factor;
+        let index = position as usize;
+        let phase = ((position - index as f64) * PHASES as f64).round() as usize;
+        if self.deltas.len() < index + WIDTH + 1 {
+            self.deltas.resize(index + WIDTH + 1, 0.0);
+        }
+        let taps = &mut self.deltas[index + 1..index + 1 + WIDTH];
+        for (sample, tap) in taps.iter_mut().zip(&self.kernel[phase]) {
+            *sample += delta * tap;
+        }
+    }
+
+    /// Close the frame `time` clocks in; later deltas count from there
+    pub fn end_frame(&mut self, time: u32) {
+        self.offset += time as f64 * self.factor;
+        let avail = self.samples_avail();
+        if self.deltas.len() < avail {
+            self.deltas.resize(avail, 0.0);
+        }
+        let excess = avail.saturating_sub(self.capacity);
+        if excess > 0 {
+            self.integrator += self.deltas[..excess].iter().sum::<f32>();
+            self.discard(excess);
+        }
+    }
+
+    /// Samples complete up to the end of the last frame
+    pub fn samples_avail(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Move up to `out.len()` finished samples into `out`, returning how many
+    pub fn read_samples(&mut self, out: &mut [f32]) -> usize {
+        let count = out.len().min(self.samples_avail());
+        for (sample, delta) in out.iter_mut().zip(&self.deltas[..count]) {
+            self.integrator += delta;
+            *sample = self.integrator;
+        }
+        self.discard(count);
+        count
+    }
+
+    /// Drop everything waiting and return to silence
+    pub fn clear(&mut self) {
+        self.offset = 0.0;
+        self.deltas.clear();
+        self.integrator = 0.0;
+    }
+
+    fn discard(&mut self, count: usize) {
+        self.deltas.drain(..count);
+        self.offset -= count as f64;
+    }
+}
+
+// Blackman-windowed sinc at each phase, normalized so a step settles on
+// exactly its delta
+fn kernel() -> [[f32; WIDTH]; PHASES + 1] {
+    let mut kernel = [[0.0; WIDTH]; PHASES + 1];
+    for (phase, taps) in kernel.iter_mut().enumerate() {
+        let mut row = [0.0f64; WIDTH];
+        for (tap, value) in row.iter_mut().enumerate() {
+            // Distance from the impulse, and the window's position over the kernel
+            let x = tap as f64 - (WIDTH / 2 - 1) as f64 - phase as f64 / PHASES as f64;
+            let w = (x + WIDTH as f64 / 2.0) / WIDTH as f64;
+            let sinc = if x == 0.0 { 1.0 } else { (2.0 * PI * CUTOFF * x).sin() / (2.0 * PI * CUTOFF * x) };
+            let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+            *value = sinc * window;
+        }
+        let sum: f64 = row.iter().sum();
+        for (tap, value) in taps.iter_mut().zip(row) {
+            *tap = (value / sum) as f32;
+        }
+    }
+    kernel
+}
@@ -0,0 +1,57 @@
+// src/nes/apu/envelope.rs
+// Volume envelope shared by the pulse and noise channels: a decaying level or a constant volume
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(super) struct Envelope {
+    start: bool,
+    looping: bool, // Also the length counter halt flag
+    constant: bool,
+    volume: u8, // Constant volume, or the divider period
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    /// --LC VVVV of the channel's first register
+    pub fn write(&mut self, data: u8) {
+        self.looping = data & 0x20 != 0;
+        self.constant = data & 0x10 != 0;
+        self.volume = data & 0x0F;
+    }
+
+    /// Written through the channel's fourth register: decay starts over
+    /// from 15 on the next quarter frame
+    pub fn restart(&mut self) {
+        self.start = true;
+    }
+
+    /// Quarter frame
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider > 0 {
+            self.divider -= 1;
+        } else {
+            self.divider = self.volume;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.constant {
+            self.volume
+        } else {
+            self.decay
+        }
+    }
+}
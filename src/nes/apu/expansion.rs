@@ -0,0 +1,44 @@
+// src/nes/apu/expansion.rs
+// Cartridge sound chips: the interface mappers expose them through and the sources they can be
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+/// The expansion chips a cartridge (or NSF) can carry, each with its own
+/// volume control on the APU
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AudioSource {
+    Vrc6,
+    Vrc7,
+    N163,
+    Fds,
+    Sunsoft5b,
+    Mmc5,
+}
+
+impl AudioSource {
+    pub const ALL: [AudioSource; 6] = [
+        AudioSource::Vrc6,
+        AudioSource::Vrc7,
+        AudioSource::N163,
+        AudioSource::Fds,
+        AudioSource::Sunsoft5b,
+        AudioSource::Mmc5,
+    ];
+}
+
+/// Sound hardware on the cartridge. The bus clocks it alongside the APU
+/// and mixes its output in after the 2A03's own channels, which is where
+/// the cartridge's audio pin joins the console's on real hardware.
+pub trait ExpansionAudio {
+    /// Which chip this is, for its volume setting
+    fn source(&self) -> AudioSource;
+
+    /// Advance one CPU cycle
+    fn clock(&mut self);
+
+    /// Current output level on the APU's scale, where 1.0 is about the
+    /// 2A03's own loudest output
+    fn output(&self) -> f32;
+}
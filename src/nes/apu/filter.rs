@@ -0,0 +1,87 @@
+// src/nes/apu/filter.rs
+// First-order high- and low-pass filters modelling the console's analog output path
+
+use std::f32::consts::PI;
+
+/// One first-order stage, with its cutoff in Hz
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    HighPass(f32),
+    LowPass(f32),
+}
+
+impl Filter {
+    /// The front-loading NES: two high-passes from the coupling capacitors
+    /// and a low-pass at the output amplifier
+    pub const NES: [Filter; 3] = [Filter::HighPass(90.0), Filter::HighPass(440.0), Filter::LowPass(14_000.0)];
+
+    /// The Famicom's gentler path, which keeps much more bass
+    pub const FAMICOM: [Filter; 2] = [Filter::HighPass(37.0), Filter::LowPass(14_000.0)];
+}
+
+// A stage's coefficient for the sample rate, and what it last saw
+#[derive(Clone, Debug)]
+struct Stage {
+    filter: Filter,
+    coefficient: f32,
+    last_input: f32,
+    last_output: f32,
+}
+
+impl Stage {
+    fn new(filter: Filter, sample_rate: u32) -> Self {
+        let dt = 1.0 / sample_rate as f32;
+        let coefficient = match filter {
+            Filter::HighPass(cutoff) => {
+                let rc = 1.0 / (2.0 * PI * cutoff);
+                rc / (rc + dt)
+            }
+            Filter::LowPass(cutoff) => {
+                let rc = 1.0 / (2.0 * PI * cutoff);
+                dt / (rc + dt)
+            }
+        };
+        Self {
+            filter,
+            coefficient,
+            last_input: 0.0,
+            last_output: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let output = match self.filter {
+            Filter::HighPass(_) => self.coefficient * (self.last_output + input - self.last_input),
+            Filter::LowPass(_) => self.last_output + self.coefficient * (input - self.last_output),
+        };
+        self.last_input = input;
+        self.last_output = output;
+        output
+    }
+}
+
+/// Stages run in order over each output sample
+#[derive(Clone, Debug)]
+pub(super) struct FilterChain {
+    filters: Vec<Filter>,
+    stages: Vec<Stage>,
+}
+
+impl FilterChain {
+    pub(super) fn new(filters: &[Filter], sample_rate: u32) -> Self {
+        Self {
+            filters: filters.to_vec(),
+            stages: filters.iter().map(|&filter| Stage::new(filter, sample_rate)).collect(),
+        }
+    }
+
+    pub(super) fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    pub(super) fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            *sample = self.stages.iter_mut().fold(*sample, |level, stage| stage.process(level));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// src/nes/apu/frame_counter.rs
+// Frame counter ($4017): the 4-step/5-step sequence clocking envelopes, length counters and sweeps
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::nes::clock::Region;
+
+// CPU cycles from the start of the sequence to each step. The fifth is
+// only reached in 5-step mode; the 4-step sequence ends on the fourth.
+const NTSC_STEPS: [u32; 5] = [7457, 14913, 22371, 29829, 37281];
+const PAL_STEPS: [u32; 5] = [8313, 16627, 24939, 33253, 41565];
+
+/// Which units a step clocks: quarter frames drive envelopes and the
+/// triangle's linear counter, half frames length counters and sweeps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameClock {
+    pub quarter: bool,
+    pub half: bool,
+}
+
+impl FrameClock {
+    const QUARTER: Self = Self { quarter: true, half: false };
+    const HALF: Self = Self { quarter: true, half: true };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameCounter {
+    region: Region,
+    five_step: bool,
+    irq_inhibit: bool,
+    irq_flag: bool,
+    cycle: u32,                // CPU cycles into the sequence
+    pending: Option<(u8, u8)>, // $4017 value waiting to take effect, and the cycles left
+    blocked: u8,               // Cycles until a write may clock again after a step
+}
+
+impl FrameCounter {
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            five_step: false,
+            irq_inhibit: false,
+            irq_flag: false,
+            cycle: 0,
+            pending: None,
+            blocked: 0,
+        }
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    pub fn five_step(&self) -> bool {
+        self.five_step
+    }
+
+    pub fn irq_inhibit(&self) -> bool {
+        self.irq_inhibit
+    }
+
+    /// The frame interrupt, raised at the end of each 4-step sequence
+    pub fn irq_flag(&self) -> bool {
+        self.irq_flag
+    }
+
+    /// Acknowledged by a $4015 read
+    pub fn clear_irq(&mut self) {
+        self.irq_flag = false;
+    }
+
+    /// $4017: bit 6 inhibits the IRQ, clearing the flag at once. Bit 7
+    /// picks the 5-step sequence, which restarts after `delay` cycles (3
+    /// or 4, depending on where the write falls in the APU cycle); 5-step
+    /// mode clocks everything as it does, unless a step already clocked
+    /// on that cycle or the one before.
+    pub fn write(&mut self, data: u8, delay: u8) {
+        self.irq_inhibit = data & 0x40 != 0;
+        if self.irq_inhibit {
+            self.irq_flag = false;
+        }
+        self.pending = Some((data, delay));
+    }
+
+    /// Advance one CPU cycle. A write taking effect lands after the
+    /// sequence has moved for the cycle, so a step and the restart can
+    /// fall on the same cycle.
+    pub fn clock(&mut self) -> FrameClock {
+        let mut clock = self.step();
+        if clock != FrameClock::default() {
+            self.blocked = 2;
+        }
+
+        if let Some((data, delay)) = self.pending.take() {
+            if delay > 1 {
+                self.pending = Some((data, delay - 1));
+            } else {
+                self.five_step = data & 0x80 != 0;
+                self.cycle = 0;
+                if self.five_step && self.blocked == 0 {
+                    clock = FrameClock::HALF;
+                    self.blocked = 2;
+                }
+            }
+        }
+        self.blocked = self.blocked.saturating_sub(1);
+        clock
+    }
+
+    // Move the sequence on a cycle, raising the IRQ and handing out any
+    // step that lands
+    fn step(&mut self) -> FrameClock {
+        let steps = match self.region {
+            Region::Ntsc => &NTSC_STEPS,
+            Region::Pal => &PAL_STEPS,
+        };
+        let last = if self.five_step { steps[4] } else { steps[3] };
+
+        self.cycle += 1;
+        // The 4-step sequence raises the IRQ over its last three cycles
+        if !self.five_step && !self.irq_inhibit && self.cycle + 1 >= last {
+            self.irq_flag = true;
+        }
+        let clock = match self.cycle {
+            cycle if cycle == steps[0] || cycle == steps[2] => FrameClock::QUARTER,
+            cycle if cycle == steps[1] || cycle == last => FrameClock::HALF,
+            _ => FrameClock::default(),
+        };
+        if self.cycle > last {
+            self.cycle = 0;
+        }
+        clock
+    }
+}
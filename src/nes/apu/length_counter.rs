@@ -0,0 +1,50 @@
+// src/nes/apu/length_counter.rs
+// Length counter: silences a channel after a programmed number of half frames
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+// Loaded through the top five bits of a channel's fourth register
+const LENGTHS: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14, //
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(super) struct LengthCounter {
+    enabled: bool, // The channel's $4015 bit
+    halt: bool,
+    counter: u8,
+}
+
+impl LengthCounter {
+    /// Disabling through $4015 clears the counter at once
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    pub fn set_halt(&mut self, halt: bool) {
+        self.halt = halt;
+    }
+
+    /// Load from the length table; ignored while the channel is disabled
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTHS[(index & 0x1F) as usize];
+        }
+    }
+
+    pub fn clock(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.counter > 0
+    }
+}
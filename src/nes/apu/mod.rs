@@ -0,0 +1,429 @@
+// src/nes/apu/mod.rs
+// 2A03 audio processing unit: sound channel registers at $4000-$4013/$4015 and the frame counter at $4017
+
+mod blip;
+mod envelope;
+mod expansion;
+mod filter;
+mod frame_counter;
+mod length_counter;
+mod mixer;
+mod noise;
+mod output;
+mod pulse;
+mod triangle;
+
+use log::debug;
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::nes::clock::Region;
+use output::OutputStage;
+
+pub use blip::BlipBuffer;
+pub use expansion::{AudioSource, ExpansionAudio};
+pub use filter::Filter;
+pub use frame_counter::{FrameClock, FrameCounter};
+pub use mixer::Channel;
+pub use noise::Noise;
+pub use pulse::Pulse;
+pub use triangle::{Triangle, Ultrasonic};
+
+/// Output rate until `set_sample_rate` picks another
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+// CPU cycles per band-limited buffer frame
+const BLIP_FRAME: u32 = 1024;
+
+/// The APU side of the 2A03, clocked once per CPU cycle
+pub struct Apu {
+    pub pulse: [Pulse; 2],
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub frame_counter: FrameCounter,
+    last_frame_write: u8, // Rewritten to $4017 on reset
+    odd_cycle: bool,      // Second CPU cycle of an APU cycle
+    outputs: Vec<OutputStage>,  // Mono, or left and right in stereo
+    blip_time: u32,             // CPU cycles into the buffers' frame
+    region: Region,
+    sample_rate: u32,
+    rate_adjust: f64,           // Fractional stretch of the output rate, from dynamic rate control
+    expansion: [f32; AudioSource::ALL.len()], // Each chip's level this cycle, after its volume
+    expansion_volume: [f32; AudioSource::ALL.len()],
+    muted: u16,                 // `Channel::mask` bits
+    soloed: u16,
+    pan: [f32; Channel::COUNT], // By `Channel::index`, -1.0 left to 1.0 right
+    scratch: Vec<f32>,          // One side's samples while interleaving
+}
+
+/// Snapshot of the 2A03's sound state, mid-sequence timers and all, for
+/// save states and rewind. Output settings (sample rate, filters, pans,
+/// mutes, volumes) and samples not yet read stay with the running APU.
+/// There's no DMC channel yet, so no sample reader or DMC IRQ to keep.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ApuState {
+    pulse: [Pulse; 2],
+    triangle: Triangle,
+    noise: Noise,
+    frame_counter: FrameCounter,
+    last_frame_write: u8,
+    odd_cycle: bool,
+    expansion: [f32; AudioSource::ALL.len()],
+    region: Region,
+}
+
+impl Apu {
+    pub fn new(region: Region) -> Self {
+        Self {
+            pulse: [Pulse::new(true), Pulse::new(false)],
+            triangle: Triangle::default(),
+            noise: Noise::new(region),
+            frame_counter: FrameCounter::new(region),
+            last_frame_write: 0,
+            odd_cycle: false,
+            outputs: vec![OutputStage::new(cpu_rate(region), DEFAULT_SAMPLE_RATE, &Filter::NES)],
+            blip_time: 0,
+            region,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            rate_adjust: 0.0,
+            expansion: [0.0; AudioSource::ALL.len()],
+            expansion_volume: [1.0; AudioSource::ALL.len()],
+            muted: 0,
+            soloed: 0,
+            pan: [0.0; Channel::COUNT],
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.frame_counter.set_region(region);
+        self.noise.set_region(region);
+        self.region = region;
+        self.update_rates();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Resample to `rate` Hz, typically the host's 44100 or 48000. Samples
+    /// waiting at the old rate are dropped.
+    pub fn set_sample_rate(&mut self, rate: u32) {
+        assert!(rate > 0, "sample rate must be nonzero");
+        self.sample_rate = rate;
+        let clock_rate = self.clock_rate();
+        for output in &mut self.outputs {
+            output.restart(clock_rate, rate, self.blip_time);
+        }
+    }
+
+    pub fn save_state(&self) -> ApuState {
+        ApuState {
+            pulse: self.pulse.clone(),
+            triangle: self.triangle.clone(),
+            noise: self.noise.clone(),
+            frame_counter: self.frame_counter.clone(),
+            last_frame_write: self.last_frame_write,
+            odd_cycle: self.odd_cycle,
+            expansion: self.expansion,
+            region: self.region,
+        }
+    }
+
+    /// Restore a snapshot. The output carries on from the level it was at,
+    /// so the jump to the restored level is band-limited and filtered like
+    /// any other rather than clicking. The ultrasonic triangle setting is
+    /// kept.
+    pub fn load_state(&mut self, state: &ApuState) {
+        let ultrasonic = self.triangle.ultrasonic();
+        self.pulse = state.pulse.clone();
+        self.triangle = state.triangle.clone();
+        self.triangle.set_ultrasonic(ultrasonic);
+        self.noise = state.noise.clone();
+        self.frame_counter = state.frame_counter.clone();
+        self.last_frame_write = state.last_frame_write;
+        self.odd_cycle = state.odd_cycle;
+        self.expansion = state.expansion;
+        self.region = state.region;
+        self.update_rates();
+    }
+
+    /// The console's reset line: channels are silenced and the frame
+    /// counter restarts in the mode last written to $4017
+    pub fn reset(&mut self) {
+        self.write_register(0x4015, 0x00);
+        self.write_register(0x4017, self.last_frame_write);
+    }
+
+    /// CPU write to $4000-$4013, $4015 or $4017
+    pub fn write_register(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4000..=0x4003 => self.pulse[0].write(addr, data),
+            0x4004..=0x4007 => self.pulse[1].write(addr, data),
+            0x4008..=0x400B => self.triangle.write(addr, data),
+            0x400C..=0x400F => self.noise.write(addr, data),
+            0x4015 => {
+                self.pulse[0].set_enabled(data & 0x01 != 0);
+                self.pulse[1].set_enabled(data & 0x02 != 0);
+                self.triangle.set_enabled(data & 0x04 != 0);
+                self.noise.set_enabled(data & 0x08 != 0);
+            }
+            0x4017 => {
+                self.last_frame_write = data;
+                // The write lands mid-cycle, after this cycle's `clock`
+                let delay = if self.odd_cycle { 4 } else { 3 };
+                self.frame_counter.write(data, delay);
+            }
+            _ => debug!("APU write {:02X} to {:04X}", data, addr),
+        }
+    }
+
+    /// $4015 read: length counters still running in bits 0-3 and the frame
+    /// IRQ in bit 6, which the read acknowledges. With no DMC channel, bits
+    /// 4 and 7 read clear; bit 5 is left to the bus.
+    pub fn read_status(&mut self) -> u8 {
+        let status = self.peek_status();
+        self.frame_counter.clear_irq();
+        status
+    }
+
+    /// $4015 without acknowledging the IRQ, for debuggers
+    pub fn peek_status(&self) -> u8 {
+        let active = [self.pulse[0].active(), self.pulse[1].active(), self.triangle.active(), self.noise.active()];
+        let lengths = active.iter().enumerate().fold(0, |status, (bit, &on)| status | (on as u8) << bit);
+        lengths | (self.frame_counter.irq_flag() as u8) << 6
+    }
+
+    /// The APU's side of the CPU's /IRQ line
+    pub fn irq_asserted(&self) -> bool {
+        self.frame_counter.irq_flag()
+    }
+
+    /// Volume of an expansion chip, 1.0 by default
+    pub fn expansion_volume(&self, source: AudioSource) -> f32 {
+        self.expansion_volume[source as usize]
+    }
+
+    /// Scale `source`'s output; 0.0 leaves it out of the mix
+    pub fn set_expansion_volume(&mut self, source: AudioSource, volume: f32) {
+        self.expansion_volume[source as usize] = volume;
+    }
+
+    /// A cartridge chip's output for the coming cycle. The bus calls this
+    /// before `clock` for each chip present; an NSF may carry several.
+    pub fn mix_expansion(&mut self, source: AudioSource, level: f32) {
+        let audible = self.audible(Channel::Expansion(source));
+        self.expansion[source as usize] = if audible { level * self.expansion_volume(source) } else { 0.0 };
+    }
+
+    /// Leave `channel` out of the mix. The channel keeps running, so
+    /// unmuting picks up wherever it has got to.
+    pub fn set_muted(&mut self, channel: Channel, muted: bool) {
+        self.muted = if muted { self.muted | channel.mask() } else { self.muted & !channel.mask() };
+    }
+
+    pub fn muted(&self, channel: Channel) -> bool {
+        self.muted & channel.mask() != 0
+    }
+
+    /// While any channel is soloed, only soloed channels are heard
+    pub fn set_solo(&mut self, channel: Channel, solo: bool) {
+        self.soloed = if solo { self.soloed | channel.mask() } else { self.soloed & !channel.mask() };
+    }
+
+    pub fn soloed(&self, channel: Channel) -> bool {
+        self.soloed & channel.mask() != 0
+    }
+
+    /// Clear every mute and solo
+    pub fn unmute_all(&mut self) {
+        self.muted = 0;
+        self.soloed = 0;
+    }
+
+    /// Whether `channel` reaches the output under the current mutes and solos
+    pub fn audible(&self, channel: Channel) -> bool {
+        let mask = channel.mask();
+        self.muted & mask == 0 && (self.soloed == 0 || self.soloed & mask != 0)
+    }
+
+    /// Place `channel` from -1.0 (hard left) through 0.0 (center) to 1.0
+    /// (hard right). Only heard once `set_stereo` is on.
+    pub fn set_pan(&mut self, channel: Channel, pan: f32) {
+        self.pan[channel.index()] = pan.clamp(-1.0, 1.0);
+    }
+
+    pub fn pan(&self, channel: Channel) -> f32 {
+        self.pan[channel.index()]
+    }
+
+    pub fn stereo(&self) -> bool {
+        self.outputs.len() == 2
+    }
+
+    /// Mix left and right separately by each channel's pan, reading
+    /// interleaved left/right pairs. Samples already waiting are dropped.
+    pub fn set_stereo(&mut self, stereo: bool) {
+        let sides = if stereo { 2 } else { 1 };
+        let filters = self.filters().to_vec();
+        let clock_rate = self.clock_rate();
+        self.outputs = (0..sides).map(|_| OutputStage::new(clock_rate, self.sample_rate, &filters)).collect();
+    }
+
+    /// Advance one CPU cycle
+    pub fn clock(&mut self) {
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        // The pulse timers run at half the CPU rate
+        if self.odd_cycle {
+            for pulse in &mut self.pulse {
+                pulse.clock_timer();
+            }
+        }
+        self.odd_cycle = !self.odd_cycle;
+
+        let clock = self.frame_counter.clock();
+        self.frame_clock(clock);
+
+        if self.stereo() {
+            let left = self.mix(|channel| (1.0 - self.pan(channel)).min(1.0));
+            let right = self.mix(|channel| (1.0 + self.pan(channel)).min(1.0));
+            self.outputs[0].set_level(self.blip_time, left);
+            self.outputs[1].set_level(self.blip_time, right);
+        } else {
+            let level = self.output();
+            self.outputs[0].set_level(self.blip_time, level);
+        }
+        self.blip_time += 1;
+        if self.blip_time == BLIP_FRAME {
+            self.end_frame();
+        }
+    }
+
+    /// Produce `adjust` more samples per second than `sample_rate` calls
+    /// for (0.002 is 0.2% more, negative is fewer), without disturbing the
+    /// samples already waiting. Frontends nudge this to keep their output
+    /// queue from draining or filling when the host's audio clock and
+    /// video refresh don't match the console's exactly.
+    pub fn set_rate_adjust(&mut self, adjust: f64) {
+        self.rate_adjust = adjust;
+        self.update_rates();
+    }
+
+    pub fn rate_adjust(&self) -> f64 {
+        self.rate_adjust
+    }
+
+    /// The filters output samples pass through, NES-style by default
+    pub fn filters(&self) -> &[Filter] {
+        self.outputs[0].filters()
+    }
+
+    /// Replace the output filters, e.g. with `Filter::FAMICOM`, or with
+    /// nothing for the raw DAC level
+    pub fn set_filters(&mut self, filters: &[Filter]) {
+        for output in &mut self.outputs {
+            output.set_filters(filters, self.sample_rate);
+        }
+    }
+
+    /// Band-limited samples ready to read, at `sample_rate`; in stereo,
+    /// each is a left/right pair
+    pub fn samples_avail(&self) -> usize {
+        self.outputs[0].samples_avail()
+    }
+
+    /// Move up to `out.len()` samples into `out`, returning how many values
+    /// were written (two per stereo pair). Up to a second's worth is held
+    /// for a frontend that falls behind.
+    pub fn read_samples(&mut self, out: &mut [f32]) -> usize {
+        let sides = self.outputs.len();
+        if sides == 1 {
+            return self.outputs[0].read_samples(out);
+        }
+        let count = (out.len() / sides).min(self.samples_avail());
+        self.scratch.resize(count, 0.0);
+        for (side, output) in self.outputs.iter_mut().enumerate() {
+            output.read_samples(&mut self.scratch);
+            for (frame, &sample) in out.chunks_exact_mut(sides).zip(&self.scratch) {
+                frame[side] = sample;
+            }
+        }
+        count * sides
+    }
+
+    /// Append every sample up to the current cycle to `out`, interleaved
+    /// left/right in stereo. Frontends call this once per video frame and
+    /// queue the result for the host.
+    pub fn take_samples(&mut self, out: &mut Vec<f32>) {
+        self.end_frame();
+        let start = out.len();
+        out.resize(start + self.samples_avail() * self.outputs.len(), 0.0);
+        let count = self.read_samples(&mut out[start..]);
+        out.truncate(start + count);
+    }
+
+    /// The mixed output level: 0.0 to about 1.0 from the 2A03, plus any
+    /// expansion audio
+    pub fn output(&self) -> f32 {
+        self.mix(|_| 1.0)
+    }
+
+    // The mix with each audible channel scaled by `gain`, as for one side
+    // of the stereo field
+    fn mix(&self, gain: impl Fn(Channel) -> f32) -> f32 {
+        let level = |channel, output: u8| if self.audible(channel) { output as f32 * gain(channel) } else { 0.0 };
+        let pulse1 = level(Channel::Pulse1, self.pulse[0].output());
+        let pulse2 = level(Channel::Pulse2, self.pulse[1].output());
+        let triangle = level(Channel::Triangle, self.triangle.output());
+        let noise = level(Channel::Noise, self.noise.output());
+        let expansion: f32 = AudioSource::ALL
+            .iter()
+            .map(|&source| self.expansion[source as usize] * gain(Channel::Expansion(source)))
+            .sum();
+        mixer::pulse(pulse1, pulse2) + mixer::tnd(triangle, noise) + expansion
+    }
+
+    fn end_frame(&mut self) {
+        for output in &mut self.outputs {
+            output.end_frame(self.blip_time);
+        }
+        self.blip_time = 0;
+    }
+
+    fn update_rates(&mut self) {
+        let clock_rate = self.clock_rate();
+        for output in &mut self.outputs {
+            output.set_rates(clock_rate, self.sample_rate);
+        }
+    }
+
+    // Stretching the output is the same as playing back a slower CPU
+    fn clock_rate(&self) -> f64 {
+        cpu_rate(self.region) / (1.0 + self.rate_adjust)
+    }
+
+    // Envelopes, length counters and sweeps on the frame counter's steps
+    fn frame_clock(&mut self, clock: FrameClock) {
+        if clock.quarter {
+            for pulse in &mut self.pulse {
+                pulse.quarter_frame();
+            }
+            self.triangle.quarter_frame();
+            self.noise.quarter_frame();
+        }
+        if clock.half {
+            for pulse in &mut self.pulse {
+                pulse.half_frame();
+            }
+            self.triangle.half_frame();
+            self.noise.half_frame();
+        }
+    }
+}
+
+// The CPU clock without rounding, which would drift the sample rate
+fn cpu_rate(region: Region) -> f64 {
+    region.master_hz() as f64 / region.cpu_divider() as f64
+}
@@ -0,0 +1,110 @@
+// src/nes/apu/noise.rs
+// Noise channel ($400C-$400F): 15-bit LFSR in long or short mode, envelope and length counter
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use super::envelope::Envelope;
+use super::length_counter::LengthCounter;
+use crate::nes::clock::Region;
+
+// Timer periods in CPU cycles for each $400E index
+const NTSC_PERIODS: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+const PAL_PERIODS: [u16; 16] = [4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Noise {
+    region: Region,
+    // Short mode taps bit 6 instead of bit 1, for a 93-step metallic loop
+    short_mode: bool,
+    period_index: u8,
+    timer: u16,
+    shift: u16,
+    envelope: Envelope,
+    length: LengthCounter,
+}
+
+impl Noise {
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            short_mode: false,
+            period_index: 0,
+            timer: 0,
+            shift: 1,
+            envelope: Envelope::default(),
+            length: LengthCounter::default(),
+        }
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// Write to the channel's register 0-3 ($400D is unused)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg & 0x03 {
+            0 => {
+                self.length.set_halt(data & 0x20 != 0);
+                self.envelope.write(data);
+            }
+            1 => {}
+            2 => {
+                self.short_mode = data & 0x80 != 0;
+                self.period_index = data & 0x0F;
+            }
+            _ => {
+                self.length.load(data >> 3);
+                self.envelope.restart();
+            }
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Length counter still running, for $4015
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    /// Timer period in CPU cycles
+    pub fn period(&self) -> u16 {
+        let periods = match self.region {
+            Region::Ntsc => &NTSC_PERIODS,
+            Region::Pal => &PAL_PERIODS,
+        };
+        periods[self.period_index as usize]
+    }
+
+    /// Every CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period() - 1;
+            let tap = if self.short_mode { 6 } else { 1 };
+            let feedback = (self.shift ^ (self.shift >> tap)) & 0x01;
+            self.shift = (self.shift >> 1) | feedback << 14;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+    }
+
+    /// Volume 0-15, or 0 while bit 0 of the shift register is set
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.shift & 0x01 != 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// src/nes/apu/output.rs
+// One side of the audio output: band-limited synthesis to the host rate, then the filters
+
+use super::blip::BlipBuffer;
+use super::filter::{Filter, FilterChain};
+
+/// Mono output has one stage; stereo has a left and a right
+pub(super) struct OutputStage {
+    blip: BlipBuffer,
+    filters: FilterChain, // Run over samples as they're read
+    level: f32,           // Mixed level as last handed to the buffer
+}
+
+impl OutputStage {
+    pub(super) fn new(clock_rate: f64, sample_rate: u32, filters: &[Filter]) -> Self {
+        Self {
+            blip: BlipBuffer::new(clock_rate, sample_rate),
+            filters: FilterChain::new(filters, sample_rate),
+            level: 0.0,
+        }
+    }
+
+    /// The mixed level is `level` from `time` CPU cycles into the frame
+    pub(super) fn set_level(&mut self, time: u32, level: f32) {
+        if level != self.level {
+            self.blip.add_delta(time, level - self.level);
+            self.level = level;
+        }
+    }
+
+    pub(super) fn end_frame(&mut self, time: u32) {
+        self.blip.end_frame(time);
+    }
+
+    pub(super) fn samples_avail(&self) -> usize {
+        self.blip.samples_avail()
+    }
+
+    pub(super) fn read_samples(&mut self, out: &mut [f32]) -> usize {
+        let count = self.blip.read_samples(out);
+        self.filters.process(&mut out[..count]);
+        count
+    }
+
+    pub(super) fn set_rates(&mut self, clock_rate: f64, sample_rate: u32) {
+        self.blip.set_rates(clock_rate, sample_rate);
+    }
+
+    /// Start over at a new sample rate from `time` into the frame, keeping
+    /// the current level and the filter choice
+    pub(super) fn restart(&mut self, clock_rate: f64, sample_rate: u32, time: u32) {
+        self.blip.set_rates(clock_rate, sample_rate);
+        self.blip.clear();
+        self.blip.add_delta(time, self.level);
+        self.filters = FilterChain::new(self.filters.filters(), sample_rate);
+    }
+
+    pub(super) fn filters(&self) -> &[Filter] {
+        self.filters.filters()
+    }
+
+    pub(super) fn set_filters(&mut self, filters: &[Filter], sample_rate: u32) {
+        self.filters = FilterChain::new(filters, sample_rate);
+    }
+}
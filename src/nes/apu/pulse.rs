@@ -0,0 +1,153 @@
+// src/nes/apu/pulse.rs
+// Pulse channels ($4000-$4007): duty sequencer, envelope, length counter and sweep
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use super::envelope::Envelope;
+use super::length_counter::LengthCounter;
+
+// 12.5%, 25%, 50% and 25% negated, in output order
+const DUTY: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Sweep {
+    enabled: bool,
+    period: u8,
+    negate: bool,
+    shift: u8,
+    divider: u8,
+    reload: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pulse {
+    // Pulse 1 negates with ones' complement, so its sweep down lands one
+    // lower than pulse 2's
+    ones_complement: bool,
+    duty: u8,
+    step: u8,
+    timer_period: u16,
+    timer: u16,
+    envelope: Envelope,
+    length: LengthCounter,
+    sweep: Sweep,
+}
+
+impl Pulse {
+    /// Pulse 1 (`first`) or pulse 2
+    pub fn new(first: bool) -> Self {
+        Self {
+            ones_complement: first,
+            duty: 0,
+            step: 0,
+            timer_period: 0,
+            timer: 0,
+            envelope: Envelope::default(),
+            length: LengthCounter::default(),
+            sweep: Sweep::default(),
+        }
+    }
+
+    /// Write to the channel's register 0-3
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg & 0x03 {
+            0 => {
+                self.duty = data >> 6;
+                self.length.set_halt(data & 0x20 != 0);
+                self.envelope.write(data);
+            }
+            1 => {
+                self.sweep.enabled = data & 0x80 != 0;
+                self.sweep.period = (data >> 4) & 0x07;
+                self.sweep.negate = data & 0x08 != 0;
+                self.sweep.shift = data & 0x07;
+                self.sweep.reload = true;
+            }
+            2 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            _ => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((data & 0x07) as u16) << 8;
+                self.length.load(data >> 3);
+                self.step = 0;
+                self.envelope.restart();
+            }
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Length counter still running, for $4015
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    /// The 11-bit timer period, as the sweep leaves it
+    pub fn period(&self) -> u16 {
+        self.timer_period
+    }
+
+    /// Every APU cycle (two CPU cycles)
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.step = (self.step + 1) & 0x07;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+
+        let sweep = &self.sweep;
+        if sweep.divider == 0 && sweep.enabled && sweep.shift != 0 && !self.muted() {
+            self.timer_period = self.sweep_target();
+        }
+        let sweep = &mut self.sweep;
+        if sweep.divider == 0 || sweep.reload {
+            sweep.divider = sweep.period;
+            sweep.reload = false;
+        } else {
+            sweep.divider -= 1;
+        }
+    }
+
+    /// Volume 0-15 at the current step
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.muted() || DUTY[self.duty as usize][self.step as usize] == 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+
+    // The sweep works out its target all the time, and mutes the channel
+    // when it would overflow even if sweeping is off
+    fn sweep_target(&self) -> u16 {
+        let change = self.timer_period >> self.sweep.shift;
+        if !self.sweep.negate {
+            self.timer_period + change
+        } else if self.ones_complement {
+            self.timer_period.saturating_sub(change + 1)
+        } else {
+            self.timer_period.saturating_sub(change)
+        }
+    }
+
+    fn muted(&self) -> bool {
+        self.timer_period < 8 || self.sweep_target() > 0x7FF
+    }
+}
@@ -0,0 +1,147 @@
+// src/nes/apu/triangle.rs
+// Triangle channel ($4008-$400B): linear counter, length counter and the 32-step sequencer
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use super::length_counter::LengthCounter;
+
+// Down the ramp and back up
+const SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, //
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+// CPU cycles per step of `Ultrasonic::Fade`, about 2ms from either end of
+// the ramp to the middle
+const FADE_PERIOD: u16 = 256;
+
+/// What the triangle does at the ultrasonic periods 0 and 1, where the
+/// hardware averages to a level around 7.5 with a pop on the way in and out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ultrasonic {
+    /// Step at full speed, as the console does
+    #[default]
+    Accurate,
+    /// Hold the sequencer where it is, so the channel goes quiet with no pop
+    Silence,
+    /// Ease the level along the ramp to the middle, the average the
+    /// hardware lands on, without the jump
+    Fade,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Triangle {
+    control: bool, // Also the length counter halt flag
+    linear_reload_value: u8,
+    linear_counter: u8,
+    linear_reload: bool,
+    step: u8,
+    timer_period: u16,
+    timer: u16,
+    length: LengthCounter,
+    ultrasonic: Ultrasonic,
+    fade_timer: u16,
+}
+
+impl Triangle {
+    /// Write to the channel's register 0-3 ($4009 is unused)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg & 0x03 {
+            0 => {
+                self.control = data & 0x80 != 0;
+                self.length.set_halt(self.control);
+                self.linear_reload_value = data & 0x7F;
+            }
+            1 => {}
+            2 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            _ => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((data & 0x07) as u16) << 8;
+                self.length.load(data >> 3);
+                self.linear_reload = true;
+            }
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Length counter still running, for $4015
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    pub fn period(&self) -> u16 {
+        self.timer_period
+    }
+
+    pub fn ultrasonic(&self) -> Ultrasonic {
+        self.ultrasonic
+    }
+
+    /// Trade accuracy for a cleaner sound at ultrasonic periods
+    pub fn set_ultrasonic(&mut self, ultrasonic: Ultrasonic) {
+        self.ultrasonic = ultrasonic;
+    }
+
+    /// Every CPU cycle. The sequencer only moves while both counters are
+    /// nonzero. Periods of 0 and 1 step it far above hearing, which the
+    /// DAC turns into a level around 7.5 with a pop on the way in and out,
+    /// unless `set_ultrasonic` says otherwise.
+    pub fn clock_timer(&mut self) {
+        if self.timer_period < 2 && self.ultrasonic != Ultrasonic::Accurate {
+            if self.ultrasonic == Ultrasonic::Fade && self.linear_counter > 0 && self.length.active() {
+                self.fade();
+            }
+            return;
+        }
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            if self.linear_counter > 0 && self.length.active() {
+                self.step = (self.step + 1) & 0x1F;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    // One step toward whichever middle of the ramp is nearer, every
+    // `FADE_PERIOD` cycles the sequencer would be running, stopping on
+    // level 7
+    fn fade(&mut self) {
+        self.fade_timer += 1;
+        if self.fade_timer < FADE_PERIOD {
+            return;
+        }
+        self.fade_timer = 0;
+        self.step = match self.step {
+            0..=7 | 16..=22 => self.step + 1,
+            9..=15 | 24..=31 => self.step - 1,
+            step => step,
+        };
+    }
+
+    pub fn quarter_frame(&mut self) {
+        if self.linear_reload {
+            self.linear_counter = self.linear_reload_value;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+        if !self.control {
+            self.linear_reload = false;
+        }
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+    }
+
+    /// Level 0-15. A silenced triangle holds whatever step it stopped on
+    /// rather than dropping to 0.
+    pub fn output(&self) -> u8 {
+        SEQUENCE[self.step as usize]
+    }
+}
@@ -0,0 +1,208 @@
+// src/nes/bus.rs
+// System bus: CPU address decoding across RAM, PPU, I/O ports and the cartridge
+
+use crate::nes::apu::Apu;
+use crate::nes::cart::SharedMapper;
+use crate::nes::clock::{MasterClock, Region};
+use crate::nes::controller::Controller;
+use crate::nes::cpu::Bus;
+use crate::nes::ppu::{Ppu, PpuEventKind};
+use crate::nes::vs::VsSystem;
+
+const RAM_SIZE: usize = 2048; // 2KB NES RAM
+
+/// Everything on the CPU's side of the console
+pub struct NesBus {
+    ram: [u8; RAM_SIZE],
+    pub cart: SharedMapper,     // Cartridge mapper
+    pub ppu: Ppu,               // Clocked from `tick` at the master clock's dot rate
+    pub apu: Apu,               // Clocked from `tick` once per CPU cycle
+    pub clock: MasterClock,
+    pub vs: Option<VsSystem>,   // Vs. System mainboard, for arcade ROMs
+    pub controllers: [Controller; 2], // Standard pads on $4016/$4017
+    oam_dma: Option<u8>,        // Page written to $4014, waiting for the CPU to halt
+    open_bus: u8,               // Last value driven on the CPU data bus
+    nmi: bool,                  // Raised by the PPU, not yet taken by the CPU
+    frame_complete: bool,       // PPU finished a frame since the last check
+    irq_logged: bool,           // IRQ line as the PPU's event log last saw it
+}
+
+impl NesBus {
+    pub fn new(cart: SharedMapper, vs: Option<VsSystem>, region: Region) -> Self {
+        let mut ppu = Ppu::new(cart.clone());
+        ppu.region = region;
+        Self {
+            ram: [0; RAM_SIZE],
+            ppu,
+            apu: Apu::new(region),
+            clock: MasterClock::new(region),
+            cart,
+            vs,
+            controllers: Default::default(),
+            oam_dma: None,
+            open_bus: 0,
+            nmi: false,
+            frame_complete: false,
+            irq_logged: false,
+        }
+    }
+
+    /// Whether the PPU finished a frame since the last call
+    pub fn take_frame_complete(&mut self) -> bool {
+        std::mem::take(&mut self.frame_complete)
+    }
+
+    // Controller ports drive bits 0-4 and the rest float, so `LDA $4016`
+    // usually reads $40 or $41. Vs. System coin slots and DIP switches drive
+    // the upper bits as well.
+    fn port_value(&self, port: usize, controller: u8) -> u8 {
+        match (&self.vs, port) {
+            (Some(vs), 0) => (self.open_bus & 0x80) | vs.read_4016() | controller,
+            (Some(vs), _) => vs.read_4017() | controller,
+            (None, _) => (self.open_bus & 0xE0) | controller,
+        }
+    }
+}
+
+impl Bus for NesBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let data = match addr {
+            // RAM (mirrored every 2KB)
+            0x0000..=0x1FFF => self.ram[addr as usize % RAM_SIZE],
+
+            // PPU registers (mirrored every 8 bytes)
+            0x2000..=0x3FFF => {
+                let reg = (addr - 0x2000) % 8;
+                let reg = self.vs.as_ref().map_or(reg, |vs| vs.ppu_register(reg));
+                let data = self.ppu.read_register(reg);
+                match &self.vs {
+                    Some(vs) if reg == 2 => vs.ppu_status(data),
+                    _ => data,
+                }
+            }
+
+            // APU status is read inside the 2A03 without driving the external
+            // bus: bit 5 keeps the old bus value and the latch isn't updated
+            0x4015 => return (self.open_bus & 0x20) | self.apu.read_status(),
+
+            0x4016 => {
+                let data = self.controllers[0].read();
+                self.port_value(0, data)
+            }
+            0x4017 => {
+                let data = self.controllers[1].read();
+                self.port_value(1, data)
+            }
+
+            // Write-only APU registers, sprite DMA and the disabled test
+            // registers leave the bus floating
+            0x4000..=0x401F => self.open_bus,
+
+            // Vs. System mainboard work RAM
+            0x6000..=0x7FFF if self.vs.is_some() => self.vs.as_ref().map_or(0, |vs| vs.read_ram(addr)),
+
+            // Cartridge space; undriven reads float at the last bus value
+            0x4020..=0xFFFF => self.cart.borrow_mut().cpu_read(addr).unwrap_or(self.open_bus),
+        };
+        self.open_bus = data;
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.open_bus = data;
+        match addr {
+            // RAM
+            0x0000..=0x1FFF => self.ram[addr as usize % RAM_SIZE] = data,
+
+            // PPU registers; RC2C05 Vs. PPUs swap PPUCTRL and PPUMASK
+            0x2000..=0x3FFF => {
+                let reg = (addr - 0x2000) % 8;
+                let reg = self.vs.as_ref().map_or(reg, |vs| vs.ppu_register(reg));
+                self.ppu.write_register(reg, data);
+            }
+
+            // Sprite DMA; the CPU performs the copy through $2004
+            0x4014 => self.oam_dma = Some(data),
+
+            // Controller strobe goes to both ports
+            0x4016 => {
+                for controller in &mut self.controllers {
+                    controller.write_strobe(data);
+                }
+            }
+
+            // APU, including the frame counter at $4017
+            0x4000..=0x4013 | 0x4015 | 0x4017 => self.apu.write_register(addr, data),
+
+            // Unused I/O and the disabled test registers
+            0x4018..=0x401F => {}
+
+            // Vs. System mainboard work RAM
+            0x6000..=0x7FFF if self.vs.is_some() => {
+                if let Some(vs) = &mut self.vs {
+                    vs.write_ram(addr, data);
+                }
+            }
+
+            // Cartridge space
+            0x4020..=0xFFFF => self.cart.borrow_mut().cpu_write(addr, data),
+        }
+    }
+
+    // Registers with read side effects report the floating bus instead;
+    // the controller ports show their next bit without shifting
+    fn peek(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize % RAM_SIZE],
+            0x4015 => (self.open_bus & 0x20) | self.apu.peek_status(),
+            0x4016 => self.port_value(0, self.controllers[0].peek()),
+            0x4017 => self.port_value(1, self.controllers[1].peek()),
+            0x6000..=0x7FFF if self.vs.is_some() => self.vs.as_ref().map_or(0, |vs| vs.read_ram(addr)),
+            0x4020..=0xFFFF => self.cart.borrow_mut().cpu_read(addr).unwrap_or(self.open_bus),
+            _ => self.open_bus,
+        }
+    }
+
+    // The mapper's M2-driven counters and sound chip, the APU and the coin
+    // timers tick first, then the PPU runs the dots that fall within this
+    // CPU cycle. An NMI reaches the CPU a cycle after the PPU raises it, so
+    // a $2002 read or PPUCTRL write racing it in the same cycle can still
+    // call it off.
+    fn tick(&mut self) {
+        {
+            let mut cart = self.cart.borrow_mut();
+            cart.cpu_clock();
+            if let Some(chip) = cart.expansion_audio() {
+                chip.clock();
+                self.apu.mix_expansion(chip.source(), chip.output());
+            }
+        }
+        self.apu.clock();
+        if let Some(vs) = &mut self.vs {
+            vs.clock(1);
+        }
+        self.nmi |= std::mem::take(&mut self.ppu.nmi_occurred);
+        for _ in 0..self.clock.cpu_cycle() {
+            self.frame_complete |= self.ppu.step();
+        }
+        if self.ppu.record_events {
+            let irq = self.irq_asserted();
+            if irq && !self.irq_logged {
+                self.ppu.log_event(PpuEventKind::Irq);
+            }
+            self.irq_logged = irq;
+        }
+    }
+
+    fn take_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi)
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.apu.irq_asserted() || self.cart.borrow().irq_asserted()
+    }
+
+    fn take_oam_dma(&mut self) -> Option<u8> {
+        self.oam_dma.take()
+    }
+}
@@ -0,0 +1,72 @@
+// src/nes/cart/archive.rs
+// Reading ROM images from plain files, .zip archives, and .gz streams
+
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use crate::nes::cart::RomError;
+
+const ZIP_MAGIC: [u8; 4] = [b'P', b'K', 0x03, 0x04];
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ROM_EXTENSIONS: [&str; 3] = ["nes", "fds", "nsf"];
+
+/// Separator between an archive path and an entry inside it, e.g. `games.zip#smb.nes`
+const INNER_PATH_SEPARATOR: char = '#';
+
+/// Split a command-line ROM argument into the file path and optional archive entry
+pub fn split_inner_path(arg: &str) -> (PathBuf, Option<String>) {
+    if !Path::new(arg).exists() {
+        if let Some((outer, inner)) = arg.rsplit_once(INNER_PATH_SEPARATOR) {
+            return (PathBuf::from(outer), Some(inner.to_string()));
+        }
+    }
+    (PathBuf::from(arg), None)
+}
+
+/// Read a ROM image into memory, transparently decompressing archives. For
+/// zip files, `inner` names the entry to use; otherwise the first entry with
+/// a .nes/.fds/.nsf extension is picked.
+pub fn read_image(path: &Path, inner: Option<&str>) -> Result<Vec<u8>, RomError> {
+    let data = fs::read(path)?;
+
+    if data.starts_with(&ZIP_MAGIC) {
+        read_zip_entry(data, inner)
+    } else if data.starts_with(&GZIP_MAGIC) {
+        let mut image = Vec::new();
+        GzDecoder::new(data.as_slice()).read_to_end(&mut image)?;
+        Ok(image)
+    } else {
+        Ok(data)
+    }
+}
+
+fn read_zip_entry(data: Vec<u8>, inner: Option<&str>) -> Result<Vec<u8>, RomError> {
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(|err| RomError::Archive(err.to_string()))?;
+
+    let name = match inner {
+        Some(inner) => inner.to_string(),
+        None => archive
+            .file_names()
+            .filter(|name| has_rom_extension(name))
+            .min_by_key(|name| archive.index_for_name(name))
+            .map(str::to_string)
+            .ok_or(RomError::NoRomInArchive)?,
+    };
+
+    let mut entry = archive.by_name(&name).map_err(|err| RomError::Archive(format!("{}: {}", name, err)))?;
+    // The header's size is only a claim, so let the read grow the buffer
+    let mut image = Vec::new();
+    entry.read_to_end(&mut image)?;
+    Ok(image)
+}
+
+fn has_rom_extension(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ROM_EXTENSIONS.iter().any(|rom_ext| ext.eq_ignore_ascii_case(rom_ext)))
+}
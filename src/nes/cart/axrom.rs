@@ -0,0 +1,66 @@
+// src/nes/cart/axrom.rs
+// AxROM (mapper 7): switchable 32KB PRG bank and single-screen mirroring select
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::mapper::has_bus_conflicts;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const PRG_BANK_SIZE: usize = 0x8000; // 32KB
+
+pub struct Axrom {
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    prg_bank: u8,
+    mirroring: Mirroring,
+    bus_conflicts: bool,
+}
+
+impl Axrom {
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            bus_conflicts: has_bus_conflicts(&rom),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            prg_bank: 0,
+            mirroring: Mirroring::SingleScreenLow,
+        }
+    }
+}
+
+impl Mapper for Axrom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x8000..=0xFFFF => {
+                let offset = (self.prg_bank & 0x07) as usize * PRG_BANK_SIZE + (addr as usize - 0x8000);
+                Some(self.prg_rom[offset % self.prg_rom.len()])
+            }
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if addr >= 0x8000 {
+            let data = if self.bus_conflicts { data & self.cpu_read(addr).unwrap_or(data) } else { data };
+            self.prg_bank = data & 0x07;
+            // Bit 4 selects which 1KB of console VRAM backs all four nametables
+            self.mirroring = if data & 0x10 != 0 {
+                Mirroring::SingleScreenHigh
+            } else {
+                Mirroring::SingleScreenLow
+            };
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(addr as usize)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.chr.write(addr as usize, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
@@ -0,0 +1,42 @@
+// src/nes/cart/battery.rs
+// Persisting battery-backed cartridge RAM to .sav files
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::nes::cart::SharedMapper;
+
+/// Save file location for a ROM: same directory and stem, `.sav` extension
+pub fn save_path(rom_path: &Path) -> PathBuf {
+    rom_path.with_extension("sav")
+}
+
+/// Fill the board's battery RAM from `path`. Returns `Ok(false)` if the board
+/// has no battery or no save exists yet.
+pub fn load_battery_ram(mapper: &SharedMapper, path: &Path) -> io::Result<bool> {
+    let mut mapper = mapper.borrow_mut();
+    let Some(ram) = mapper.battery_ram_mut() else {
+        return Ok(false);
+    };
+
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let len = data.len().min(ram.len());
+    ram[..len].copy_from_slice(&data[..len]);
+    Ok(true)
+}
+
+/// Write the board's battery RAM to `path`. Returns `Ok(false)` if the board has no battery.
+pub fn save_battery_ram(mapper: &SharedMapper, path: &Path) -> io::Result<bool> {
+    let mapper = mapper.borrow();
+    let Some(ram) = mapper.battery_ram() else {
+        return Ok(false);
+    };
+    fs::write(path, ram)?;
+    Ok(true)
+}
@@ -0,0 +1,37 @@
+// src/nes/cart/chr.rs
+// Pattern table storage: CHR ROM, or writable CHR RAM when the cart has none
+
+const CHR_RAM_SIZE: usize = 0x2000; // 8KB
+
+pub struct ChrMemory {
+    data: Vec<u8>,
+    is_ram: bool,
+}
+
+impl ChrMemory {
+    /// Use the CHR ROM image, or allocate CHR RAM (at least 8KB) if it is empty
+    pub fn new(chr_rom: Vec<u8>, ram_size: usize) -> Self {
+        if chr_rom.is_empty() {
+            Self {
+                data: vec![0; ram_size.max(CHR_RAM_SIZE)],
+                is_ram: true,
+            }
+        } else {
+            Self {
+                data: chr_rom,
+                is_ram: false,
+            }
+        }
+    }
+
+    pub fn read(&self, offset: usize) -> u8 {
+        self.data[offset % self.data.len()]
+    }
+
+    pub fn write(&mut self, offset: usize, data: u8) {
+        if self.is_ram {
+            let len = self.data.len();
+            self.data[offset % len] = data;
+        }
+    }
+}
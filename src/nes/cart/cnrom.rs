@@ -0,0 +1,64 @@
+// src/nes/cart/cnrom.rs
+// CNROM (mapper 3): fixed PRG ROM, switchable 8KB CHR ROM bank
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::mapper::has_bus_conflicts;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const CHR_BANK_SIZE: usize = 0x2000; // 8KB
+
+pub struct Cnrom {
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    mirroring: Mirroring,
+    bus_conflicts: bool,
+    chr_bank: u8,
+}
+
+impl Cnrom {
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            bus_conflicts: has_bus_conflicts(&rom),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            mirroring: rom.mirroring,
+            chr_bank: 0,
+        }
+    }
+}
+
+impl Cnrom {
+    fn chr_offset(&self, addr: u16) -> usize {
+        self.chr_bank as usize * CHR_BANK_SIZE + (addr as usize & (CHR_BANK_SIZE - 1))
+    }
+}
+
+impl Mapper for Cnrom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            // 16KB images are mirrored into $C000-$FFFF
+            0x8000..=0xFFFF => Some(self.prg_rom[(addr as usize - 0x8000) % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if addr >= 0x8000 {
+            self.chr_bank = if self.bus_conflicts { data & self.cpu_read(addr).unwrap_or(data) } else { data };
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(self.chr_offset(addr))
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let offset = self.chr_offset(addr);
+        self.chr.write(offset, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
@@ -0,0 +1,65 @@
+// src/nes/cart/db.rs
+// Compiled-in ROM database for fixing bad iNES headers in old dumps
+
+use crate::nes::cart::Rom;
+use crate::nes::ppu::Mirroring;
+
+const BATTERY_RAM_SIZE: usize = 0x2000; // 8KB
+
+/// Known-good board description, keyed by CRC32 of PRG ROM + CHR ROM
+struct DbEntry {
+    crc32: u32,
+    name: &'static str,
+    mapper: u16,
+    /// `None` for boards whose mapper controls mirroring
+    mirroring: Option<Mirroring>,
+    battery: bool,
+}
+
+static ROM_DB: &[DbEntry] = &[
+    DbEntry { crc32: 0x3337_EC46, name: "Super Mario Bros.", mapper: 0, mirroring: Some(Mirroring::Vertical), battery: false },
+    DbEntry { crc32: 0x3FE2_72FB, name: "The Legend of Zelda", mapper: 1, mirroring: None, battery: true },
+];
+
+fn lookup(crc32: u32) -> Option<&'static DbEntry> {
+    ROM_DB.iter().find(|entry| entry.crc32 == crc32)
+}
+
+/// Overwrite header fields that disagree with the database entry for this
+/// image. Returns a description of what changed, or `None` if nothing did.
+pub fn apply_corrections(rom: &mut Rom) -> Option<String> {
+    let entry = lookup(rom.crc32)?;
+    let mut changes = Vec::new();
+
+    if rom.mapper != entry.mapper {
+        changes.push(format!("mapper {} -> {}", rom.mapper, entry.mapper));
+        rom.mapper = entry.mapper;
+    }
+
+    if let Some(mirroring) = entry.mirroring {
+        if rom.mirroring != mirroring {
+            changes.push(format!("mirroring {:?} -> {:?}", rom.mirroring, mirroring));
+            rom.mirroring = mirroring;
+        }
+    }
+
+    if rom.battery != entry.battery {
+        changes.push(format!("battery {} -> {}", rom.battery, entry.battery));
+        rom.battery = entry.battery;
+        // Keep the RAM split consistent with the corrected flag
+        let ram_size = rom.work_ram_size().max(BATTERY_RAM_SIZE);
+        if entry.battery {
+            rom.prg_ram_size = 0;
+            rom.prg_nvram_size = ram_size;
+        } else {
+            rom.prg_ram_size = ram_size;
+            rom.prg_nvram_size = 0;
+        }
+    }
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(format!("{}: {}", entry.name, changes.join(", ")))
+    }
+}
@@ -0,0 +1,247 @@
+// src/nes/cart/fds_audio.rs
+// Famicom Disk System sound ($4040-$408A): 64-step wavetable with a modulation unit and two envelopes
+
+use crate::nes::apu::{AudioSource, ExpansionAudio};
+
+// Full output (wave 63 at gain 32, master volume 2/2), about 2.4 times a
+// 2A03 pulse at volume 15
+const OUTPUT_SCALE: f32 = 0.36 / 2016.0;
+
+// Master volume from $4089: 2/2, 2/3, 2/4 and 2/5
+const MASTER_VOLUME: [f32; 4] = [1.0, 2.0 / 3.0, 0.5, 0.4];
+
+// Modulation counter steps for each table value; 4 resets the counter
+const MOD_STEPS: [i8; 8] = [0, 1, 2, 4, 0, -4, -2, -1];
+
+/// The volume or modulation envelope ($4080/$4084)
+#[derive(Clone, Debug, Default)]
+struct Envelope {
+    speed: u8,
+    increase: bool,
+    disabled: bool, // Gain is set directly from the register
+    gain: u8,
+    timer: u32,
+}
+
+impl Envelope {
+    fn write(&mut self, data: u8, master_speed: u8) {
+        self.speed = data & 0x3F;
+        self.increase = data & 0x40 != 0;
+        self.disabled = data & 0x80 != 0;
+        if self.disabled {
+            self.gain = self.speed;
+        }
+        self.reset_timer(master_speed);
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (self.speed as u32 + 1) * master_speed as u32;
+    }
+
+    // Gain moves one step toward 0 or 32 each period
+    fn clock(&mut self, master_speed: u8) {
+        if self.disabled || master_speed == 0 {
+            return;
+        }
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.reset_timer(master_speed);
+            if self.increase && self.gain < 32 {
+                self.gain += 1;
+            } else if !self.increase && self.gain > 0 {
+                self.gain -= 1;
+            }
+        }
+    }
+}
+
+/// The sound unit inside the RAM adapter. Its registers are mapped through
+/// `read`/`write` and it's mixed as expansion audio: by the NSF player for
+/// files that declare it, and by a disk system board once there is one.
+#[derive(Clone, Debug)]
+pub struct FdsAudio {
+    wave_table: [u8; 64], // 6-bit samples
+    wave_write: bool,     // $4089 bit 7: the table is writable and playback holds
+    wave_halt: bool,
+    wave_freq: u16,
+    wave_accumulator: u32,
+    wave_position: u8,
+    wave_output: u8, // Sample latched from the table, held while writing it
+    envelopes_halted: bool,
+    volume: Envelope,
+    mod_envelope: Envelope,
+    mod_table: [u8; 64], // Each $4088 write fills two entries
+    mod_halt: bool,
+    mod_freq: u16,
+    mod_accumulator: u32,
+    mod_position: u8,
+    mod_counter: i8, // 7-bit signed
+    master_volume: u8,
+    master_speed: u8, // $408A
+}
+
+impl Default for FdsAudio {
+    fn default() -> Self {
+        Self {
+            wave_table: [0; 64],
+            wave_write: false,
+            wave_halt: true,
+            wave_freq: 0,
+            wave_accumulator: 0,
+            wave_position: 0,
+            wave_output: 0,
+            envelopes_halted: false,
+            volume: Envelope::default(),
+            mod_envelope: Envelope::default(),
+            mod_table: [0; 64],
+            mod_halt: true,
+            mod_freq: 0,
+            mod_accumulator: 0,
+            mod_position: 0,
+            mod_counter: 0,
+            master_volume: 0,
+            // The BIOS writes $E8 at boot
+            master_speed: 0xE8,
+        }
+    }
+}
+
+impl FdsAudio {
+    /// CPU read from $4040-$4097: the wavetable and the two gains. Other
+    /// addresses aren't driven.
+    pub fn read(&self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4040..=0x407F => Some(self.wave_table[addr as usize - 0x4040]),
+            0x4090 => Some(self.volume.gain),
+            0x4092 => Some(self.mod_envelope.gain),
+            _ => None,
+        }
+    }
+
+    /// CPU write to $4040-$408A
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4040..=0x407F if self.wave_write => self.wave_table[addr as usize - 0x4040] = data & 0x3F,
+            0x4080 => self.volume.write(data, self.master_speed),
+            0x4082 => self.wave_freq = (self.wave_freq & 0x0F00) | data as u16,
+            0x4083 => {
+                self.wave_freq = (self.wave_freq & 0x00FF) | ((data & 0x0F) as u16) << 8;
+                self.wave_halt = data & 0x80 != 0;
+                self.envelopes_halted = data & 0x40 != 0;
+                if self.wave_halt {
+                    self.wave_accumulator = 0;
+                    self.wave_position = 0;
+                }
+                if self.envelopes_halted {
+                    self.volume.reset_timer(self.master_speed);
+                    self.mod_envelope.reset_timer(self.master_speed);
+                }
+            }
+            0x4084 => self.mod_envelope.write(data, self.master_speed),
+            0x4085 => self.mod_counter = sign_extend_7(data),
+            0x4086 => self.mod_freq = (self.mod_freq & 0x0F00) | data as u16,
+            0x4087 => {
+                self.mod_freq = (self.mod_freq & 0x00FF) | ((data & 0x0F) as u16) << 8;
+                self.mod_halt = data & 0x80 != 0;
+                if self.mod_halt {
+                    self.mod_accumulator = 0;
+                }
+            }
+            // The table only takes writes while the unit is halted
+            0x4088 if self.mod_halt => {
+                let position = self.mod_position as usize;
+                self.mod_table[position] = data & 0x07;
+                self.mod_table[position + 1] = data & 0x07;
+                self.mod_position = (self.mod_position + 2) & 0x3F;
+            }
+            0x4089 => {
+                self.wave_write = data & 0x80 != 0;
+                self.master_volume = data & 0x03;
+            }
+            0x408A => {
+                self.master_speed = data;
+                self.volume.reset_timer(data);
+                self.mod_envelope.reset_timer(data);
+            }
+            _ => {}
+        }
+    }
+
+    // The modulator's pitch offset: the counter scaled by its gain, with
+    // the hardware's rounding, then applied to the carrier frequency
+    fn mod_pitch(&self) -> i32 {
+        let mut temp = self.mod_counter as i32 * self.mod_envelope.gain as i32;
+        let remainder = temp & 0x0F;
+        temp >>= 4;
+        if remainder > 0 && temp & 0x80 == 0 {
+            temp += if self.mod_counter < 0 { -1 } else { 2 };
+        }
+        if temp >= 192 {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+        temp *= self.wave_freq as i32;
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if remainder >= 32 {
+            temp += 1;
+        }
+        temp
+    }
+
+    fn clock_modulator(&mut self) {
+        if self.mod_halt || self.mod_freq == 0 {
+            return;
+        }
+        self.mod_accumulator += self.mod_freq as u32;
+        if self.mod_accumulator > 0xFFFF {
+            self.mod_accumulator -= 0x10000;
+            let value = self.mod_table[self.mod_position as usize];
+            self.mod_counter = if value == 4 {
+                0
+            } else {
+                sign_extend_7((self.mod_counter + MOD_STEPS[value as usize]) as u8)
+            };
+            self.mod_position = (self.mod_position + 1) & 0x3F;
+        }
+    }
+}
+
+// The modulation counter wraps within 7 bits
+fn sign_extend_7(data: u8) -> i8 {
+    ((data << 1) as i8) >> 1
+}
+
+impl ExpansionAudio for FdsAudio {
+    fn source(&self) -> AudioSource {
+        AudioSource::Fds
+    }
+
+    fn clock(&mut self) {
+        if !self.envelopes_halted && !self.wave_halt {
+            self.volume.clock(self.master_speed);
+            self.mod_envelope.clock(self.master_speed);
+        }
+
+        self.clock_modulator();
+        if self.wave_write {
+            return;
+        }
+        if !self.wave_halt {
+            let pitch = (self.wave_freq as i32 + self.mod_pitch()).max(0) as u32;
+            self.wave_accumulator += pitch;
+            if self.wave_accumulator > 0xFFFF {
+                self.wave_accumulator &= 0xFFFF;
+                self.wave_position = (self.wave_position + 1) & 0x3F;
+            }
+        }
+        self.wave_output = self.wave_table[self.wave_position as usize];
+    }
+
+    // Gain above 32 plays as 32
+    fn output(&self) -> f32 {
+        let level = self.wave_output as u32 * self.volume.gain.min(32) as u32;
+        level as f32 * OUTPUT_SCALE * MASTER_VOLUME[self.master_volume as usize]
+    }
+}
@@ -0,0 +1,175 @@
+// src/nes/cart/fme7.rs
+// Sunsoft FME-7 (mapper 69): command/parameter banking, WRAM control, CPU-cycle IRQ and 5B audio
+
+use crate::nes::apu::ExpansionAudio;
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::prg_ram::PrgRam;
+use crate::nes::cart::sunsoft5b::Sunsoft5b;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const PRG_BANK_SIZE: usize = 0x2000; // 8KB
+const CHR_BANK_SIZE: usize = 0x0400; // 1KB
+
+pub struct Fme7 {
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    prg_ram: PrgRam,
+
+    command: u8,
+    chr_banks: [u8; 8],
+    prg_banks: [u8; 3],
+    // $6000-$7FFF: bank number, RAM-vs-ROM select, RAM enable
+    wram_bank: u8,
+    wram_select: bool,
+    wram_enable: bool,
+    mirroring: Mirroring,
+
+    irq_enabled: bool,
+    irq_counter_enabled: bool,
+    irq_counter: u16,
+    irq_pending: bool,
+
+    // Only the 5B variant has the sound chip, but nothing else decodes
+    // $C000-$FFFF writes, so every board gets one
+    audio: Sunsoft5b,
+}
+
+impl Fme7 {
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            // Boards without declared RAM still decode the WRAM select
+            prg_ram: PrgRam::with_min_size(&rom, PRG_BANK_SIZE),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            command: 0,
+            chr_banks: [0; 8],
+            prg_banks: [0; 3],
+            wram_bank: 0,
+            wram_select: false,
+            wram_enable: false,
+            mirroring: rom.mirroring,
+            irq_enabled: false,
+            irq_counter_enabled: false,
+            irq_counter: 0,
+            irq_pending: false,
+            audio: Sunsoft5b::default(),
+        }
+    }
+
+    fn prg_rom_read(&self, bank: usize, addr: u16) -> u8 {
+        let offset = bank * PRG_BANK_SIZE + (addr as usize & (PRG_BANK_SIZE - 1));
+        self.prg_rom[offset % self.prg_rom.len()]
+    }
+
+    fn chr_offset(&self, addr: u16) -> usize {
+        let bank = self.chr_banks[(addr as usize / CHR_BANK_SIZE) & 0x07] as usize;
+        bank * CHR_BANK_SIZE + (addr as usize & (CHR_BANK_SIZE - 1))
+    }
+
+    fn wram_offset(&self, addr: u16) -> usize {
+        self.wram_bank as usize * PRG_BANK_SIZE + (addr as usize & (PRG_BANK_SIZE - 1))
+    }
+
+    fn write_parameter(&mut self, data: u8) {
+        match self.command {
+            0x0..=0x7 => self.chr_banks[self.command as usize] = data,
+            0x8 => {
+                self.wram_bank = data & 0x3F;
+                self.wram_select = data & 0x40 != 0;
+                self.wram_enable = data & 0x80 != 0;
+            }
+            0x9..=0xB => self.prg_banks[(self.command - 0x9) as usize] = data & 0x3F,
+            0xC => {
+                self.mirroring = match data & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLow,
+                    _ => Mirroring::SingleScreenHigh,
+                };
+            }
+            0xD => {
+                self.irq_enabled = data & 0x01 != 0;
+                self.irq_counter_enabled = data & 0x80 != 0;
+                self.irq_pending = false;
+            }
+            0xE => self.irq_counter = (self.irq_counter & 0xFF00) | data as u16,
+            _ => self.irq_counter = (self.irq_counter & 0x00FF) | (data as u16) << 8,
+        }
+    }
+}
+
+impl Mapper for Fme7 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7FFF if self.wram_select && self.wram_enable => {
+                self.prg_ram.read(self.wram_offset(addr))
+            }
+            0x6000..=0x7FFF if self.wram_select => None,
+            0x6000..=0x7FFF => Some(self.prg_rom_read(self.wram_bank as usize, addr)),
+            0x8000..=0xDFFF => {
+                let slot = (addr as usize - 0x8000) / PRG_BANK_SIZE;
+                Some(self.prg_rom_read(self.prg_banks[slot] as usize, addr))
+            }
+            0xE000..=0xFFFF => {
+                let last = (self.prg_rom.len() / PRG_BANK_SIZE).max(1) - 1;
+                Some(self.prg_rom_read(last, addr))
+            }
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF if self.wram_select && self.wram_enable => {
+                let offset = self.wram_offset(addr);
+                self.prg_ram.write(offset, data);
+            }
+            0x8000..=0x9FFF => self.command = data & 0x0F,
+            0xA000..=0xBFFF => self.write_parameter(data),
+            0xC000..=0xDFFF => self.audio.select(data),
+            0xE000..=0xFFFF => self.audio.write(data),
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(self.chr_offset(addr))
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let offset = self.chr_offset(addr);
+        self.chr.write(offset, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.prg_ram.battery_data()
+    }
+
+    fn battery_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.prg_ram.battery_data_mut()
+    }
+
+    fn cpu_clock(&mut self) {
+        if !self.irq_counter_enabled {
+            return;
+        }
+        // The IRQ fires when the 16-bit down counter wraps from $0000 to $FFFF
+        self.irq_counter = self.irq_counter.wrapping_sub(1);
+        if self.irq_counter == 0xFFFF && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.irq_pending
+    }
+
+    fn expansion_audio(&mut self) -> Option<&mut dyn ExpansionAudio> {
+        Some(&mut self.audio)
+    }
+}
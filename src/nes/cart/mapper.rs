@@ -0,0 +1,96 @@
+// src/nes/cart/mapper.rs
+// Cartridge mapper interface and board selection
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::nes::apu::ExpansionAudio;
+use crate::nes::cart::axrom::Axrom;
+use crate::nes::cart::cnrom::Cnrom;
+use crate::nes::cart::fme7::Fme7;
+use crate::nes::cart::mmc1::Mmc1;
+use crate::nes::cart::mmc2::{Mmc2, Mmc2Chip};
+use crate::nes::cart::nrom::Nrom;
+use crate::nes::cart::uxrom::Uxrom;
+use crate::nes::cart::vrc4::{Vrc4, VrcWiring};
+use crate::nes::cart::{Rom, RomError};
+use crate::nes::ppu::Mirroring;
+
+/// Cartridge board logic sitting between the console buses and the ROM/RAM chips
+pub trait Mapper {
+    /// CPU read from cartridge space ($4020-$FFFF); `None` when the board
+    /// doesn't drive the data bus and the CPU sees open bus
+    fn cpu_read(&mut self, addr: u16) -> Option<u8>;
+    /// CPU write to cartridge space ($4020-$FFFF)
+    fn cpu_write(&mut self, addr: u16, data: u8);
+    /// PPU read from pattern table space ($0000-$1FFF)
+    fn ppu_read(&mut self, addr: u16) -> u8;
+    /// PPU write to pattern table space ($0000-$1FFF)
+    fn ppu_write(&mut self, addr: u16, data: u8);
+    /// Current nametable mirroring arrangement
+    fn mirroring(&self) -> Mirroring;
+
+    /// Notification after each PPU pattern fetch, for boards that latch on the PPU address bus
+    fn ppu_fetch(&mut self, _addr: u16) {}
+
+    /// PPU A12 rose after staying low long enough to get through the MMC3's
+    /// filter, the edge its scanline counter clocks on
+    fn ppu_a12_rise(&mut self) {}
+
+    /// Advance board logic by one CPU cycle (IRQ counters)
+    fn cpu_clock(&mut self) {}
+
+    /// Battery-backed RAM to persist between sessions, if the board has any
+    fn battery_ram(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn battery_ram_mut(&mut self) -> Option<&mut [u8]> {
+        None
+    }
+
+    /// Whether the board is currently pulling the CPU IRQ line low
+    fn irq_asserted(&self) -> bool {
+        false
+    }
+
+    /// The board's sound chip, if it has one
+    fn expansion_audio(&mut self) -> Option<&mut dyn ExpansionAudio> {
+        None
+    }
+}
+
+/// NES 2.0 submapper 2 marks discrete-logic boards (UxROM, CNROM, AxROM) whose
+/// register writes collide with the ROM output, ANDing the two values.
+pub fn has_bus_conflicts(rom: &Rom) -> bool {
+    rom.submapper == 2
+}
+
+/// Mapper handle shared between the CPU bus and the PPU
+pub type SharedMapper = Rc<RefCell<dyn Mapper>>;
+
+/// Build the mapper declared by the ROM header
+pub fn new_mapper(rom: Rom) -> Result<SharedMapper, RomError> {
+    // These boards have no work RAM to hold a trainer
+    if rom.trainer.is_some() && matches!(rom.mapper, 2 | 3 | 7 | 9) {
+        warn!("Mapper {} has no work RAM at $7000; ignoring the trainer", rom.mapper);
+    }
+    let mapper: SharedMapper = match rom.mapper {
+        0 => Rc::new(RefCell::new(Nrom::new(rom))),
+        1 => Rc::new(RefCell::new(Mmc1::new(rom))),
+        2 => Rc::new(RefCell::new(Uxrom::new(rom))),
+        3 => Rc::new(RefCell::new(Cnrom::new(rom))),
+        7 => Rc::new(RefCell::new(Axrom::new(rom))),
+        9 => Rc::new(RefCell::new(Mmc2::new(rom, Mmc2Chip::Mmc2))),
+        10 => Rc::new(RefCell::new(Mmc2::new(rom, Mmc2Chip::Mmc4))),
+        21 | 22 | 23 | 25 => {
+            let (chip, wiring) = VrcWiring::for_board(rom.mapper, rom.submapper);
+            Rc::new(RefCell::new(Vrc4::new(rom, chip, wiring)))
+        }
+        69 => Rc::new(RefCell::new(Fme7::new(rom))),
+        id => return Err(RomError::UnsupportedMapper(id)),
+    };
+    Ok(mapper)
+}
@@ -0,0 +1,197 @@
+// src/nes/cart/mmc1.rs
+// MMC1 (mapper 1): serial-loaded PRG/CHR banking and mirroring control
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::prg_ram::PrgRam;
+use crate::nes::cart::{Mapper, Rom, RomFormat};
+use crate::nes::ppu::Mirroring;
+
+const PRG_BANK_SIZE: usize = 0x4000; // 16KB
+const CHR_BANK_SIZE: usize = 0x1000; // 4KB
+const PRG_RAM_BANK_SIZE: usize = 0x2000; // 8KB
+const SUROM_PRG_SIZE: usize = 0x80000; // 512KB
+
+/// SxROM boards that repurpose CHR bank bits when CHR is RAM
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mmc1Board {
+    Standard,
+    Surom, // CHR bank bit 4 selects the 256KB PRG half
+    Sorom, // CHR bank bit 3 selects the 8KB PRG RAM bank
+    Sxrom, // Both of the above; bits 2-3 select PRG RAM
+    Serom, // 32KB PRG wired straight through, PRG banking ignored
+}
+
+impl Mmc1Board {
+    /// NES 2.0 submappers name the board outright; plain iNES images can
+    /// only be told apart by PRG size
+    fn detect(rom: &Rom) -> Self {
+        match (rom.format, rom.submapper) {
+            (RomFormat::Nes20, 1) => Mmc1Board::Surom,
+            (RomFormat::Nes20, 2) => Mmc1Board::Sorom,
+            (RomFormat::Nes20, 4) => Mmc1Board::Sxrom,
+            (RomFormat::Nes20, 5) => Mmc1Board::Serom,
+            (RomFormat::INes, _) if rom.prg_rom.len() >= SUROM_PRG_SIZE => Mmc1Board::Surom,
+            _ => Mmc1Board::Standard,
+        }
+    }
+}
+
+pub struct Mmc1 {
+    board: Mmc1Board,
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    prg_ram: PrgRam,
+
+    // Serial load state
+    shift: u8,
+    shift_count: u8,
+
+    // Internal registers
+    control: u8,
+    chr_bank0: u8,
+    chr_bank1: u8,
+    prg_bank: u8,
+}
+
+impl Mmc1 {
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            board: Mmc1Board::detect(&rom),
+            prg_ram: PrgRam::new(&rom),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            shift: 0,
+            shift_count: 0,
+            control: 0x0C, // PRG mode 3 at power-on: last bank fixed at $C000
+            chr_bank0: 0,
+            chr_bank1: 0,
+            prg_bank: 0,
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x8000..=0x9FFF => self.control = data,
+            0xA000..=0xBFFF => self.chr_bank0 = data,
+            0xC000..=0xDFFF => self.chr_bank1 = data,
+            _ => {
+                self.prg_bank = data;
+                // Bit 4 clear enables work RAM (MMC1B and later)
+                self.prg_ram.set_enabled(data & 0x10 == 0);
+            }
+        }
+    }
+
+    fn prg_offset(&self, addr: u16) -> usize {
+        if self.board == Mmc1Board::Serom {
+            return addr as usize - 0x8000;
+        }
+
+        let bank_count = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let bank = (self.prg_bank & 0x0F) as usize;
+        let slot = (addr as usize - 0x8000) / PRG_BANK_SIZE;
+
+        let selected = match (self.control >> 2) & 0x03 {
+            // 32KB mode: ignore the low bank bit
+            0 | 1 => (bank & !1) + slot,
+            // Fix first bank at $8000, switch $C000
+            2 => if slot == 0 { 0 } else { bank },
+            // Switch $8000, fix last bank (of the current 256KB half) at $C000
+            _ => if slot == 0 { bank } else { (bank_count - 1).min(0x0F) },
+        };
+
+        // SUROM/SXROM: the fixed banks follow the outer 256KB select too
+        let outer = match self.board {
+            Mmc1Board::Surom | Mmc1Board::Sxrom => (self.chr_bank0 & 0x10) as usize,
+            _ => 0,
+        };
+
+        ((outer | selected) % bank_count) * PRG_BANK_SIZE + (addr as usize & (PRG_BANK_SIZE - 1))
+    }
+
+    fn prg_ram_offset(&self, addr: u16) -> usize {
+        let bank = match self.board {
+            Mmc1Board::Sorom => (self.chr_bank0 >> 3) & 0x01,
+            Mmc1Board::Sxrom => (self.chr_bank0 >> 2) & 0x03,
+            _ => 0,
+        };
+        bank as usize * PRG_RAM_BANK_SIZE + (addr as usize - 0x6000)
+    }
+
+    fn chr_offset(&self, addr: u16) -> usize {
+        let selected = if self.control & 0x10 == 0 {
+            // 8KB mode: ignore the low bank bit
+            (self.chr_bank0 & !1) as usize + (addr as usize / CHR_BANK_SIZE)
+        } else if addr < 0x1000 {
+            self.chr_bank0 as usize
+        } else {
+            self.chr_bank1 as usize
+        };
+
+        selected * CHR_BANK_SIZE + (addr as usize & (CHR_BANK_SIZE - 1))
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram.read(self.prg_ram_offset(addr)),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(addr) % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF => {
+                let offset = self.prg_ram_offset(addr);
+                self.prg_ram.write(offset, data);
+            }
+            0x8000..=0xFFFF => {
+                // Bit 7 resets the shift register and locks PRG mode 3
+                if data & 0x80 != 0 {
+                    self.shift = 0;
+                    self.shift_count = 0;
+                    self.control |= 0x0C;
+                    return;
+                }
+
+                // Five writes load a register, LSB first
+                self.shift |= (data & 0x01) << self.shift_count;
+                self.shift_count += 1;
+                if self.shift_count == 5 {
+                    self.write_register(addr, self.shift);
+                    self.shift = 0;
+                    self.shift_count = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(self.chr_offset(addr))
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let offset = self.chr_offset(addr);
+        self.chr.write(offset, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SingleScreenLow,
+            1 => Mirroring::SingleScreenHigh,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        }
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.prg_ram.battery_data()
+    }
+
+    fn battery_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.prg_ram.battery_data_mut()
+    }
+}
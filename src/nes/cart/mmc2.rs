@@ -0,0 +1,142 @@
+// src/nes/cart/mmc2.rs
+// MMC2 (mapper 9) / MMC4 (mapper 10): CHR banks switched by PPU fetches of tiles $FD/$FE
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::prg_ram::PrgRam;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const CHR_BANK_SIZE: usize = 0x1000; // 4KB
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mmc2Chip {
+    /// Punch-Out!!: 8KB switchable PRG bank, three fixed 8KB banks
+    Mmc2,
+    /// Fire Emblem / Famicom Wars: 16KB switchable PRG bank, fixed last 16KB
+    Mmc4,
+}
+
+pub struct Mmc2 {
+    chip: Mmc2Chip,
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    prg_ram: PrgRam,
+
+    prg_bank: u8,
+    // CHR bank pairs indexed by latch state: [$FD, $FE]
+    chr_banks0: [u8; 2],
+    chr_banks1: [u8; 2],
+    latch0: usize,
+    latch1: usize,
+    mirroring: Mirroring,
+}
+
+impl Mmc2 {
+    pub fn new(rom: Rom, chip: Mmc2Chip) -> Self {
+        Self {
+            chip,
+            prg_ram: if chip == Mmc2Chip::Mmc4 {
+                PrgRam::new(&rom)
+            } else {
+                PrgRam::with_size(0, false)
+            },
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            prg_bank: 0,
+            chr_banks0: [0; 2],
+            chr_banks1: [0; 2],
+            latch0: 1,
+            latch1: 1,
+            mirroring: rom.mirroring,
+        }
+    }
+
+    fn prg_offset(&self, addr: u16) -> usize {
+        let addr = addr as usize - 0x8000;
+        let (bank_size, slot_count) = match self.chip {
+            Mmc2Chip::Mmc2 => (0x2000, 4),
+            Mmc2Chip::Mmc4 => (0x4000, 2),
+        };
+        let bank_count = (self.prg_rom.len() / bank_size).max(slot_count);
+        let slot = addr / bank_size;
+
+        // Only the first slot switches; the rest map the final banks in order
+        let bank = if slot == 0 {
+            self.prg_bank as usize % bank_count
+        } else {
+            bank_count - slot_count + slot
+        };
+        bank * bank_size + (addr % bank_size)
+    }
+
+    fn chr_offset(&self, addr: u16) -> usize {
+        let bank = if addr < 0x1000 {
+            self.chr_banks0[self.latch0]
+        } else {
+            self.chr_banks1[self.latch1]
+        };
+        bank as usize * CHR_BANK_SIZE + (addr as usize & (CHR_BANK_SIZE - 1))
+    }
+}
+
+impl Mapper for Mmc2 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram.read(addr as usize - 0x6000),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(addr) % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram.write(addr as usize - 0x6000, data),
+            0xA000..=0xAFFF => self.prg_bank = data & 0x0F,
+            0xB000..=0xBFFF => self.chr_banks0[0] = data & 0x1F,
+            0xC000..=0xCFFF => self.chr_banks0[1] = data & 0x1F,
+            0xD000..=0xDFFF => self.chr_banks1[0] = data & 0x1F,
+            0xE000..=0xEFFF => self.chr_banks1[1] = data & 0x1F,
+            0xF000..=0xFFFF => {
+                self.mirroring = if data & 0x01 != 0 {
+                    Mirroring::Horizontal
+                } else {
+                    Mirroring::Vertical
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(self.chr_offset(addr))
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let offset = self.chr_offset(addr);
+        self.chr.write(offset, data);
+    }
+
+    fn ppu_fetch(&mut self, addr: u16) {
+        // The latch flips after the triggering fetch completes. MMC2 only
+        // watches a single address for the low table, MMC4 a full tile row.
+        match (addr, self.chip) {
+            (0x0FD8, _) | (0x0FD9..=0x0FDF, Mmc2Chip::Mmc4) => self.latch0 = 0,
+            (0x0FE8, _) | (0x0FE9..=0x0FEF, Mmc2Chip::Mmc4) => self.latch0 = 1,
+            (0x1FD8..=0x1FDF, _) => self.latch1 = 0,
+            (0x1FE8..=0x1FEF, _) => self.latch1 = 1,
+            _ => {}
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.prg_ram.battery_data()
+    }
+
+    fn battery_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.prg_ram.battery_data_mut()
+    }
+}
@@ -0,0 +1,30 @@
+// src/nes/cart/mod.rs
+// Cartridge module
+mod archive;
+mod axrom;
+mod battery;
+mod chr;
+mod cnrom;
+mod db;
+mod fds_audio;
+mod fme7;
+mod mapper;
+mod mmc1;
+mod mmc2;
+mod nrom;
+mod nsf;
+mod patch;
+mod prg_ram;
+mod rom;
+mod sunsoft5b;
+mod uxrom;
+mod vrc4;
+
+// Re-export public interface
+pub use archive::{read_image, split_inner_path};
+pub use battery::{load_battery_ram, save_battery_ram, save_path};
+pub use fds_audio::FdsAudio;
+pub use mapper::{new_mapper, Mapper, SharedMapper};
+pub use nsf::{Nsf, NsfHeader, NsfMapper};
+pub use patch::{adjacent_patch, apply_patch, PatchError};
+pub use rom::{ConsoleType, Rom, RomError, RomFormat, VsPpu};
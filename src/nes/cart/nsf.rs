@@ -0,0 +1,225 @@
+// src/nes/cart/nsf.rs
+// NSF music file loader and its 4KB bank-switching "board"
+
+use std::path::Path;
+
+use log::warn;
+
+use crate::nes::apu::{Apu, ExpansionAudio};
+use crate::nes::cart::archive;
+use crate::nes::cart::fds_audio::FdsAudio;
+use crate::nes::cart::prg_ram::PrgRam;
+use crate::nes::cart::sunsoft5b::Sunsoft5b;
+use crate::nes::cart::{Mapper, RomError};
+use crate::nes::ppu::Mirroring;
+
+const NSF_MAGIC: [u8; 5] = [b'N', b'E', b'S', b'M', 0x1A];
+const HEADER_SIZE: usize = 0x80;
+const BANK_SIZE: usize = 0x1000; // 4KB
+const WORK_RAM_SIZE: usize = 0x2000; // 8KB at $6000-$7FFF
+
+// Expansion chip bits in header byte $7B
+const CHIP_FDS: u8 = 0x04;
+const CHIP_SUNSOFT_5B: u8 = 0x20;
+const CHIP_NAMES: [&str; 6] = ["VRC6", "VRC7", "FDS", "MMC5", "Namco 163", "Sunsoft 5B"];
+const SUPPORTED_CHIPS: u8 = CHIP_FDS | CHIP_SUNSOFT_5B;
+
+/// Metadata from the 128-byte NSF header
+#[derive(Debug, Clone)]
+pub struct NsfHeader {
+    pub version: u8,
+    pub total_songs: u8,
+    pub starting_song: u8,
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+    pub song_name: String,
+    pub artist: String,
+    pub copyright: String,
+    /// PLAY call period in microseconds
+    pub ntsc_speed: u16,
+    pub pal_speed: u16,
+    pub bankswitch_init: [u8; 8],
+    pub pal: bool,
+    pub dual_region: bool,
+    pub expansion_chips: u8,
+}
+
+impl NsfHeader {
+    pub fn uses_bankswitching(&self) -> bool {
+        self.bankswitch_init.iter().any(|&bank| bank != 0)
+    }
+}
+
+/// Parsed NSF file: header plus program data
+pub struct Nsf {
+    pub header: NsfHeader,
+    pub data: Vec<u8>,
+}
+
+impl Nsf {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RomError> {
+        let data = archive::read_image(path.as_ref(), None)?;
+        Self::from_bytes(&data)
+    }
+
+    /// Whether an in-memory image carries the NSF signature
+    pub fn is_nsf(data: &[u8]) -> bool {
+        data.starts_with(&NSF_MAGIC)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RomError> {
+        if data.len() < HEADER_SIZE {
+            return Err(RomError::Truncated { expected: HEADER_SIZE, found: data.len() });
+        }
+        if data[0..5] != NSF_MAGIC {
+            return Err(RomError::InvalidMagic);
+        }
+
+        let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let text = |offset: usize| {
+            let field = &data[offset..offset + 32];
+            let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+
+        let mut bankswitch_init = [0; 8];
+        bankswitch_init.copy_from_slice(&data[0x70..0x78]);
+
+        let header = NsfHeader {
+            version: data[5],
+            total_songs: data[6].max(1),
+            starting_song: data[7].max(1),
+            load_addr: word(0x08),
+            init_addr: word(0x0A),
+            play_addr: word(0x0C),
+            song_name: text(0x0E),
+            artist: text(0x2E),
+            copyright: text(0x4E),
+            ntsc_speed: word(0x6E),
+            pal_speed: word(0x78),
+            bankswitch_init,
+            pal: data[0x7A] & 0x01 != 0,
+            dual_region: data[0x7A] & 0x02 != 0,
+            expansion_chips: data[0x7B],
+        };
+
+        if !header.uses_bankswitching() && header.load_addr < 0x8000 {
+            return Err(RomError::InvalidSize("NSF load address"));
+        }
+
+        Ok(Self {
+            header,
+            data: data[HEADER_SIZE..].to_vec(),
+        })
+    }
+}
+
+/// Maps NSF program data into $8000-$FFFF as eight switchable 4KB banks
+/// selected through $5FF8-$5FFF. Non-bankswitched files are laid out at their
+/// load address with an identity bank mapping. The expansion chips the
+/// header declares sit on the same bus.
+pub struct NsfMapper {
+    data: Vec<u8>,
+    banks: [u8; 8],
+    initial_banks: [u8; 8],
+    prg_ram: PrgRam,
+    chips: u8,
+    fds: Option<FdsAudio>,
+    sunsoft5b: Option<Sunsoft5b>,
+}
+
+impl NsfMapper {
+    pub fn new(nsf: Nsf) -> Self {
+        let header = &nsf.header;
+        let (padding, initial_banks) = if header.uses_bankswitching() {
+            ((header.load_addr & 0x0FFF) as usize, header.bankswitch_init)
+        } else {
+            ((header.load_addr - 0x8000) as usize, [0, 1, 2, 3, 4, 5, 6, 7])
+        };
+
+        let mut data = vec![0; padding];
+        data.extend_from_slice(&nsf.data);
+        // Round up to whole banks so every bank number maps to real data
+        data.resize(data.len().div_ceil(BANK_SIZE).max(1) * BANK_SIZE, 0);
+
+        let unsupported = header.expansion_chips & !SUPPORTED_CHIPS;
+        for (bit, name) in CHIP_NAMES.iter().enumerate() {
+            if unsupported & (1 << bit) != 0 {
+                warn!("NSF uses {} audio, which isn't emulated; those parts will be silent", name);
+            }
+        }
+
+        let mut mapper = Self {
+            data,
+            banks: initial_banks,
+            initial_banks,
+            prg_ram: PrgRam::with_size(WORK_RAM_SIZE, false),
+            chips: header.expansion_chips & SUPPORTED_CHIPS,
+            fds: None,
+            sunsoft5b: None,
+        };
+        mapper.reset_expansion();
+        mapper
+    }
+
+    /// Power the declared expansion chips back up silent, ahead of INIT
+    pub fn reset_expansion(&mut self) {
+        self.fds = (self.chips & CHIP_FDS != 0).then(FdsAudio::default);
+        self.sunsoft5b = (self.chips & CHIP_SUNSOFT_5B != 0).then(Sunsoft5b::default);
+    }
+
+    /// Clock each expansion chip one CPU cycle and hand its output to the
+    /// APU's mixer
+    pub fn clock_expansion(&mut self, apu: &mut Apu) {
+        if let Some(chip) = &mut self.fds {
+            chip.clock();
+            apu.mix_expansion(chip.source(), chip.output());
+        }
+        if let Some(chip) = &mut self.sunsoft5b {
+            chip.clock();
+            apu.mix_expansion(chip.source(), chip.output());
+        }
+    }
+
+    /// Bank register values to restore before each INIT call
+    pub fn initial_banks(&self) -> [u8; 8] {
+        self.initial_banks
+    }
+}
+
+impl Mapper for NsfMapper {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x4040..=0x4097 => self.fds.as_ref().and_then(|chip| chip.read(addr)),
+            0x6000..=0x7FFF => self.prg_ram.read(addr as usize - 0x6000),
+            0x8000..=0xFFFF => {
+                let slot = (addr as usize - 0x8000) / BANK_SIZE;
+                let offset = self.banks[slot] as usize * BANK_SIZE + (addr as usize & (BANK_SIZE - 1));
+                Some(self.data[offset % self.data.len()])
+            }
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4040..=0x408A => self.fds.iter_mut().for_each(|chip| chip.write(addr, data)),
+            0x5FF8..=0x5FFF => self.banks[(addr - 0x5FF8) as usize] = data,
+            0x6000..=0x7FFF => self.prg_ram.write(addr as usize - 0x6000, data),
+            0xC000..=0xDFFF => self.sunsoft5b.iter_mut().for_each(|chip| chip.select(data)),
+            0xE000..=0xFFFF => self.sunsoft5b.iter_mut().for_each(|chip| chip.write(data)),
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, _addr: u16) -> u8 {
+        0
+    }
+
+    fn ppu_write(&mut self, _addr: u16, _data: u8) {}
+
+    fn mirroring(&self) -> Mirroring {
+        Mirroring::Horizontal
+    }
+}
@@ -0,0 +1,213 @@
+// src/nes/cart/patch.rs
+// IPS and BPS soft-patching of ROM images before header parsing
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: u32 = 0x454F46; // "EOF"
+const BPS_MAGIC: &[u8] = b"BPS1";
+const BPS_FOOTER_SIZE: usize = 12; // Source, target and patch CRC32s
+
+#[derive(Debug, Error)]
+pub enum PatchError {
+    #[error("not an IPS or BPS patch")]
+    UnknownFormat,
+    #[error("patch is truncated")]
+    Truncated,
+    #[error("patch refers to data outside the ROM image")]
+    OutOfRange,
+    #[error("patch was made for a different ROM (CRC32 {found:08X}, expected {expected:08X})")]
+    SourceMismatch { expected: u32, found: u32 },
+    #[error("patched ROM failed its CRC32 check")]
+    TargetMismatch,
+    #[error("patch file is corrupt (CRC32 mismatch)")]
+    PatchCorrupt,
+}
+
+/// Apply an IPS or BPS patch, detected by its magic, to a raw ROM image
+pub fn apply_patch(image: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(image, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(image, patch)
+    } else {
+        Err(PatchError::UnknownFormat)
+    }
+}
+
+/// Soft patch sitting next to the ROM (`game.nes` -> `game.bps` / `game.ips`),
+/// applied in memory so the file on disk stays untouched. BPS wins if both exist.
+pub fn adjacent_patch(rom_path: &Path) -> Option<PathBuf> {
+    ["bps", "ips"]
+        .iter()
+        .map(|ext| rom_path.with_extension(ext))
+        .find(|path| path.is_file())
+}
+
+// Cursor over the patch body with bounds-checked reads
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        let end = self.pos.checked_add(len).ok_or(PatchError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(PatchError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, PatchError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn be(&mut self, len: usize) -> Result<u32, PatchError> {
+        Ok(self.bytes(len)?.iter().fold(0, |acc, &b| (acc << 8) | b as u32))
+    }
+
+    // BPS variable-length integer: 7 bits per byte, high bit terminates,
+    // with an implicit +1 per continuation so encodings are unique
+    fn varint(&mut self) -> Result<usize, PatchError> {
+        let mut value = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.byte()?;
+            value = value
+                .checked_add((byte & 0x7F) as usize * shift)
+                .ok_or(PatchError::OutOfRange)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_shl(7).ok_or(PatchError::OutOfRange)?;
+            value = value.checked_add(shift).ok_or(PatchError::OutOfRange)?;
+        }
+    }
+}
+
+// IPS: 24-bit offset, 16-bit length records (length 0 means an RLE run),
+// terminated by "EOF" and an optional 24-bit truncation size
+fn apply_ips(image: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut output = image.to_vec();
+    let mut reader = Reader::new(patch, IPS_MAGIC.len());
+
+    loop {
+        let offset = reader.be(3)?;
+        if offset == IPS_EOF {
+            break;
+        }
+        let offset = offset as usize;
+        let size = reader.be(2)? as usize;
+
+        let (len, data) = if size == 0 {
+            let run = reader.be(2)? as usize;
+            (run, None)
+        } else {
+            (size, Some(reader.bytes(size)?))
+        };
+
+        if output.len() < offset + len {
+            output.resize(offset + len, 0);
+        }
+        match data {
+            Some(data) => output[offset..offset + len].copy_from_slice(data),
+            None => {
+                let value = reader.byte()?;
+                output[offset..offset + len].fill(value);
+            }
+        }
+    }
+
+    if let Ok(truncate) = reader.be(3) {
+        output.truncate(truncate as usize);
+    }
+    Ok(output)
+}
+
+// BPS: source/target sizes, metadata, then SourceRead / TargetRead /
+// SourceCopy / TargetCopy actions, all checked against trailing CRC32s
+fn apply_bps(image: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.len() < BPS_MAGIC.len() + BPS_FOOTER_SIZE {
+        return Err(PatchError::Truncated);
+    }
+    let body_end = patch.len() - BPS_FOOTER_SIZE;
+    let mut footer = Reader::new(patch, body_end);
+    let source_crc = u32::from_le_bytes(footer.bytes(4)?.try_into().unwrap());
+    let target_crc = u32::from_le_bytes(footer.bytes(4)?.try_into().unwrap());
+    let patch_crc = u32::from_le_bytes(footer.bytes(4)?.try_into().unwrap());
+
+    if crc32fast::hash(&patch[..patch.len() - 4]) != patch_crc {
+        return Err(PatchError::PatchCorrupt);
+    }
+    let found = crc32fast::hash(image);
+    if found != source_crc {
+        return Err(PatchError::SourceMismatch { expected: source_crc, found });
+    }
+
+    let mut reader = Reader::new(&patch[..body_end], BPS_MAGIC.len());
+    let source_size = reader.varint()?;
+    let target_size = reader.varint()?;
+    let metadata_size = reader.varint()?;
+    reader.bytes(metadata_size)?;
+    if source_size != image.len() {
+        return Err(PatchError::OutOfRange);
+    }
+
+    // The sizes are only as trustworthy as the patch, so let the output grow
+    let mut output = Vec::new();
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+    while reader.pos < body_end {
+        let action = reader.varint()?;
+        let len = (action >> 2) + 1;
+        match action & 0x03 {
+            // SourceRead: copy from the same position in the source
+            0 => {
+                let data = image.get(output.len()..).and_then(|rest| rest.get(..len)).ok_or(PatchError::OutOfRange)?;
+                output.extend_from_slice(data);
+            }
+            // TargetRead: literal bytes from the patch
+            1 => output.extend_from_slice(reader.bytes(len)?),
+            // SourceCopy: copy from a relative position in the source
+            2 => {
+                source_offset = relative_offset(source_offset, reader.varint()?)?;
+                let data = image.get(source_offset..).and_then(|rest| rest.get(..len)).ok_or(PatchError::OutOfRange)?;
+                output.extend_from_slice(data);
+                source_offset += len;
+            }
+            // TargetCopy: copy already-written output, byte by byte so runs can overlap
+            _ => {
+                target_offset = relative_offset(target_offset, reader.varint()?)?;
+                for _ in 0..len {
+                    let byte = *output.get(target_offset).ok_or(PatchError::OutOfRange)?;
+                    output.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+        if output.len() > target_size {
+            return Err(PatchError::OutOfRange);
+        }
+    }
+
+    if output.len() != target_size || crc32fast::hash(&output) != target_crc {
+        return Err(PatchError::TargetMismatch);
+    }
+    Ok(output)
+}
+
+// Signed BPS offset delta: bit 0 is the sign, the rest the magnitude
+fn relative_offset(base: usize, encoded: usize) -> Result<usize, PatchError> {
+    let delta = encoded >> 1;
+    if encoded & 0x01 != 0 {
+        base.checked_sub(delta).ok_or(PatchError::OutOfRange)
+    } else {
+        base.checked_add(delta).ok_or(PatchError::OutOfRange)
+    }
+}
@@ -0,0 +1,73 @@
+// src/nes/cart/prg_ram.rs
+// Cartridge work RAM at $6000-$7FFF, optionally battery-backed
+
+use crate::nes::cart::Rom;
+
+const BATTERY_RAM_SIZE: usize = 0x2000; // 8KB
+const TRAINER_OFFSET: usize = 0x1000; // $7000
+
+pub struct PrgRam {
+    data: Vec<u8>,
+    battery: bool,
+    enabled: bool, // Chip enable; disabled RAM leaves the bus floating
+}
+
+impl PrgRam {
+    /// Size the RAM from the header; battery and trainer carts always get at
+    /// least 8KB. A trainer is preloaded at $7000.
+    pub fn new(rom: &Rom) -> Self {
+        Self::with_min_size(rom, 0)
+    }
+
+    /// As `new`, for boards that decode at least `min_size` of RAM whatever
+    /// the header declares
+    pub fn with_min_size(rom: &Rom, min_size: usize) -> Self {
+        let min_size = if rom.battery || rom.trainer.is_some() {
+            min_size.max(BATTERY_RAM_SIZE)
+        } else {
+            min_size
+        };
+
+        let mut ram = Self::with_size(rom.work_ram_size().max(min_size), rom.battery);
+        if let Some(trainer) = &rom.trainer {
+            ram.data[TRAINER_OFFSET..TRAINER_OFFSET + trainer.len()].copy_from_slice(trainer);
+        }
+        ram
+    }
+
+    pub fn with_size(size: usize, battery: bool) -> Self {
+        Self {
+            data: vec![0; size],
+            battery,
+            enabled: true,
+        }
+    }
+
+    /// Mapper-controlled chip enable (MMC1 PRG bank bit 4)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// `None` when the board has no RAM or it is disabled, leaving open bus
+    pub fn read(&self, offset: usize) -> Option<u8> {
+        if !self.enabled || self.data.is_empty() {
+            return None;
+        }
+        Some(self.data[offset % self.data.len()])
+    }
+
+    pub fn write(&mut self, offset: usize, data: u8) {
+        if self.enabled && !self.data.is_empty() {
+            let len = self.data.len();
+            self.data[offset % len] = data;
+        }
+    }
+
+    pub fn battery_data(&self) -> Option<&[u8]> {
+        self.battery.then_some(self.data.as_slice())
+    }
+
+    pub fn battery_data_mut(&mut self) -> Option<&mut [u8]> {
+        self.battery.then_some(self.data.as_mut_slice())
+    }
+}
@@ -0,0 +1,271 @@
+// src/nes/cart/rom.rs
+// iNES / NES 2.0 cartridge image loader
+
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::nes::cart::{archive, db};
+use crate::nes::clock::Region;
+use crate::nes::ppu::Mirroring;
+
+const INES_MAGIC: [u8; 4] = [b'N', b'E', b'S', 0x1A];
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+const PRG_BANK_SIZE: usize = 0x4000; // 16KB
+const CHR_BANK_SIZE: usize = 0x2000; // 8KB
+const PRG_RAM_UNIT: usize = 0x2000; // 8KB, iNES byte 8 units
+
+// Flags 6
+const FLAG_VERTICAL: u8 = 1 << 0;
+const FLAG_BATTERY: u8 = 1 << 1;
+const FLAG_TRAINER: u8 = 1 << 2;
+const FLAG_FOUR_SCREEN: u8 = 1 << 3;
+
+// Flags 7
+const NES2_ID_MASK: u8 = 0x0C;
+const NES2_ID: u8 = 0x08;
+
+// iNES flags 9
+const FLAG_PAL: u8 = 1 << 0;
+
+#[derive(Debug, Error)]
+pub enum RomError {
+    #[error("failed to read ROM file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("missing iNES header magic")]
+    InvalidMagic,
+    #[error("ROM image truncated: expected {expected} bytes, found {found}")]
+    Truncated { expected: usize, found: usize },
+    #[error("ROM header declares no PRG ROM banks")]
+    NoPrgRom,
+    #[error("ROM header declares an unsupported {0} size")]
+    InvalidSize(&'static str),
+    #[error("mapper {0} is not supported")]
+    UnsupportedMapper(u16),
+    #[error("failed to read archive: {0}")]
+    Archive(String),
+    #[error("archive contains no .nes, .fds or .nsf file")]
+    NoRomInArchive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomFormat {
+    INes,
+    Nes20,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem(VsPpu),
+    Playchoice10,
+    Extended(u8),
+}
+
+/// PPU fitted to a Vs. System board (NES 2.0 byte 13, low nibble)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VsPpu {
+    /// RP2C03/RC2C03: standard RGB palette order
+    Rp2c03,
+    /// RP2C04-0001 through -0004: scrambled palette order per revision
+    Rp2c04(u8),
+    /// RC2C05-01 through -05: $2000/$2001 swapped and an ID in $2002
+    Rc2c05(u8),
+}
+
+impl VsPpu {
+    fn from_header(id: u8) -> Self {
+        match id {
+            0x02..=0x05 => VsPpu::Rp2c04(id - 0x01),
+            0x08..=0x0C => VsPpu::Rc2c05(id - 0x07),
+            _ => VsPpu::Rp2c03,
+        }
+    }
+}
+
+/// Parsed iNES / NES 2.0 cartridge image
+pub struct Rom {
+    pub format: RomFormat,
+    /// 512-byte trainer loaded at $7000, present on some hacked dumps
+    pub trainer: Option<Vec<u8>>,
+    pub prg_rom: Vec<u8>,
+    pub chr_rom: Vec<u8>,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub mirroring: Mirroring,
+    pub battery: bool,
+    pub console_type: ConsoleType,
+    /// Console timing the game was made for
+    pub region: Region,
+
+    // Cartridge RAM sizes in bytes (volatile / battery-backed)
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+
+    /// CRC32 of PRG ROM followed by CHR ROM, the ROM database key
+    pub crc32: u32,
+    /// Description of the header fix applied from the ROM database, if any
+    pub db_correction: Option<String>,
+}
+
+impl Rom {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RomError> {
+        let data = archive::read_image(path.as_ref(), None)?;
+        Self::from_bytes(&data)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, RomError> {
+        if data.len() < HEADER_SIZE {
+            return Err(RomError::Truncated { expected: HEADER_SIZE, found: data.len() });
+        }
+        if data[0..4] != INES_MAGIC {
+            return Err(RomError::InvalidMagic);
+        }
+
+        let header = &data[..HEADER_SIZE];
+        let flags6 = header[6];
+        let flags7 = header[7];
+        let format = if flags7 & NES2_ID_MASK == NES2_ID {
+            RomFormat::Nes20
+        } else {
+            RomFormat::INes
+        };
+
+        let mirroring = if flags6 & FLAG_FOUR_SCREEN != 0 {
+            Mirroring::FourScreen
+        } else if flags6 & FLAG_VERTICAL != 0 {
+            Mirroring::Vertical
+        } else {
+            Mirroring::Horizontal
+        };
+
+        let mut mapper = ((flags7 & 0xF0) | (flags6 >> 4)) as u16;
+        let mut submapper = 0;
+        let console_type;
+        let region;
+        let prg_size;
+        let chr_size;
+        let prg_ram_size;
+        let prg_nvram_size;
+        let chr_ram_size;
+        let chr_nvram_size;
+
+        match format {
+            RomFormat::Nes20 => {
+                mapper |= ((header[8] & 0x0F) as u16) << 8;
+                submapper = header[8] >> 4;
+                console_type = match flags7 & 0x03 {
+                    0 => ConsoleType::Nes,
+                    1 => ConsoleType::VsSystem(VsPpu::from_header(header[13] & 0x0F)),
+                    2 => ConsoleType::Playchoice10,
+                    _ => ConsoleType::Extended(header[13] & 0x0F),
+                };
+                // Multi-region games default to NTSC; Dendy's 312 lines
+                // are closest to PAL
+                region = match header[12] & 0x03 {
+                    1 | 3 => Region::Pal,
+                    _ => Region::Ntsc,
+                };
+                prg_size = nes2_rom_size(header[4], header[9] & 0x0F, PRG_BANK_SIZE)
+                    .ok_or(RomError::InvalidSize("PRG ROM"))?;
+                chr_size = nes2_rom_size(header[5], header[9] >> 4, CHR_BANK_SIZE)
+                    .ok_or(RomError::InvalidSize("CHR ROM"))?;
+                prg_ram_size = nes2_ram_size(header[10] & 0x0F);
+                prg_nvram_size = nes2_ram_size(header[10] >> 4);
+                chr_ram_size = nes2_ram_size(header[11] & 0x0F);
+                chr_nvram_size = nes2_ram_size(header[11] >> 4);
+            }
+            RomFormat::INes => {
+                // iNES has no PPU field; most Vs. titles run on an RP2C03-compatible part
+                console_type = match flags7 & 0x03 {
+                    1 => ConsoleType::VsSystem(VsPpu::Rp2c03),
+                    2 => ConsoleType::Playchoice10,
+                    _ => ConsoleType::Nes,
+                };
+                // Few dumps set the TV system bit, but it's all iNES has
+                region = if header[9] & FLAG_PAL != 0 { Region::Pal } else { Region::Ntsc };
+                prg_size = header[4] as usize * PRG_BANK_SIZE;
+                chr_size = header[5] as usize * CHR_BANK_SIZE;
+                // Byte 8 is PRG RAM in 8KB units; 0 means 8KB for compatibility.
+                // With the battery flag set, that RAM is the save RAM.
+                let ram_size = header[8].max(1) as usize * PRG_RAM_UNIT;
+                if flags6 & FLAG_BATTERY != 0 {
+                    prg_ram_size = 0;
+                    prg_nvram_size = ram_size;
+                } else {
+                    prg_ram_size = ram_size;
+                    prg_nvram_size = 0;
+                }
+                chr_ram_size = if chr_size == 0 { CHR_BANK_SIZE } else { 0 };
+                chr_nvram_size = 0;
+            }
+        }
+
+        if prg_size == 0 {
+            return Err(RomError::NoPrgRom);
+        }
+
+        // The trainer sits between the header and PRG ROM
+        let has_trainer = flags6 & FLAG_TRAINER != 0;
+        let prg_start = if has_trainer { HEADER_SIZE + TRAINER_SIZE } else { HEADER_SIZE };
+        let chr_start = prg_start.checked_add(prg_size).ok_or(RomError::InvalidSize("PRG ROM"))?;
+        let end = chr_start.checked_add(chr_size).ok_or(RomError::InvalidSize("CHR ROM"))?;
+        if data.len() < end {
+            return Err(RomError::Truncated { expected: end, found: data.len() });
+        }
+
+        let mut rom = Self {
+            format,
+            trainer: has_trainer.then(|| data[HEADER_SIZE..prg_start].to_vec()),
+            prg_rom: data[prg_start..chr_start].to_vec(),
+            chr_rom: data[chr_start..end].to_vec(),
+            mapper,
+            submapper,
+            mirroring,
+            battery: flags6 & FLAG_BATTERY != 0,
+            console_type,
+            region,
+            prg_ram_size,
+            prg_nvram_size,
+            chr_ram_size,
+            chr_nvram_size,
+            crc32: crc32fast::hash(&data[prg_start..end]),
+            db_correction: None,
+        };
+
+        // NES 2.0 headers are trusted; old iNES dumps are checked against the database
+        if rom.format == RomFormat::INes {
+            rom.db_correction = db::apply_corrections(&mut rom);
+        }
+        Ok(rom)
+    }
+
+    /// Total PRG work RAM the board exposes at $6000-$7FFF
+    pub fn work_ram_size(&self) -> usize {
+        self.prg_ram_size + self.prg_nvram_size
+    }
+}
+
+// NES 2.0 ROM size: either a 12-bit bank count or, when the MSB nibble is
+// $F, an exponent-multiplier form `2^E * (MM * 2 + 1)` bytes.
+fn nes2_rom_size(lsb: u8, msb: u8, bank_size: usize) -> Option<usize> {
+    if msb == 0x0F {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = ((lsb & 0x03) as usize) * 2 + 1;
+        1usize.checked_shl(exponent)?.checked_mul(multiplier)
+    } else {
+        Some((((msb as usize) << 8) | lsb as usize) * bank_size)
+    }
+}
+
+// NES 2.0 RAM size: shift count of 0 means none, otherwise `64 << shift` bytes
+fn nes2_ram_size(shift: u8) -> usize {
+    if shift == 0 {
+        0
+    } else {
+        64 << shift
+    }
+}
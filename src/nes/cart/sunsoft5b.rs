@@ -0,0 +1,205 @@
+// src/nes/cart/sunsoft5b.rs
+// Sunsoft 5B audio on FME-7 boards: three AY-3-8910 style square channels with shared noise and envelope
+
+use crate::nes::apu::{AudioSource, ExpansionAudio};
+
+// CPU cycles per tick of the tone, noise and envelope dividers
+const PRESCALER: u8 = 16;
+
+// Full volume on one channel, about as loud as a 2A03 pulse at volume 15
+const CHANNEL_SCALE: f32 = 0.15;
+
+#[derive(Clone, Debug, Default)]
+struct Tone {
+    period: u16, // 12 bits
+    counter: u16,
+    high: bool,
+    volume: u8, // 4 bits, or the envelope when `use_envelope`
+    use_envelope: bool,
+}
+
+/// The 5B's registers sit behind an address latch at $C000-$DFFF, with
+/// data going to $E000-$FFFF
+#[derive(Clone, Debug)]
+pub struct Sunsoft5b {
+    register: u8,
+    prescaler: u8,
+    tones: [Tone; 3],
+    tone_disable: u8,  // Bits 0-2 of the mixer register
+    noise_disable: u8, // Bits 3-5, shifted down
+    noise_period: u8,
+    noise_counter: u8,
+    noise_half: bool, // The noise divider runs at half the tone rate
+    noise_shift: u32, // 17-bit LFSR
+    envelope_period: u16,
+    envelope_counter: u16,
+    envelope_shape: u8,
+    envelope_step: u8, // 0-31 through the current ramp
+    envelope_attack: bool,
+    envelope_holding: bool,
+    levels: [f32; 32], // Logarithmic DAC, 1.5dB per step
+}
+
+impl Default for Sunsoft5b {
+    fn default() -> Self {
+        let mut levels = [0.0; 32];
+        for (step, level) in levels.iter_mut().enumerate().skip(1) {
+            *level = CHANNEL_SCALE * 10f32.powf((step as f32 - 31.0) * 1.5 / 20.0);
+        }
+        Self {
+            register: 0,
+            prescaler: 0,
+            tones: Default::default(),
+            tone_disable: 0,
+            noise_disable: 0,
+            noise_period: 0,
+            noise_counter: 0,
+            noise_half: false,
+            noise_shift: 1,
+            envelope_period: 0,
+            envelope_counter: 0,
+            envelope_shape: 0,
+            envelope_step: 0,
+            envelope_attack: false,
+            envelope_holding: false,
+            levels,
+        }
+    }
+}
+
+impl Sunsoft5b {
+    /// $C000: pick the register the next data write goes to. The upper
+    /// nibble must be 0 or writes are ignored.
+    pub fn select(&mut self, data: u8) {
+        self.register = data;
+    }
+
+    /// $E000: write the selected register
+    pub fn write(&mut self, data: u8) {
+        match self.register {
+            reg @ 0x00..=0x05 => {
+                let tone = &mut self.tones[reg as usize / 2];
+                tone.period = if reg & 0x01 == 0 {
+                    (tone.period & 0x0F00) | data as u16
+                } else {
+                    (tone.period & 0x00FF) | ((data & 0x0F) as u16) << 8
+                };
+            }
+            0x06 => self.noise_period = data & 0x1F,
+            0x07 => {
+                self.tone_disable = data & 0x07;
+                self.noise_disable = (data >> 3) & 0x07;
+            }
+            reg @ 0x08..=0x0A => {
+                let tone = &mut self.tones[reg as usize - 0x08];
+                tone.volume = data & 0x0F;
+                tone.use_envelope = data & 0x10 != 0;
+            }
+            0x0B => self.envelope_period = (self.envelope_period & 0xFF00) | data as u16,
+            0x0C => self.envelope_period = (self.envelope_period & 0x00FF) | (data as u16) << 8,
+            0x0D => {
+                // Writing the shape restarts the envelope
+                self.envelope_shape = data & 0x0F;
+                self.envelope_step = 0;
+                self.envelope_attack = data & 0x04 != 0;
+                self.envelope_holding = false;
+                self.envelope_counter = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn envelope_level(&self) -> u8 {
+        if self.envelope_attack {
+            self.envelope_step
+        } else {
+            31 - self.envelope_step
+        }
+    }
+
+    // One step along the 32-step ramp. Shapes 0-7 fall silent after one
+    // ramp; the rest repeat, alternate or hold as their bits say.
+    fn step_envelope(&mut self) {
+        if self.envelope_holding {
+            return;
+        }
+        if self.envelope_step < 31 {
+            self.envelope_step += 1;
+            return;
+        }
+        let (cont, alternate, hold) = (self.envelope_shape & 0x08, self.envelope_shape & 0x02, self.envelope_shape & 0x01);
+        if cont == 0 {
+            self.envelope_attack = false;
+            self.envelope_holding = true;
+        } else {
+            if alternate != 0 {
+                self.envelope_attack = !self.envelope_attack;
+            }
+            if hold != 0 {
+                self.envelope_holding = true;
+            } else {
+                self.envelope_step = 0;
+            }
+        }
+    }
+}
+
+impl ExpansionAudio for Sunsoft5b {
+    fn source(&self) -> AudioSource {
+        AudioSource::Sunsoft5b
+    }
+
+    // A tone flips every `period` ticks, for a square of CPU / (32 * period)
+    fn clock(&mut self) {
+        self.prescaler += 1;
+        if self.prescaler < PRESCALER {
+            return;
+        }
+        self.prescaler = 0;
+
+        for tone in &mut self.tones {
+            tone.counter += 1;
+            if tone.counter >= tone.period.max(1) {
+                tone.counter = 0;
+                tone.high = !tone.high;
+            }
+        }
+
+        self.noise_half = !self.noise_half;
+        if self.noise_half {
+            self.noise_counter += 1;
+            if self.noise_counter >= self.noise_period.max(1) {
+                self.noise_counter = 0;
+                let feedback = (self.noise_shift ^ (self.noise_shift >> 3)) & 0x01;
+                self.noise_shift = (self.noise_shift >> 1) | feedback << 16;
+            }
+        }
+
+        self.envelope_counter += 1;
+        if self.envelope_counter >= self.envelope_period.max(1) {
+            self.envelope_counter = 0;
+            self.step_envelope();
+        }
+    }
+
+    // A channel sounds while each of its enabled generators is high; with
+    // both disabled it holds its volume level
+    fn output(&self) -> f32 {
+        let noise = self.noise_shift & 0x01 != 0;
+        self.tones
+            .iter()
+            .enumerate()
+            .filter(|&(i, tone)| {
+                (tone.high || self.tone_disable & (1 << i) != 0) && (noise || self.noise_disable & (1 << i) != 0)
+            })
+            .map(|(_, tone)| {
+                let level = match (tone.use_envelope, tone.volume) {
+                    (true, _) => self.envelope_level(),
+                    (false, 0) => 0,
+                    (false, volume) => volume * 2 + 1,
+                };
+                self.levels[level as usize]
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,60 @@
+// src/nes/cart/uxrom.rs
+// UxROM (mapper 2): switchable 16KB PRG bank at $8000, last bank fixed at $C000
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::mapper::has_bus_conflicts;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const PRG_BANK_SIZE: usize = 0x4000; // 16KB
+
+pub struct Uxrom {
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    mirroring: Mirroring,
+    bus_conflicts: bool,
+    prg_bank: u8,
+}
+
+impl Uxrom {
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            bus_conflicts: has_bus_conflicts(&rom),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            mirroring: rom.mirroring,
+            prg_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Uxrom {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        let bank_count = (self.prg_rom.len() / PRG_BANK_SIZE).max(1);
+        let bank = match addr {
+            0x8000..=0xBFFF => self.prg_bank as usize % bank_count,
+            0xC000..=0xFFFF => bank_count - 1,
+            _ => return None,
+        };
+        let offset = bank * PRG_BANK_SIZE + (addr as usize & (PRG_BANK_SIZE - 1));
+        Some(self.prg_rom[offset % self.prg_rom.len()])
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if addr >= 0x8000 {
+            self.prg_bank = if self.bus_conflicts { data & self.cpu_read(addr).unwrap_or(data) } else { data };
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(addr as usize)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.chr.write(addr as usize, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
@@ -0,0 +1,258 @@
+// src/nes/cart/vrc4.rs
+// Konami VRC2/VRC4 (mappers 21, 22, 23, 25): PRG/CHR banking and the VRC4 IRQ counter
+
+use crate::nes::cart::chr::ChrMemory;
+use crate::nes::cart::prg_ram::PrgRam;
+use crate::nes::cart::{Mapper, Rom};
+use crate::nes::ppu::Mirroring;
+
+const PRG_BANK_SIZE: usize = 0x2000; // 8KB
+const CHR_BANK_SIZE: usize = 0x0400; // 1KB
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VrcChip {
+    Vrc2,
+    Vrc4,
+}
+
+/// CPU address lines wired to the chip's register select pins. Each mask may
+/// cover several lines so one mapper number can serve boards wired either way.
+#[derive(Clone, Copy)]
+pub struct VrcWiring {
+    pub a0: u16,
+    pub a1: u16,
+}
+
+impl VrcWiring {
+    /// Chip and wiring for a VRC board. NES 2.0 submappers name the exact
+    /// variant; without one, OR together every wiring sharing the number.
+    pub fn for_board(mapper: u16, submapper: u8) -> (VrcChip, VrcWiring) {
+        let (chip, a0, a1) = match (mapper, submapper) {
+            (21, 1) => (VrcChip::Vrc4, 0x02, 0x04), // VRC4a
+            (21, 2) => (VrcChip::Vrc4, 0x40, 0x80), // VRC4c
+            (21, _) => (VrcChip::Vrc4, 0x42, 0x84),
+            (22, _) => (VrcChip::Vrc2, 0x02, 0x01), // VRC2a
+            (23, 1) => (VrcChip::Vrc4, 0x01, 0x02), // VRC4f
+            (23, 2) => (VrcChip::Vrc4, 0x04, 0x08), // VRC4e
+            (23, 3) => (VrcChip::Vrc2, 0x01, 0x02), // VRC2b
+            (23, _) => (VrcChip::Vrc4, 0x05, 0x0A),
+            (25, 1) => (VrcChip::Vrc4, 0x02, 0x01), // VRC4b
+            (25, 2) => (VrcChip::Vrc4, 0x08, 0x04), // VRC4d
+            (25, 3) => (VrcChip::Vrc2, 0x02, 0x01), // VRC2c
+            _ => (VrcChip::Vrc4, 0x0A, 0x05),
+        };
+        (chip, VrcWiring { a0, a1 })
+    }
+
+    fn register(&self, addr: u16) -> u16 {
+        let a0 = (addr & self.a0 != 0) as u16;
+        let a1 = (addr & self.a1 != 0) as u16;
+        (addr & 0xF000) | (a1 << 1) | a0
+    }
+}
+
+// VRC4 IRQ counter with the CPU-cycle prescaler
+struct VrcIrq {
+    latch: u8,
+    counter: u8,
+    prescaler: i16,
+    enabled: bool,
+    enable_after_ack: bool,
+    cycle_mode: bool,
+    pending: bool,
+}
+
+impl VrcIrq {
+    fn new() -> Self {
+        Self {
+            latch: 0,
+            counter: 0,
+            prescaler: 341,
+            enabled: false,
+            enable_after_ack: false,
+            cycle_mode: false,
+            pending: false,
+        }
+    }
+
+    fn write_control(&mut self, data: u8) {
+        self.enable_after_ack = data & 0x01 != 0;
+        self.enabled = data & 0x02 != 0;
+        self.cycle_mode = data & 0x04 != 0;
+        self.pending = false;
+        if self.enabled {
+            self.counter = self.latch;
+            self.prescaler = 341;
+        }
+    }
+
+    fn acknowledge(&mut self) {
+        self.pending = false;
+        self.enabled = self.enable_after_ack;
+    }
+
+    fn clock(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        // Scanline mode divides CPU cycles by 113.667 (341 PPU dots / 3)
+        if !self.cycle_mode {
+            self.prescaler -= 3;
+            if self.prescaler > 0 {
+                return;
+            }
+            self.prescaler += 341;
+        }
+
+        if self.counter == 0xFF {
+            self.counter = self.latch;
+            self.pending = true;
+        } else {
+            self.counter += 1;
+        }
+    }
+}
+
+pub struct Vrc4 {
+    chip: VrcChip,
+    wiring: VrcWiring,
+    chr_bank_shift: bool, // VRC2a (mapper 22) drops the low CHR bank bit
+    prg_rom: Vec<u8>,
+    chr: ChrMemory,
+    prg_ram: PrgRam,
+
+    prg_banks: [u8; 2],
+    prg_swap: bool,
+    chr_banks: [u16; 8],
+    mirroring: Mirroring,
+    irq: VrcIrq,
+}
+
+impl Vrc4 {
+    pub fn new(rom: Rom, chip: VrcChip, wiring: VrcWiring) -> Self {
+        Self {
+            chip,
+            wiring,
+            chr_bank_shift: rom.mapper == 22,
+            prg_ram: PrgRam::new(&rom),
+            prg_rom: rom.prg_rom,
+            chr: ChrMemory::new(rom.chr_rom, rom.chr_ram_size + rom.chr_nvram_size),
+            prg_banks: [0; 2],
+            prg_swap: false,
+            chr_banks: [0; 8],
+            mirroring: rom.mirroring,
+            irq: VrcIrq::new(),
+        }
+    }
+
+    fn prg_offset(&self, addr: u16) -> usize {
+        let bank_count = (self.prg_rom.len() / PRG_BANK_SIZE).max(2);
+        let second_last = bank_count - 2;
+        let bank = match ((addr - 0x8000) / PRG_BANK_SIZE as u16, self.prg_swap) {
+            (0, false) | (2, true) => self.prg_banks[0] as usize,
+            (0, true) | (2, false) => second_last,
+            (1, _) => self.prg_banks[1] as usize,
+            _ => bank_count - 1,
+        };
+        (bank % bank_count) * PRG_BANK_SIZE + (addr as usize & (PRG_BANK_SIZE - 1))
+    }
+
+    fn chr_offset(&self, addr: u16) -> usize {
+        let mut bank = self.chr_banks[(addr as usize / CHR_BANK_SIZE) & 0x07] as usize;
+        if self.chr_bank_shift {
+            bank >>= 1;
+        }
+        bank * CHR_BANK_SIZE + (addr as usize & (CHR_BANK_SIZE - 1))
+    }
+
+    fn write_chr_bank(&mut self, reg: u16, data: u8) {
+        // $B000-$E003: each 1KB bank is split into low/high nibble registers
+        let index = (((reg >> 12) - 0xB) * 2 + ((reg >> 1) & 0x01)) as usize;
+        let bank = &mut self.chr_banks[index];
+        if reg & 0x01 == 0 {
+            *bank = (*bank & 0x1F0) | (data & 0x0F) as u16;
+        } else {
+            *bank = (*bank & 0x00F) | ((data & 0x1F) as u16) << 4;
+        }
+    }
+}
+
+impl Mapper for Vrc4 {
+    fn cpu_read(&mut self, addr: u16) -> Option<u8> {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram.read(addr as usize - 0x6000),
+            0x8000..=0xFFFF => Some(self.prg_rom[self.prg_offset(addr) % self.prg_rom.len()]),
+            _ => None,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if (0x6000..=0x7FFF).contains(&addr) {
+            self.prg_ram.write(addr as usize - 0x6000, data);
+            return;
+        }
+        if addr < 0x8000 {
+            return;
+        }
+
+        let reg = self.wiring.register(addr);
+        match reg {
+            0x8000..=0x8003 => self.prg_banks[0] = data & 0x1F,
+            0x9000..=0x9003 if self.chip == VrcChip::Vrc2 => {
+                self.mirroring = if data & 0x01 != 0 {
+                    Mirroring::Horizontal
+                } else {
+                    Mirroring::Vertical
+                };
+            }
+            0x9000 | 0x9001 => {
+                self.mirroring = match data & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLow,
+                    _ => Mirroring::SingleScreenHigh,
+                };
+            }
+            0x9002 | 0x9003 => self.prg_swap = data & 0x02 != 0,
+            0xA000..=0xA003 => self.prg_banks[1] = data & 0x1F,
+            0xB000..=0xE003 => self.write_chr_bank(reg, data),
+            0xF000..=0xF003 if self.chip == VrcChip::Vrc4 => match reg & 0x03 {
+                0 => self.irq.latch = (self.irq.latch & 0xF0) | (data & 0x0F),
+                1 => self.irq.latch = (self.irq.latch & 0x0F) | (data << 4),
+                2 => self.irq.write_control(data),
+                _ => self.irq.acknowledge(),
+            },
+            _ => {}
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr.read(self.chr_offset(addr))
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let offset = self.chr_offset(addr);
+        self.chr.write(offset, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.prg_ram.battery_data()
+    }
+
+    fn battery_ram_mut(&mut self) -> Option<&mut [u8]> {
+        self.prg_ram.battery_data_mut()
+    }
+
+    fn cpu_clock(&mut self) {
+        self.irq.clock();
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.irq.pending
+    }
+}
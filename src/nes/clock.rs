@@ -0,0 +1,90 @@
+// src/nes/clock.rs
+// Master clock: derives CPU and PPU timing from the console's crystal
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+/// Console timing. Every component divides the same master clock, so the
+/// CPU:PPU ratio follows from the dividers: 1:3 on NTSC, 1:3.2 on PAL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+}
+
+impl Region {
+    /// Crystal frequency in Hz
+    pub fn master_hz(self) -> u64 {
+        match self {
+            Region::Ntsc => 21_477_272,
+            Region::Pal => 26_601_712,
+        }
+    }
+
+    /// Master clock cycles per CPU cycle
+    pub fn cpu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 12,
+            Region::Pal => 16,
+        }
+    }
+
+    /// Master clock cycles per PPU dot
+    pub fn ppu_divider(self) -> u64 {
+        match self {
+            Region::Ntsc => 4,
+            Region::Pal => 5,
+        }
+    }
+
+    pub fn cpu_hz(self) -> u64 {
+        self.master_hz() / self.cpu_divider()
+    }
+
+    /// PPU scanlines per frame, pre-render line included. PAL's extra 50
+    /// lines all go to VBlank.
+    pub fn scanlines(self) -> u16 {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal => 312,
+        }
+    }
+}
+
+/// Counts master clock cycles and hands out the PPU dots owed after each
+/// CPU cycle. PAL's fractional ratio comes out as a 3,3,3,3,4 dot pattern.
+#[derive(Clone, Debug)]
+pub struct MasterClock {
+    region: Region,
+    master_cycles: u64,
+    ppu_master_cycles: u64, // Master cycles already consumed by PPU dots
+}
+
+impl MasterClock {
+    pub fn new(region: Region) -> Self {
+        Self {
+            region,
+            master_cycles: 0,
+            ppu_master_cycles: 0,
+        }
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn master_cycles(&self) -> u64 {
+        self.master_cycles
+    }
+
+    /// Advance by one CPU cycle and return the PPU dots that fall within it
+    pub fn cpu_cycle(&mut self) -> u64 {
+        self.master_cycles += self.region.cpu_divider();
+        let divider = self.region.ppu_divider();
+        let dots = (self.master_cycles - self.ppu_master_cycles) / divider;
+        self.ppu_master_cycles += dots * divider;
+        dots
+    }
+}
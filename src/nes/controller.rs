@@ -0,0 +1,68 @@
+// src/nes/controller.rs
+// Standard controller: button latch and serial shift register read through $4016/$4017
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// Buttons held on a standard controller, in the order they shift out
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct InputState: u8 {
+        const A      = 0b00000001;
+        const B      = 0b00000010;
+        const SELECT = 0b00000100;
+        const START  = 0b00001000;
+        const UP     = 0b00010000;
+        const DOWN   = 0b00100000;
+        const LEFT   = 0b01000000;
+        const RIGHT  = 0b10000000;
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Controller {
+    buttons: InputState,
+    shift: u8,
+    strobe: bool,
+}
+
+impl Controller {
+    /// Buttons currently held, as set by the frontend once per frame
+    pub fn set_buttons(&mut self, buttons: InputState) {
+        self.buttons = buttons;
+        if self.strobe {
+            self.shift = buttons.bits();
+        }
+    }
+
+    pub fn buttons(&self) -> InputState {
+        self.buttons
+    }
+
+    /// Bit 0 of a $4016 write. While high the shift register keeps reloading,
+    /// so reads return A; the fall latches all eight buttons.
+    pub fn write_strobe(&mut self, data: u8) {
+        self.strobe = data & 0x01 != 0;
+        if self.strobe {
+            self.shift = self.buttons.bits();
+        }
+    }
+
+    /// Next button in bit 0. After all eight, official controllers shift in 1s.
+    pub fn read(&mut self) -> u8 {
+        if self.strobe {
+            return self.buttons.bits() & 0x01;
+        }
+        let bit = self.shift & 0x01;
+        self.shift = (self.shift >> 1) | 0x80;
+        bit
+    }
+
+    /// Bit 0 the next read would return, without shifting
+    pub fn peek(&self) -> u8 {
+        if self.strobe {
+            self.buttons.bits() & 0x01
+        } else {
+            self.shift & 0x01
+        }
+    }
+}
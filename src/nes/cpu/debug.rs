@@ -0,0 +1,138 @@
+// src/nes/cpu/debug.rs
+// PC breakpoints and memory watchpoints checked by the CPU core
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Which CPU accesses a watchpoint triggers on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u16>,
+    pub kind: WatchKind,
+}
+
+/// Why `step()` stopped to hand control back to a debugger
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// About to execute the instruction at `pc`
+    Breakpoint { pc: u16 },
+    /// The previous instruction read or wrote a watched address
+    Watchpoint { addr: u16, value: u8, write: bool },
+}
+
+impl fmt::Display for DebugEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DebugEvent::Breakpoint { pc } => write!(f, "breakpoint at {:04X}", pc),
+            DebugEvent::Watchpoint { addr, value, write } => {
+                let access = if write { "write" } else { "read" };
+                write!(f, "watchpoint {} of {:02X} at {:04X}", access, value, addr)
+            }
+        }
+    }
+}
+
+/// Breakpoints and watchpoints owned by the CPU. Both lookups sit behind a
+/// single flag each, so an empty debugger costs a branch per step and access.
+#[derive(Default)]
+pub struct Debugger {
+    breakpoints: BTreeSet<u16>,
+    watchpoints: Vec<Watchpoint>,
+    // Event raised mid-instruction, reported by the next step
+    pending: Option<DebugEvent>,
+    // Breakpoint just reported, skipped once so stepping again resumes
+    resume_pc: Option<u16>,
+    // JSR and interrupt entries minus RTS and RTI exits, for step over/out.
+    // Signed since a debugger may attach below the routine that returns.
+    call_depth: isize,
+}
+
+impl Debugger {
+    pub fn add_breakpoint(&mut self, pc: u16) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn remove_breakpoint(&mut self, pc: u16) {
+        self.breakpoints.remove(&pc);
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    pub fn add_watchpoint(&mut self, range: RangeInclusive<u16>, kind: WatchKind) {
+        self.watchpoints.push(Watchpoint { range, kind });
+    }
+
+    pub fn remove_watchpoint(&mut self, range: &RangeInclusive<u16>) {
+        self.watchpoints.retain(|watch| &watch.range != range);
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.watchpoints.clear();
+        self.pending = None;
+        self.resume_pc = None;
+    }
+
+    /// Subroutine and interrupt nesting relative to power-on
+    pub fn call_depth(&self) -> isize {
+        self.call_depth
+    }
+
+    pub(super) fn enter_call(&mut self) {
+        self.call_depth += 1;
+    }
+
+    pub(super) fn leave_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    // Called at an instruction boundary before executing anything
+    pub(super) fn check_boundary(&mut self, pc: u16) -> Option<DebugEvent> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        if self.breakpoints.is_empty() {
+            return None;
+        }
+        if self.resume_pc.take() == Some(pc) || !self.breakpoints.contains(&pc) {
+            return None;
+        }
+        self.resume_pc = Some(pc);
+        Some(DebugEvent::Breakpoint { pc })
+    }
+
+    pub(super) fn watching(&self) -> bool {
+        !self.watchpoints.is_empty()
+    }
+
+    pub(super) fn check_access(&mut self, addr: u16, value: u8, write: bool) {
+        if self.pending.is_some() {
+            return;
+        }
+        let hit = self.watchpoints.iter().any(|watch| {
+            let kind_matches = match watch.kind {
+                WatchKind::Read => !write,
+                WatchKind::Write => write,
+                WatchKind::ReadWrite => true,
+            };
+            kind_matches && watch.range.contains(&addr)
+        });
+        if hit {
+            self.pending = Some(DebugEvent::Watchpoint { addr, value, write });
+        }
+    }
+}
@@ -1,6 +1,12 @@
 // src/nes/cpu/mod.rs
 // CPU module
+mod debug;
+mod opcodes;
 mod ricoh_2a03_cpu;
+mod trace;
 
 // Re-export public interface
-pub use ricoh_2a03_cpu::{Bus, Cpu2A03, InterruptType};
\ No newline at end of file
+pub use debug::{DebugEvent, Debugger, WatchKind, Watchpoint};
+pub use opcodes::{AddressingMode, Mnemonic, Opcode, OPCODES};
+pub use ricoh_2a03_cpu::{Bus, Cpu2A03, CpuError, CpuState, InterruptType, IrqSource, UnstableOpcodes};
+pub use trace::trace_line;
\ No newline at end of file
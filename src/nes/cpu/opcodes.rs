@@ -0,0 +1,333 @@
+// src/nes/cpu/opcodes.rs
+// 256-entry 6502 opcode descriptor table: mnemonic, addressing mode, base cycles
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mnemonic {
+    Adc, Alr, Anc, And, Ane, Arr, Asl, Axs, Bcc, Bcs, Beq, Bit,
+    Bmi, Bne, Bpl, Brk, Bvc, Bvs, Clc, Cld, Cli, Clv, Cmp, Cpx,
+    Cpy, Dcp, Dec, Dex, Dey, Eor, Inc, Inx, Iny, Isc, Jam, Jmp,
+    Jsr, Las, Lax, Lda, Ldx, Ldy, Lsr, Lxa, Nop, Ora, Pha, Php,
+    Pla, Plp, Rla, Rol, Ror, Rra, Rti, Rts, Sax, Sbc, Sec, Sed,
+    Sei, Sha, Shx, Shy, Slo, Sre, Sta, Stx, Sty, Tas, Tax, Tay,
+    Tsx, Txa, Txs, Tya,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Relative,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+}
+
+impl AddressingMode {
+    /// Operand bytes following the opcode
+    pub fn operand_len(self) -> u16 {
+        match self {
+            Implied | Accumulator => 0,
+            Immediate | ZeroPage | ZeroPageX | ZeroPageY | Relative | IndirectX | IndirectY => 1,
+            Absolute | AbsoluteX | AbsoluteY | Indirect => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Opcode {
+    pub mnemonic: Mnemonic,
+    pub mode: AddressingMode,
+    /// Cycles before page-crossing and branch penalties
+    pub cycles: u8,
+    /// Documented by MOS; everything else is an undocumented NMOS side effect
+    pub official: bool,
+}
+
+const fn op(mnemonic: Mnemonic, mode: AddressingMode, cycles: u8, official: bool) -> Opcode {
+    Opcode { mnemonic, mode, cycles, official }
+}
+
+use AddressingMode::*;
+use Mnemonic::*;
+
+pub static OPCODES: [Opcode; 256] = [
+    // 0x0_
+    op(Brk, Implied, 7, true), // 0x00
+    op(Ora, IndirectX, 6, true), // 0x01
+    op(Jam, Implied, 2, false), // 0x02
+    op(Slo, IndirectX, 8, false), // 0x03
+    op(Nop, ZeroPage, 3, false), // 0x04
+    op(Ora, ZeroPage, 3, true), // 0x05
+    op(Asl, ZeroPage, 5, true), // 0x06
+    op(Slo, ZeroPage, 5, false), // 0x07
+    op(Php, Implied, 3, true), // 0x08
+    op(Ora, Immediate, 2, true), // 0x09
+    op(Asl, Accumulator, 2, true), // 0x0A
+    op(Anc, Immediate, 2, false), // 0x0B
+    op(Nop, Absolute, 4, false), // 0x0C
+    op(Ora, Absolute, 4, true), // 0x0D
+    op(Asl, Absolute, 6, true), // 0x0E
+    op(Slo, Absolute, 6, false), // 0x0F
+    // 0x1_
+    op(Bpl, Relative, 2, true), // 0x10
+    op(Ora, IndirectY, 5, true), // 0x11
+    op(Jam, Implied, 2, false), // 0x12
+    op(Slo, IndirectY, 8, false), // 0x13
+    op(Nop, ZeroPageX, 4, false), // 0x14
+    op(Ora, ZeroPageX, 4, true), // 0x15
+    op(Asl, ZeroPageX, 6, true), // 0x16
+    op(Slo, ZeroPageX, 6, false), // 0x17
+    op(Clc, Implied, 2, true), // 0x18
+    op(Ora, AbsoluteY, 4, true), // 0x19
+    op(Nop, Implied, 2, false), // 0x1A
+    op(Slo, AbsoluteY, 7, false), // 0x1B
+    op(Nop, AbsoluteX, 4, false), // 0x1C
+    op(Ora, AbsoluteX, 4, true), // 0x1D
+    op(Asl, AbsoluteX, 7, true), // 0x1E
+    op(Slo, AbsoluteX, 7, false), // 0x1F
+    // 0x2_
+    op(Jsr, Absolute, 6, true), // 0x20
+    op(And, IndirectX, 6, true), // 0x21
+    op(Jam, Implied, 2, false), // 0x22
+    op(Rla, IndirectX, 8, false), // 0x23
+    op(Bit, ZeroPage, 3, true), // 0x24
+    op(And, ZeroPage, 3, true), // 0x25
+    op(Rol, ZeroPage, 5, true), // 0x26
+    op(Rla, ZeroPage, 5, false), // 0x27
+    op(Plp, Implied, 4, true), // 0x28
+    op(And, Immediate, 2, true), // 0x29
+    op(Rol, Accumulator, 2, true), // 0x2A
+    op(Anc, Immediate, 2, false), // 0x2B
+    op(Bit, Absolute, 4, true), // 0x2C
+    op(And, Absolute, 4, true), // 0x2D
+    op(Rol, Absolute, 6, true), // 0x2E
+    op(Rla, Absolute, 6, false), // 0x2F
+    // 0x3_
+    op(Bmi, Relative, 2, true), // 0x30
+    op(And, IndirectY, 5, true), // 0x31
+    op(Jam, Implied, 2, false), // 0x32
+    op(Rla, IndirectY, 8, false), // 0x33
+    op(Nop, ZeroPageX, 4, false), // 0x34
+    op(And, ZeroPageX, 4, true), // 0x35
+    op(Rol, ZeroPageX, 6, true), // 0x36
+    op(Rla, ZeroPageX, 6, false), // 0x37
+    op(Sec, Implied, 2, true), // 0x38
+    op(And, AbsoluteY, 4, true), // 0x39
+    op(Nop, Implied, 2, false), // 0x3A
+    op(Rla, AbsoluteY, 7, false), // 0x3B
+    op(Nop, AbsoluteX, 4, false), // 0x3C
+    op(And, AbsoluteX, 4, true), // 0x3D
+    op(Rol, AbsoluteX, 7, true), // 0x3E
+    op(Rla, AbsoluteX, 7, false), // 0x3F
+    // 0x4_
+    op(Rti, Implied, 6, true), // 0x40
+    op(Eor, IndirectX, 6, true), // 0x41
+    op(Jam, Implied, 2, false), // 0x42
+    op(Sre, IndirectX, 8, false), // 0x43
+    op(Nop, ZeroPage, 3, false), // 0x44
+    op(Eor, ZeroPage, 3, true), // 0x45
+    op(Lsr, ZeroPage, 5, true), // 0x46
+    op(Sre, ZeroPage, 5, false), // 0x47
+    op(Pha, Implied, 3, true), // 0x48
+    op(Eor, Immediate, 2, true), // 0x49
+    op(Lsr, Accumulator, 2, true), // 0x4A
+    op(Alr, Immediate, 2, false), // 0x4B
+    op(Jmp, Absolute, 3, true), // 0x4C
+    op(Eor, Absolute, 4, true), // 0x4D
+    op(Lsr, Absolute, 6, true), // 0x4E
+    op(Sre, Absolute, 6, false), // 0x4F
+    // 0x5_
+    op(Bvc, Relative, 2, true), // 0x50
+    op(Eor, IndirectY, 5, true), // 0x51
+    op(Jam, Implied, 2, false), // 0x52
+    op(Sre, IndirectY, 8, false), // 0x53
+    op(Nop, ZeroPageX, 4, false), // 0x54
+    op(Eor, ZeroPageX, 4, true), // 0x55
+    op(Lsr, ZeroPageX, 6, true), // 0x56
+    op(Sre, ZeroPageX, 6, false), // 0x57
+    op(Cli, Implied, 2, true), // 0x58
+    op(Eor, AbsoluteY, 4, true), // 0x59
+    op(Nop, Implied, 2, false), // 0x5A
+    op(Sre, AbsoluteY, 7, false), // 0x5B
+    op(Nop, AbsoluteX, 4, false), // 0x5C
+    op(Eor, AbsoluteX, 4, true), // 0x5D
+    op(Lsr, AbsoluteX, 7, true), // 0x5E
+    op(Sre, AbsoluteX, 7, false), // 0x5F
+    // 0x6_
+    op(Rts, Implied, 6, true), // 0x60
+    op(Adc, IndirectX, 6, true), // 0x61
+    op(Jam, Implied, 2, false), // 0x62
+    op(Rra, IndirectX, 8, false), // 0x63
+    op(Nop, ZeroPage, 3, false), // 0x64
+    op(Adc, ZeroPage, 3, true), // 0x65
+    op(Ror, ZeroPage, 5, true), // 0x66
+    op(Rra, ZeroPage, 5, false), // 0x67
+    op(Pla, Implied, 4, true), // 0x68
+    op(Adc, Immediate, 2, true), // 0x69
+    op(Ror, Accumulator, 2, true), // 0x6A
+    op(Arr, Immediate, 2, false), // 0x6B
+    op(Jmp, Indirect, 5, true), // 0x6C
+    op(Adc, Absolute, 4, true), // 0x6D
+    op(Ror, Absolute, 6, true), // 0x6E
+    op(Rra, Absolute, 6, false), // 0x6F
+    // 0x7_
+    op(Bvs, Relative, 2, true), // 0x70
+    op(Adc, IndirectY, 5, true), // 0x71
+    op(Jam, Implied, 2, false), // 0x72
+    op(Rra, IndirectY, 8, false), // 0x73
+    op(Nop, ZeroPageX, 4, false), // 0x74
+    op(Adc, ZeroPageX, 4, true), // 0x75
+    op(Ror, ZeroPageX, 6, true), // 0x76
+    op(Rra, ZeroPageX, 6, false), // 0x77
+    op(Sei, Implied, 2, true), // 0x78
+    op(Adc, AbsoluteY, 4, true), // 0x79
+    op(Nop, Implied, 2, false), // 0x7A
+    op(Rra, AbsoluteY, 7, false), // 0x7B
+    op(Nop, AbsoluteX, 4, false), // 0x7C
+    op(Adc, AbsoluteX, 4, true), // 0x7D
+    op(Ror, AbsoluteX, 7, true), // 0x7E
+    op(Rra, AbsoluteX, 7, false), // 0x7F
+    // 0x8_
+    op(Nop, Immediate, 2, false), // 0x80
+    op(Sta, IndirectX, 6, true), // 0x81
+    op(Nop, Immediate, 2, false), // 0x82
+    op(Sax, IndirectX, 6, false), // 0x83
+    op(Sty, ZeroPage, 3, true), // 0x84
+    op(Sta, ZeroPage, 3, true), // 0x85
+    op(Stx, ZeroPage, 3, true), // 0x86
+    op(Sax, ZeroPage, 3, false), // 0x87
+    op(Dey, Implied, 2, true), // 0x88
+    op(Nop, Immediate, 2, false), // 0x89
+    op(Txa, Implied, 2, true), // 0x8A
+    op(Ane, Immediate, 2, false), // 0x8B
+    op(Sty, Absolute, 4, true), // 0x8C
+    op(Sta, Absolute, 4, true), // 0x8D
+    op(Stx, Absolute, 4, true), // 0x8E
+    op(Sax, Absolute, 4, false), // 0x8F
+    // 0x9_
+    op(Bcc, Relative, 2, true), // 0x90
+    op(Sta, IndirectY, 6, true), // 0x91
+    op(Jam, Implied, 2, false), // 0x92
+    op(Sha, IndirectY, 6, false), // 0x93
+    op(Sty, ZeroPageX, 4, true), // 0x94
+    op(Sta, ZeroPageX, 4, true), // 0x95
+    op(Stx, ZeroPageY, 4, true), // 0x96
+    op(Sax, ZeroPageY, 4, false), // 0x97
+    op(Tya, Implied, 2, true), // 0x98
+    op(Sta, AbsoluteY, 5, true), // 0x99
+    op(Txs, Implied, 2, true), // 0x9A
+    op(Tas, AbsoluteY, 5, false), // 0x9B
+    op(Shy, AbsoluteX, 5, false), // 0x9C
+    op(Sta, AbsoluteX, 5, true), // 0x9D
+    op(Shx, AbsoluteY, 5, false), // 0x9E
+    op(Sha, AbsoluteY, 5, false), // 0x9F
+    // 0xA_
+    op(Ldy, Immediate, 2, true), // 0xA0
+    op(Lda, IndirectX, 6, true), // 0xA1
+    op(Ldx, Immediate, 2, true), // 0xA2
+    op(Lax, IndirectX, 6, false), // 0xA3
+    op(Ldy, ZeroPage, 3, true), // 0xA4
+    op(Lda, ZeroPage, 3, true), // 0xA5
+    op(Ldx, ZeroPage, 3, true), // 0xA6
+    op(Lax, ZeroPage, 3, false), // 0xA7
+    op(Tay, Implied, 2, true), // 0xA8
+    op(Lda, Immediate, 2, true), // 0xA9
+    op(Tax, Implied, 2, true), // 0xAA
+    op(Lxa, Immediate, 2, false), // 0xAB
+    op(Ldy, Absolute, 4, true), // 0xAC
+    op(Lda, Absolute, 4, true), // 0xAD
+    op(Ldx, Absolute, 4, true), // 0xAE
+    op(Lax, Absolute, 4, false), // 0xAF
+    // 0xB_
+    op(Bcs, Relative, 2, true), // 0xB0
+    op(Lda, IndirectY, 5, true), // 0xB1
+    op(Jam, Implied, 2, false), // 0xB2
+    op(Lax, IndirectY, 5, false), // 0xB3
+    op(Ldy, ZeroPageX, 4, true), // 0xB4
+    op(Lda, ZeroPageX, 4, true), // 0xB5
+    op(Ldx, ZeroPageY, 4, true), // 0xB6
+    op(Lax, ZeroPageY, 4, false), // 0xB7
+    op(Clv, Implied, 2, true), // 0xB8
+    op(Lda, AbsoluteY, 4, true), // 0xB9
+    op(Tsx, Implied, 2, true), // 0xBA
+    op(Las, AbsoluteY, 4, false), // 0xBB
+    op(Ldy, AbsoluteX, 4, true), // 0xBC
+    op(Lda, AbsoluteX, 4, true), // 0xBD
+    op(Ldx, AbsoluteY, 4, true), // 0xBE
+    op(Lax, AbsoluteY, 4, false), // 0xBF
+    // 0xC_
+    op(Cpy, Immediate, 2, true), // 0xC0
+    op(Cmp, IndirectX, 6, true), // 0xC1
+    op(Nop, Immediate, 2, false), // 0xC2
+    op(Dcp, IndirectX, 8, false), // 0xC3
+    op(Cpy, ZeroPage, 3, true), // 0xC4
+    op(Cmp, ZeroPage, 3, true), // 0xC5
+    op(Dec, ZeroPage, 5, true), // 0xC6
+    op(Dcp, ZeroPage, 5, false), // 0xC7
+    op(Iny, Implied, 2, true), // 0xC8
+    op(Cmp, Immediate, 2, true), // 0xC9
+    op(Dex, Implied, 2, true), // 0xCA
+    op(Axs, Immediate, 2, false), // 0xCB
+    op(Cpy, Absolute, 4, true), // 0xCC
+    op(Cmp, Absolute, 4, true), // 0xCD
+    op(Dec, Absolute, 6, true), // 0xCE
+    op(Dcp, Absolute, 6, false), // 0xCF
+    // 0xD_
+    op(Bne, Relative, 2, true), // 0xD0
+    op(Cmp, IndirectY, 5, true), // 0xD1
+    op(Jam, Implied, 2, false), // 0xD2
+    op(Dcp, IndirectY, 8, false), // 0xD3
+    op(Nop, ZeroPageX, 4, false), // 0xD4
+    op(Cmp, ZeroPageX, 4, true), // 0xD5
+    op(Dec, ZeroPageX, 6, true), // 0xD6
+    op(Dcp, ZeroPageX, 6, false), // 0xD7
+    op(Cld, Implied, 2, true), // 0xD8
+    op(Cmp, AbsoluteY, 4, true), // 0xD9
+    op(Nop, Implied, 2, false), // 0xDA
+    op(Dcp, AbsoluteY, 7, false), // 0xDB
+    op(Nop, AbsoluteX, 4, false), // 0xDC
+    op(Cmp, AbsoluteX, 4, true), // 0xDD
+    op(Dec, AbsoluteX, 7, true), // 0xDE
+    op(Dcp, AbsoluteX, 7, false), // 0xDF
+    // 0xE_
+    op(Cpx, Immediate, 2, true), // 0xE0
+    op(Sbc, IndirectX, 6, true), // 0xE1
+    op(Nop, Immediate, 2, false), // 0xE2
+    op(Isc, IndirectX, 8, false), // 0xE3
+    op(Cpx, ZeroPage, 3, true), // 0xE4
+    op(Sbc, ZeroPage, 3, true), // 0xE5
+    op(Inc, ZeroPage, 5, true), // 0xE6
+    op(Isc, ZeroPage, 5, false), // 0xE7
+    op(Inx, Implied, 2, true), // 0xE8
+    op(Sbc, Immediate, 2, true), // 0xE9
+    op(Nop, Implied, 2, true), // 0xEA
+    op(Sbc, Immediate, 2, false), // 0xEB
+    op(Cpx, Absolute, 4, true), // 0xEC
+    op(Sbc, Absolute, 4, true), // 0xED
+    op(Inc, Absolute, 6, true), // 0xEE
+    op(Isc, Absolute, 6, false), // 0xEF
+    // 0xF_
+    op(Beq, Relative, 2, true), // 0xF0
+    op(Sbc, IndirectY, 5, true), // 0xF1
+    op(Jam, Implied, 2, false), // 0xF2
+    op(Isc, IndirectY, 8, false), // 0xF3
+    op(Nop, ZeroPageX, 4, false), // 0xF4
+    op(Sbc, ZeroPageX, 4, true), // 0xF5
+    op(Inc, ZeroPageX, 6, true), // 0xF6
+    op(Isc, ZeroPageX, 6, false), // 0xF7
+    op(Sed, Implied, 2, true), // 0xF8
+    op(Sbc, AbsoluteY, 4, true), // 0xF9
+    op(Nop, Implied, 2, false), // 0xFA
+    op(Isc, AbsoluteY, 7, false), // 0xFB
+    op(Nop, AbsoluteX, 4, false), // 0xFC
+    op(Sbc, AbsoluteX, 4, true), // 0xFD
+    op(Inc, AbsoluteX, 7, true), // 0xFE
+    op(Isc, AbsoluteX, 7, false), // 0xFF
+];
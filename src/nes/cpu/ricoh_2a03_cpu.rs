@@ -1,18 +1,97 @@
 // ricoh_2a03_cpu.rs
 // Ricoh 2A03/2A07 CPU (NES) emulation core
 
+use bitflags::bitflags;
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::debug::{DebugEvent, Debugger};
+use super::opcodes::{AddressingMode, Mnemonic, Opcode, OPCODES};
+
 pub trait Bus {
     fn read(&mut self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, data: u8);
+
+    /// Read for debuggers and trace logs. Buses with read-sensitive
+    /// registers should override this to avoid their side effects.
+    fn peek(&mut self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
+    /// Start of a CPU cycle, before its access: the rest of the system
+    /// (PPU, APU, mapper counters) runs up to the moment the CPU reads or
+    /// writes. Called for every cycle, including DMA and reset cycles.
+    fn tick(&mut self) {}
+
+    /// NMI raised by a device on the bus (the PPU entering vblank) since
+    /// the last call
+    fn take_nmi(&mut self) -> bool {
+        false
+    }
+
+    /// Devices on the bus pulling the shared /IRQ line low (typically the
+    /// cartridge), ORed with the CPU's own `IrqSource` lines
+    fn irq_asserted(&self) -> bool {
+        false
+    }
+
+    /// Sample address the DMC wants fetched, if its buffer has emptied. The
+    /// CPU halts on its next read cycle to let the fetch through.
+    fn take_dmc_dma(&mut self) -> Option<u16> {
+        None
+    }
+
+    /// Sample byte fetched for the DMC
+    fn dmc_dma_complete(&mut self, _data: u8) {}
+
+    /// Page written to $4014 since the last call, if any. The CPU halts at
+    /// the next instruction boundary and copies that page to $2004.
+    fn take_oam_dma(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+bitflags! {
+    /// Sources wired to the /IRQ line. The line is level-triggered: it stays
+    /// asserted, and keeps interrupting whenever I is clear, until every
+    /// source has been acknowledged.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct IrqSource: u8 {
+        const FRAME_COUNTER = 0b0001;
+        const DMC           = 0b0010;
+        const MAPPER        = 0b0100;
+        const EXTERNAL      = 0b1000;
+    }
 }
 
-#[derive(PartialEq)]
-enum InterruptType {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InterruptType {
     Nmi,
     Irq,
     Brk,
 }
 
+#[derive(Debug, Error)]
+pub enum CpuError {
+    /// A JAM opcode locked up the CPU; `reset` recovers
+    #[error("CPU jammed by opcode {opcode:#04X} at {pc:#06X}")]
+    Jammed { pc: u16, opcode: u8 },
+    /// Not a fault: a breakpoint or watchpoint stopped execution at an
+    /// instruction boundary. No cycles ran; stepping again resumes.
+    #[error("stopped at {0}")]
+    Break(DebugEvent),
+}
+
+// How an instruction touches its memory operand
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+    ReadModifyWrite,
+}
+
 const CARRY: u8 = 1 << 0;
 const ZERO: u8 = 1 << 1;
 const INTERRUPT_DISABLE: u8 = 1 << 2;
@@ -21,6 +100,44 @@ const BREAK: u8 = 1 << 4;
 const OVERFLOW: u8 = 1 << 6;
 const NEGATIVE: u8 = 1 << 7;
 
+/// Chip-dependent results of the unstable undocumented opcodes. The
+/// defaults match the values most test ROMs expect from a 2A03.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnstableOpcodes {
+    /// Constant ORed into A by ANE (XAA, $8B)
+    pub ane_magic: u8,
+    /// Constant ORed into A by LXA (LAX #imm, $AB)
+    pub lxa_magic: u8,
+    /// SHA/SHX/SHY/TAS replace the address high byte with the stored value
+    /// when indexing crosses a page
+    pub sh_address_corruption: bool,
+}
+
+impl Default for UnstableOpcodes {
+    fn default() -> Self {
+        Self {
+            ane_magic: 0xEE,
+            lxa_magic: 0xEE,
+            sh_address_corruption: true,
+        }
+    }
+}
+
+const RESET_CYCLES: usize = 7;
+const OAM_DATA: u16 = 0x2004;
+const OAM_DMA_TRANSFER_CYCLES: u16 = 512; // 256 read/write pairs
+
+// Sprite DMA in progress: a halt cycle, an optional alignment cycle so reads
+// land on even cycles, then the transfer itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct OamDma {
+    page: u8,
+    cycle: u16,
+    len: u16,
+}
+
 pub struct Cpu2A03<B: Bus> {
     // Registers
     pub a: u8,
@@ -32,18 +149,82 @@ pub struct Cpu2A03<B: Bus> {
     
     // Interrupt state
     pub nmi_pending: bool,
-    pub irq_pending: bool,
-    pub interrupt_mask_delay: bool,
+    irq_line: IrqSource,
+    // Interrupt lines as sampled at the end of the latest cycle and the one
+    // before. An instruction acts on the sample from its penultimate cycle,
+    // so CLI/SEI/PLP take effect one instruction late while RTI doesn't.
+    nmi_sample: bool,
+    irq_sample: bool,
+    prev_nmi_sample: bool,
+    prev_irq_sample: bool,
     
     // Memory bus
     pub bus: B,
+
+    pub debugger: Debugger,
     
     // Cycle counting
     cycles: usize,
+
+    // Mid-instruction state for cycle stepping. `step_cycle` is 0 at an
+    // instruction boundary, where the next tick fetches an opcode.
+    opcode: u8,
+    step_cycle: u8,
+    access_cycle: u8, // First cycle of the operand access; 0 while addressing
+    addr: u16,
+    pointer: u8,
+    value: u8,
+    crossed: bool,
+    interrupt: Option<InterruptType>,
+    oam_dma: Option<OamDma>,
+    jammed: bool,
+
+    // BCD arithmetic when D is set; wired off on the 2A03
+    decimal_mode: bool,
+
+    pub unstable: UnstableOpcodes,
+}
+
+/// Snapshot of everything the CPU holds apart from its bus and debugger,
+/// mid-instruction progress included, for save states and test snapshots
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CpuState {
+    a: u8,
+    x: u8,
+    y: u8,
+    pc: u16,
+    sp: u8,
+    status: u8,
+    nmi_pending: bool,
+    irq_line: u8,
+    nmi_sample: bool,
+    irq_sample: bool,
+    prev_nmi_sample: bool,
+    prev_irq_sample: bool,
+    cycles: usize,
+    opcode: u8,
+    step_cycle: u8,
+    access_cycle: u8,
+    addr: u16,
+    pointer: u8,
+    value: u8,
+    crossed: bool,
+    interrupt: Option<InterruptType>,
+    oam_dma: Option<OamDma>,
+    jammed: bool,
+    decimal_mode: bool,
+    unstable: UnstableOpcodes,
 }
 
 impl<B: Bus> Cpu2A03<B> {
     pub fn new(bus: B) -> Self {
+        Self::with_decimal_mode(bus, false)
+    }
+
+    /// With `decimal_mode` set, ADC/SBC (and the undocumented opcodes built
+    /// on them) honour the D flag like a stock NMOS 6502
+    pub fn with_decimal_mode(bus: B, decimal_mode: bool) -> Self {
         Self {
             a: 0,
             x: 0,
@@ -52,36 +233,169 @@ impl<B: Bus> Cpu2A03<B> {
             sp: 0xFD,
             status: 0x34,
             nmi_pending: false,
-            irq_pending: false,
-            interrupt_mask_delay: false,
+            irq_line: IrqSource::empty(),
+            nmi_sample: false,
+            irq_sample: false,
+            prev_nmi_sample: false,
+            prev_irq_sample: false,
             bus,
+            debugger: Debugger::default(),
             cycles: 0,
+            opcode: 0,
+            step_cycle: 0,
+            access_cycle: 0,
+            addr: 0,
+            pointer: 0,
+            value: 0,
+            crossed: false,
+            interrupt: None,
+            oam_dma: None,
+            jammed: false,
+            decimal_mode,
+            unstable: UnstableOpcodes::default(),
         }
     }
 
+    pub fn save_state(&self) -> CpuState {
+        CpuState {
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            pc: self.pc,
+            sp: self.sp,
+            status: self.status,
+            nmi_pending: self.nmi_pending,
+            irq_line: self.irq_line.bits(),
+            nmi_sample: self.nmi_sample,
+            irq_sample: self.irq_sample,
+            prev_nmi_sample: self.prev_nmi_sample,
+            prev_irq_sample: self.prev_irq_sample,
+            cycles: self.cycles,
+            opcode: self.opcode,
+            step_cycle: self.step_cycle,
+            access_cycle: self.access_cycle,
+            addr: self.addr,
+            pointer: self.pointer,
+            value: self.value,
+            crossed: self.crossed,
+            interrupt: self.interrupt,
+            oam_dma: self.oam_dma,
+            jammed: self.jammed,
+            decimal_mode: self.decimal_mode,
+            unstable: self.unstable,
+        }
+    }
+
+    /// Restore a snapshot, possibly taken mid-instruction; the bus is left alone
+    pub fn load_state(&mut self, state: &CpuState) {
+        self.a = state.a;
+        self.x = state.x;
+        self.y = state.y;
+        self.pc = state.pc;
+        self.sp = state.sp;
+        self.status = state.status;
+        self.nmi_pending = state.nmi_pending;
+        self.irq_line = IrqSource::from_bits_truncate(state.irq_line);
+        self.nmi_sample = state.nmi_sample;
+        self.irq_sample = state.irq_sample;
+        self.prev_nmi_sample = state.prev_nmi_sample;
+        self.prev_irq_sample = state.prev_irq_sample;
+        self.cycles = state.cycles;
+        self.opcode = state.opcode;
+        self.step_cycle = state.step_cycle;
+        self.access_cycle = state.access_cycle;
+        self.addr = state.addr;
+        self.pointer = state.pointer;
+        self.value = state.value;
+        self.crossed = state.crossed;
+        self.interrupt = state.interrupt;
+        self.oam_dma = state.oam_dma;
+        self.jammed = state.jammed;
+        self.decimal_mode = state.decimal_mode;
+        self.unstable = state.unstable;
+    }
+
     pub fn reset(&mut self) {
-        self.pc = self.read_u16(0xFFFC);
+        // Five cycles of suppressed stack accesses, then the vector fetch
+        for _ in 0..RESET_CYCLES - 2 {
+            self.begin_cycle();
+        }
+        self.begin_cycle();
+        let lo = self.bus.read(0xFFFC) as u16;
+        self.begin_cycle();
+        let hi = self.bus.read(0xFFFD) as u16;
+        self.pc = (hi << 8) | lo;
         self.sp = 0xFD;
         self.status = 0x34;
-        self.cycles += 8;
+        self.step_cycle = 0;
+        self.interrupt = None;
+        self.oam_dma = None;
+        self.jammed = false;
+        self.nmi_sample = false;
+        self.irq_sample = false;
+    }
+
+    /// Total CPU cycles executed
+    pub fn cycles(&self) -> usize {
+        self.cycles
     }
 
-    // Memory operations
-    fn read_u16(&mut self, addr: u16) -> u16 {
-        let lo = self.bus.read(addr) as u16;
-        let hi = self.bus.read(addr + 1) as u16;
-        (hi << 8) | lo
+    /// True once a JAM opcode has locked up the CPU. Only `reset` recovers.
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+
+    /// True between instructions, when the next tick fetches an opcode
+    pub fn at_instruction_boundary(&self) -> bool {
+        self.step_cycle == 0
     }
 
     // Stack operations
     fn push(&mut self, data: u8) {
-        self.bus.write(0x0100 | self.sp as u16, data);
+        self.write(0x0100 | self.sp as u16, data);
         self.sp = self.sp.wrapping_sub(1);
     }
 
     fn pop(&mut self) -> u8 {
         self.sp = self.sp.wrapping_add(1);
-        self.bus.read(0x0100 | self.sp as u16)
+        self.read(0x0100 | self.sp as u16)
+    }
+
+    // Every CPU read cycle goes through here so a pending DMC fetch can halt
+    // the CPU on it. The halt, dummy and alignment cycles repeat this read,
+    // which is what clobbers back-to-back controller and $2007 reads; the
+    // read the CPU actually uses comes last.
+    fn read(&mut self, addr: u16) -> u8 {
+        if let Some(sample_addr) = self.bus.take_dmc_dma() {
+            // Halt and dummy cycles, then alignment so the fetch lands on
+            // an odd (get) cycle: 3 or 4 cycles stolen
+            let stall = if (self.cycles + 2) & 1 == 1 { 2 } else { 3 };
+            for _ in 0..stall {
+                self.bus.read(addr);
+                self.begin_cycle();
+            }
+            let sample = self.bus.read(sample_addr);
+            self.bus.dmc_dma_complete(sample);
+            self.begin_cycle();
+        }
+        let data = self.bus.read(addr);
+        if self.debugger.watching() {
+            self.debugger.check_access(addr, data, false);
+        }
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        if self.debugger.watching() {
+            self.debugger.check_access(addr, data, true);
+        }
+        self.bus.write(addr, data);
+    }
+
+    fn fetch(&mut self) -> u8 {
+        let data = self.read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        data
     }
 
     // Flag operations
@@ -97,179 +411,886 @@ impl<B: Bus> Cpu2A03<B> {
         (self.status & flag) != 0
     }
 
-    // Addressing modes
-    fn imm(&mut self) -> u8 {
-        let val = self.bus.read(self.pc);
-        self.pc += 1;
-        val
+    // Interrupt handling
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
+
+    /// Pull /IRQ low for `source`. While I is set the request simply waits.
+    pub fn assert_irq(&mut self, source: IrqSource) {
+        self.irq_line.insert(source);
     }
 
-    fn zpg(&mut self) -> u16 {
-        self.bus.read(self.pc) as u16
+    /// Release `source`'s hold on /IRQ, as when its interrupt is acknowledged
+    pub fn clear_irq(&mut self, source: IrqSource) {
+        self.irq_line.remove(source);
     }
 
-    fn abs(&mut self) -> u16 {
-        let lo = self.bus.read(self.pc) as u16;
-        self.pc += 1;
-        let hi = self.bus.read(self.pc) as u16;
-        self.pc += 1;
-        (hi << 8) | lo
+    /// Sources currently asserting /IRQ, excluding the bus's
+    pub fn irq_line(&self) -> IrqSource {
+        self.irq_line
     }
 
-    fn abs_x(&mut self) -> (u16, bool) {
-        let base = self.abs();
-        let addr = base.wrapping_add(self.x as u16);
-        (addr, (base & 0xFF00) != (addr & 0xFF00))
+    // Instruction implementations
+    fn set_zn(&mut self, value: u8) {
+        self.set_flag(ZERO, value == 0);
+        self.set_flag(NEGATIVE, (value & 0x80) != 0);
     }
 
-    fn abs_y(&mut self) -> (u16, bool) {
-        let base = self.abs();
-        let addr = base.wrapping_add(self.y as u16);
-        (addr, (base & 0xFF00) != (addr & 0xFF00))
+    fn lda(&mut self, value: u8) {
+        self.a = value;
+        self.set_zn(self.a);
     }
 
-    fn zpg_x(&mut self) -> u16 {
-        (self.zpg() + self.x as u16) & 0xFF
+    fn ldx(&mut self, value: u8) {
+        self.x = value;
+        self.set_zn(self.x);
     }
 
-    fn zpg_y(&mut self) -> u16 {
-        (self.zpg() + self.y as u16) & 0xFF
+    fn ldy(&mut self, value: u8) {
+        self.y = value;
+        self.set_zn(self.y);
     }
 
-    fn idx_ind(&mut self) -> u16 {
-        let ptr = (self.zpg() + self.x as u16) & 0xFF;
-        let lo = self.bus.read(ptr) as u16;
-        let hi = self.bus.read((ptr + 1) & 0xFF) as u16;
-        (hi << 8) | lo
+    fn tax(&mut self) {
+        self.x = self.a;
+        self.set_zn(self.x);
     }
 
-    fn ind_idx(&mut self) -> (u16, bool) {
-        let base = self.zpg();
-        let lo = self.bus.read(base) as u16;
-        let hi = self.bus.read((base + 1) & 0xFF) as u16;
-        let effective = (hi << 8) | lo;
-        let addr = effective.wrapping_add(self.y as u16);
-        (addr, (effective & 0xFF00) != (addr & 0xFF00))
+    fn tay(&mut self) {
+        self.y = self.a;
+        self.set_zn(self.y);
     }
 
-    fn ind_abs(&mut self) -> u16 {
-        let addr = self.abs();
-        let lo = self.bus.read(addr) as u16;
-        let hi = if (addr & 0x00FF) == 0x00FF {
-            self.bus.read(addr & 0xFF00) as u16
-        } else {
-            self.bus.read(addr + 1) as u16
-        };
-        (hi << 8) | lo
+    fn tsx(&mut self) {
+        self.x = self.sp;
+        self.set_zn(self.x);
     }
 
-    fn rel(&mut self) -> i8 {
-        self.imm() as i8
+    fn txa(&mut self) {
+        self.a = self.x;
+        self.set_zn(self.a);
     }
 
-    fn imp(&mut self) {
-        // No operation needed
+    fn tya(&mut self) {
+        self.a = self.y;
+        self.set_zn(self.a);
     }
 
-    // Interrupt handling
-    pub fn trigger_nmi(&mut self) {
-        self.nmi_pending = true;
+    fn and(&mut self, value: u8) {
+        self.a &= value;
+        self.set_zn(self.a);
+    }
+
+    fn ora(&mut self, value: u8) {
+        self.a |= value;
+        self.set_zn(self.a);
+    }
+
+    fn eor(&mut self, value: u8) {
+        self.a ^= value;
+        self.set_zn(self.a);
+    }
+
+    fn decimal(&self) -> bool {
+        self.decimal_mode && self.get_flag(DECIMAL)
     }
 
-    pub fn trigger_irq(&mut self) {
-        if !self.get_flag(INTERRUPT_DISABLE) {
-            self.irq_pending = true;
+    fn adc(&mut self, value: u8) {
+        if self.decimal() {
+            self.adc_decimal(value);
+        } else {
+            self.adc_binary(value);
         }
     }
 
-    fn handle_interrupt(&mut self, int_type: InterruptType) -> usize {
-        self.push((self.pc >> 8) as u8);
-        self.push(self.pc as u8);
+    fn adc_binary(&mut self, value: u8) {
+        let sum = self.a as u16 + value as u16 + self.get_flag(CARRY) as u16;
+        let result = sum as u8;
+        self.set_flag(CARRY, sum > 0xFF);
+        self.set_flag(OVERFLOW, ((self.a ^ result) & (value ^ result) & 0x80) != 0);
+        self.a = result;
+        self.set_zn(self.a);
+    }
+
+    // NMOS BCD addition: Z comes from the binary sum, N and V from the
+    // result before the high digit is adjusted
+    fn adc_decimal(&mut self, value: u8) {
+        let carry = self.get_flag(CARRY) as u16;
+        let binary = self.a as u16 + value as u16 + carry;
+        let mut lo = (self.a & 0x0F) as u16 + (value & 0x0F) as u16 + carry;
+        let mut hi = (self.a >> 4) as u16 + (value >> 4) as u16;
+        if lo > 0x09 {
+            lo += 0x06;
+            hi += 1;
+        }
+        let unadjusted = (hi << 4) as u8;
+        self.set_flag(ZERO, binary as u8 == 0);
+        self.set_flag(NEGATIVE, unadjusted & 0x80 != 0);
+        self.set_flag(OVERFLOW, ((self.a ^ unadjusted) & !(self.a ^ value) & 0x80) != 0);
+        if hi > 0x09 {
+            hi += 0x06;
+        }
+        self.set_flag(CARRY, hi > 0x0F);
+        self.a = ((hi << 4) as u8) | (lo as u8 & 0x0F);
+    }
 
-        let mut status = self.status | 0x20;
-        if int_type == InterruptType::Brk {
-            status |= BREAK;
+    fn sbc(&mut self, value: u8) {
+        // NMOS BCD subtraction sets every flag from the binary result
+        let a = self.a;
+        let borrow = !self.get_flag(CARRY) as i16;
+        self.adc_binary(!value);
+        if self.decimal() {
+            let mut lo = (a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+            let mut hi = (a >> 4) as i16 - (value >> 4) as i16;
+            if lo < 0 {
+                lo -= 0x06;
+                hi -= 1;
+            }
+            if hi < 0 {
+                hi -= 0x06;
+            }
+            self.a = ((hi << 4) as u8) | (lo as u8 & 0x0F);
         }
-        self.push(status);
+    }
 
-        self.set_flag(INTERRUPT_DISABLE, true);
+    fn bit(&mut self, value: u8) {
+        self.set_flag(ZERO, (self.a & value) == 0);
+        self.set_flag(OVERFLOW, (value & 0x40) != 0);
+        self.set_flag(NEGATIVE, (value & 0x80) != 0);
+    }
 
-        let vector = match int_type {
-            InterruptType::Nmi => 0xFFFA,
-            InterruptType::Irq | InterruptType::Brk => 0xFFFE,
-        };
+    fn compare(&mut self, reg: u8, value: u8) {
+        self.set_flag(CARRY, reg >= value);
+        self.set_zn(reg.wrapping_sub(value));
+    }
 
-        self.pc = self.read_u16(vector);
-        7
+    fn asl(&mut self, value: u8) -> u8 {
+        let result = value << 1;
+        self.set_flag(CARRY, (value & 0x80) != 0);
+        self.set_zn(result);
+        result
     }
 
-    // Instruction implementations
-    fn lda(&mut self, value: u8) {
+    fn lsr(&mut self, value: u8) -> u8 {
+        let result = value >> 1;
+        self.set_flag(CARRY, (value & 0x01) != 0);
+        self.set_zn(result);
+        result
+    }
+
+    fn rol(&mut self, value: u8) -> u8 {
+        let result = (value << 1) | self.get_flag(CARRY) as u8;
+        self.set_flag(CARRY, (value & 0x80) != 0);
+        self.set_zn(result);
+        result
+    }
+
+    fn ror(&mut self, value: u8) -> u8 {
+        let result = (value >> 1) | ((self.get_flag(CARRY) as u8) << 7);
+        self.set_flag(CARRY, (value & 0x01) != 0);
+        self.set_zn(result);
+        result
+    }
+
+    fn inc(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_add(1);
+        self.set_zn(result);
+        result
+    }
+
+    fn dec(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_sub(1);
+        self.set_zn(result);
+        result
+    }
+
+    // Unofficial opcode helpers
+    fn lax(&mut self, value: u8) {
         self.a = value;
-        self.set_flag(ZERO, self.a == 0);
-        self.set_flag(NEGATIVE, (self.a & 0x80) != 0);
+        self.x = value;
+        self.set_zn(value);
     }
 
-    fn sta(&mut self, addr: u16) {
-        self.bus.write(addr, self.a);
+    fn anc(&mut self, value: u8) {
+        self.and(value);
+        self.set_flag(CARRY, (self.a & 0x80) != 0);
     }
 
-    fn tax(&mut self) {
-        self.x = self.a;
-        self.set_flag(ZERO, self.x == 0);
-        self.set_flag(NEGATIVE, (self.x & 0x80) != 0);
+    fn alr(&mut self, value: u8) {
+        self.a &= value;
+        self.a = self.lsr(self.a);
+    }
+
+    fn arr(&mut self, value: u8) {
+        if self.decimal() {
+            return self.arr_decimal(value);
+        }
+        self.a &= value;
+        self.a = (self.a >> 1) | ((self.get_flag(CARRY) as u8) << 7);
+        self.set_zn(self.a);
+        self.set_flag(CARRY, (self.a & 0x40) != 0);
+        self.set_flag(OVERFLOW, ((self.a >> 6) ^ (self.a >> 5)) & 0x01 != 0);
+    }
+
+    // ARR's decimal fix-up: flags as in binary mode except C, then each
+    // digit is adjusted as if by the ADC carry logic
+    fn arr_decimal(&mut self, value: u8) {
+        let and = self.a & value;
+        self.a = (and >> 1) | ((self.get_flag(CARRY) as u8) << 7);
+        self.set_zn(self.a);
+        self.set_flag(OVERFLOW, ((and ^ self.a) & 0x40) != 0);
+        if (and & 0x0F) + (and & 0x01) > 0x05 {
+            self.a = (self.a & 0xF0) | (self.a.wrapping_add(0x06) & 0x0F);
+        }
+        let carry = (and & 0xF0) as u16 + (and & 0x10) as u16 > 0x50;
+        if carry {
+            self.a = self.a.wrapping_add(0x60);
+        }
+        self.set_flag(CARRY, carry);
+    }
+
+    fn axs(&mut self, value: u8) {
+        let and = self.a & self.x;
+        self.x = and.wrapping_sub(value);
+        self.set_flag(CARRY, and >= value);
+        self.set_zn(self.x);
+    }
+
+    // SHA/SHX/SHY/TAS store the value ANDed with the base address high byte
+    // plus one; on a page cross that value also replaces the high byte
+    // unless the corruption is configured off
+    fn store_high_and(&mut self, addr: u16, crossed: bool, value: u8) {
+        let high = (addr >> 8) as u8;
+        let value = value & if crossed { high } else { high.wrapping_add(1) };
+        let addr = if crossed && self.unstable.sh_address_corruption {
+            ((value as u16) << 8) | (addr & 0x00FF)
+        } else {
+            addr
+        };
+        self.write(addr, value);
+    }
+
+    fn plp(&mut self, value: u8) {
+        // B doesn't exist in the register; bit 5 always reads back set
+        self.status = (value & !BREAK) | 0x20;
     }
 
     // Main execution loop
-    pub fn step(&mut self) -> usize {
+
+    /// Run until the next instruction boundary (finishing the current
+    /// instruction, interrupt sequence or DMA stall) and return the cycles it
+    /// took. Fails once the CPU is jammed, and keeps failing until reset, or
+    /// with `CpuError::Break` when the debugger wants control.
+    pub fn step(&mut self) -> Result<usize, CpuError> {
+        if self.step_cycle == 0 {
+            if let Some(event) = self.debugger.check_boundary(self.pc) {
+                return Err(CpuError::Break(event));
+            }
+        }
+        let start = self.cycles;
+        loop {
+            self.tick();
+            if self.jammed {
+                return Err(CpuError::Jammed { pc: self.pc.wrapping_sub(1), opcode: self.opcode });
+            }
+            if self.step_cycle == 0 && self.oam_dma.is_none() {
+                return Ok(self.cycles - start);
+            }
+        }
+    }
+
+    /// Run until the current instruction returns to this call depth: a JSR
+    /// (or an interrupt taken first) runs through to its matching return.
+    /// Breakpoints, watchpoints and jams stop it early with their error.
+    pub fn step_over(&mut self) -> Result<usize, CpuError> {
+        let depth = self.debugger.call_depth();
+        let mut cycles = self.step()?;
+        while self.debugger.call_depth() > depth {
+            cycles += self.step()?;
+        }
+        Ok(cycles)
+    }
+
+    /// Run until the RTS or RTI that leaves the current subroutine or handler
+    pub fn step_out(&mut self) -> Result<usize, CpuError> {
+        let depth = self.debugger.call_depth();
         let mut cycles = 0;
+        while self.debugger.call_depth() >= depth {
+            cycles += self.step()?;
+        }
+        Ok(cycles)
+    }
+
+    /// Advance one CPU cycle, performing that cycle's bus access. A DMC
+    /// fetch that halts the CPU on this cycle adds its stall to `cycles()`.
+    pub fn tick(&mut self) {
+        self.begin_cycle();
+
+        // A jammed CPU ignores interrupts and just holds $FFFF on the bus
+        if self.jammed {
+            self.bus.read(0xFFFF);
+            return;
+        }
+
+        // DMA takes over the bus between instructions, stalling the CPU
+        if self.step_cycle == 0 {
+            if self.oam_dma.is_none() {
+                self.oam_dma = self.bus.take_oam_dma().map(|page| OamDma {
+                    page,
+                    cycle: 0,
+                    len: 1 + (self.cycles & 1) as u16 + OAM_DMA_TRANSFER_CYCLES,
+                });
+            }
+            if self.oam_dma.is_some() {
+                // The halted CPU keeps polling, so the boundary after the DMA
+                // sees whatever was raised during it
+                self.oam_dma_cycle();
+                self.sample_interrupts();
+                return;
+            }
+        }
+
+        let done = if self.step_cycle == 0 {
+            self.fetch_opcode();
+            false
+        } else {
+            let op = OPCODES[self.opcode as usize];
+            match op.mnemonic {
+                Mnemonic::Brk => self.interrupt_cycle(),
+                Mnemonic::Jsr => self.jsr_cycle(),
+                Mnemonic::Rts => self.rts_cycle(),
+                Mnemonic::Rti => self.rti_cycle(),
+                Mnemonic::Pha | Mnemonic::Php => self.push_cycle(op.mnemonic),
+                Mnemonic::Pla | Mnemonic::Plp => self.pull_cycle(op.mnemonic),
+                Mnemonic::Jmp => self.jmp_cycle(op.mode),
+                Mnemonic::Bpl | Mnemonic::Bmi | Mnemonic::Bvc | Mnemonic::Bvs
+                | Mnemonic::Bcc | Mnemonic::Bcs | Mnemonic::Bne | Mnemonic::Beq => self.branch_cycle(op.mnemonic),
+                _ if matches!(op.mode, AddressingMode::Implied | AddressingMode::Accumulator) => {
+                    // Single-byte opcodes still read the following byte
+                    self.read(self.pc);
+                    self.implied(op.mnemonic);
+                    true
+                }
+                _ => self.memory_cycle(op),
+            }
+        };
+
+        if done {
+            self.step_cycle = 0;
+        } else {
+            self.step_cycle += 1;
+            self.sample_interrupts();
+        }
+    }
+
+    // One cycle of a sprite DMA: 513 cycles when it starts on an even cycle,
+    // 514 on an odd one
+    fn oam_dma_cycle(&mut self) {
+        let Some(dma) = &mut self.oam_dma else { return };
+        let cycle = dma.cycle;
+        let page = dma.page;
+        let transfer_start = dma.len - OAM_DMA_TRANSFER_CYCLES;
+        dma.cycle += 1;
+        if dma.cycle == dma.len {
+            self.oam_dma = None;
+        }
+
+        if cycle < transfer_start {
+            // Halt and alignment cycles repeat the pending opcode read
+            self.bus.read(self.pc);
+        } else {
+            let offset = cycle - transfer_start;
+            if offset & 1 == 0 {
+                // The CPU is already halted, so a DMC fetch only steals this
+                // read cycle plus one to realign
+                if let Some(sample_addr) = self.bus.take_dmc_dma() {
+                    let sample = self.bus.read(sample_addr);
+                    self.bus.dmc_dma_complete(sample);
+                    self.begin_cycle();
+                    self.bus.read(self.pc);
+                    self.begin_cycle();
+                }
+                self.value = self.bus.read(((page as u16) << 8) | (offset >> 1));
+            } else {
+                self.bus.write(OAM_DATA, self.value);
+            }
+        }
+    }
+
+    // Count a cycle and let the bus catch the rest of the system up to it
+    fn begin_cycle(&mut self) {
+        self.cycles += 1;
+        self.bus.tick();
+        if self.bus.take_nmi() {
+            self.nmi_pending = true;
+        }
+    }
 
-        // Handle interrupts
-        if self.nmi_pending {
+    // Latch the interrupt lines at the end of a cycle that isn't an
+    // instruction's last, so the boundary sees the penultimate cycle's state
+    fn sample_interrupts(&mut self) {
+        self.prev_nmi_sample = self.nmi_sample;
+        self.prev_irq_sample = self.irq_sample;
+        self.nmi_sample = self.nmi_pending;
+        let asserted = !self.irq_line.is_empty() || self.bus.irq_asserted();
+        self.irq_sample = asserted && !self.get_flag(INTERRUPT_DISABLE);
+    }
+
+    // Cycle 0: act on the interrupt poll from the previous instruction's
+    // penultimate cycle, then fetch the opcode. A pending interrupt replaces
+    // the opcode with BRK's sequence without advancing PC.
+    fn fetch_opcode(&mut self) {
+        self.access_cycle = 0;
+        self.interrupt = if self.nmi_sample {
             self.nmi_pending = false;
-            return self.handle_interrupt(InterruptType::Nmi);
+            Some(InterruptType::Nmi)
+        } else if self.irq_sample {
+            Some(InterruptType::Irq)
+        } else {
+            None
+        };
+        self.nmi_sample = false;
+        self.irq_sample = false;
+
+        if self.interrupt.is_some() {
+            self.read(self.pc);
+            self.opcode = 0x00;
+        } else {
+            self.opcode = self.fetch();
+        }
+    }
+
+    // BRK, NMI and IRQ: push PC and P, then load the vector (7 cycles)
+    fn interrupt_cycle(&mut self) -> bool {
+        match self.step_cycle {
+            1 => {
+                // BRK skips its padding byte; hardware interrupts re-read it
+                // without advancing PC
+                if self.interrupt.is_none() {
+                    self.fetch();
+                } else {
+                    self.read(self.pc);
+                }
+            }
+            2 => self.push((self.pc >> 8) as u8),
+            3 => self.push(self.pc as u8),
+            4 => {
+                // B is only set in the pushed copy, and only for BRK
+                let kind = self.interrupt.unwrap_or(InterruptType::Brk);
+                let mut status = (self.status & !BREAK) | 0x20;
+                if kind == InterruptType::Brk {
+                    status |= BREAK;
+                }
+                self.push(status);
+                self.set_flag(INTERRUPT_DISABLE, true);
+                // An NMI seen by now hijacks a BRK or IRQ sequence: the vector
+                // comes from $FFFA and the NMI is consumed
+                let hijacked = kind != InterruptType::Nmi && self.nmi_sample;
+                if hijacked {
+                    self.nmi_pending = false;
+                }
+                self.addr = if kind == InterruptType::Nmi || hijacked { 0xFFFA } else { 0xFFFE };
+            }
+            5 => self.pc = self.read(self.addr) as u16,
+            _ => {
+                self.pc |= (self.read(self.addr.wrapping_add(1)) as u16) << 8;
+                self.interrupt = None;
+                // The sequence doesn't poll, so the handler's first instruction
+                // always runs before another interrupt
+                self.nmi_sample = false;
+                self.irq_sample = false;
+                self.debugger.enter_call();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn jsr_cycle(&mut self) -> bool {
+        match self.step_cycle {
+            1 => self.addr = self.fetch() as u16,
+            // Internal operation: the stack is read while the operand low byte is held
+            2 => {
+                self.read(0x0100 | self.sp as u16);
+            }
+            // The return address pushed is the last byte of the JSR
+            3 => self.push((self.pc >> 8) as u8),
+            4 => self.push(self.pc as u8),
+            _ => {
+                self.pc = ((self.read(self.pc) as u16) << 8) | self.addr;
+                self.debugger.enter_call();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn rts_cycle(&mut self) -> bool {
+        match self.step_cycle {
+            1 | 2 => self.dummy_stack_cycle(),
+            3 => self.addr = self.pop() as u16,
+            4 => self.addr |= (self.pop() as u16) << 8,
+            _ => {
+                // Reads the JSR's last byte while incrementing past it
+                self.pc = self.addr;
+                self.fetch();
+                self.debugger.leave_call();
+                return true;
+            }
+        }
+        false
+    }
+
+    // Cycles 1-2 of stack-pulling instructions: a read of the next byte,
+    // then of the stack slot before the pointer is incremented
+    fn dummy_stack_cycle(&mut self) {
+        if self.step_cycle == 1 {
+            self.read(self.pc);
+        } else {
+            self.read(0x0100 | self.sp as u16);
+        }
+    }
+
+    fn rti_cycle(&mut self) -> bool {
+        match self.step_cycle {
+            1 | 2 => self.dummy_stack_cycle(),
+            3 => {
+                let value = self.pop();
+                self.plp(value);
+            }
+            4 => self.addr = self.pop() as u16,
+            _ => {
+                self.pc = ((self.pop() as u16) << 8) | self.addr;
+                self.debugger.leave_call();
+                return true;
+            }
+        }
+        false
+    }
+
+    fn push_cycle(&mut self, mnemonic: Mnemonic) -> bool {
+        if self.step_cycle == 1 {
+            self.read(self.pc);
+            return false;
+        }
+        match mnemonic {
+            // B and bit 5 are set in the pushed copy only
+            Mnemonic::Php => self.push(self.status | BREAK | 0x20),
+            _ => self.push(self.a),
+        }
+        true
+    }
+
+    fn pull_cycle(&mut self, mnemonic: Mnemonic) -> bool {
+        if self.step_cycle < 3 {
+            self.dummy_stack_cycle();
+            return false;
+        }
+        let value = self.pop();
+        match mnemonic {
+            Mnemonic::Plp => self.plp(value),
+            _ => {
+                self.a = value;
+                self.set_zn(self.a);
+            }
+        }
+        true
+    }
+
+    fn jmp_cycle(&mut self, mode: AddressingMode) -> bool {
+        match (mode, self.step_cycle) {
+            (_, 1) => self.addr = self.fetch() as u16,
+            (AddressingMode::Absolute, _) => {
+                self.pc = ((self.read(self.pc) as u16) << 8) | self.addr;
+                return true;
+            }
+            (_, 2) => self.addr |= (self.fetch() as u16) << 8,
+            (_, 3) => self.value = self.read(self.addr),
+            _ => {
+                // The pointer's high byte is fetched without carrying into the page
+                let hi_addr = (self.addr & 0xFF00) | (self.addr.wrapping_add(1) & 0x00FF);
+                self.pc = ((self.read(hi_addr) as u16) << 8) | self.value as u16;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Branches take 2 cycles, 3 if taken, 4 if taken across a page
+    fn branch_cycle(&mut self, mnemonic: Mnemonic) -> bool {
+        match self.step_cycle {
+            1 => {
+                let offset = self.fetch() as i8;
+                let taken = match mnemonic {
+                    Mnemonic::Bpl => !self.get_flag(NEGATIVE),
+                    Mnemonic::Bmi => self.get_flag(NEGATIVE),
+                    Mnemonic::Bvc => !self.get_flag(OVERFLOW),
+                    Mnemonic::Bvs => self.get_flag(OVERFLOW),
+                    Mnemonic::Bcc => !self.get_flag(CARRY),
+                    Mnemonic::Bcs => self.get_flag(CARRY),
+                    Mnemonic::Bne => !self.get_flag(ZERO),
+                    _ => self.get_flag(ZERO),
+                };
+                self.addr = self.pc.wrapping_add(offset as u16);
+                !taken
+            }
+            2 => {
+                // Only the low byte is added on this cycle, while the byte
+                // after the branch is read
+                self.read(self.pc);
+                self.pc = (self.pc & 0xFF00) | (self.addr & 0x00FF);
+                if self.pc != self.addr {
+                    return false;
+                }
+                // A taken branch that stays in the page doesn't poll on its
+                // operand cycle, delaying interrupts raised there by one instruction
+                if self.nmi_sample && !self.prev_nmi_sample {
+                    self.nmi_sample = false;
+                }
+                if self.irq_sample && !self.prev_irq_sample {
+                    self.irq_sample = false;
+                }
+                true
+            }
+            _ => {
+                // Read at the un-fixed target while the high byte is corrected
+                self.read(self.pc);
+                self.pc = self.addr;
+                true
+            }
+        }
+    }
+
+    // Addressing cycles, then the operand access once `addr` is final
+    fn memory_cycle(&mut self, op: Opcode) -> bool {
+        let access = access_kind(op.mnemonic);
+
+        if op.mode == AddressingMode::Immediate {
+            self.addr = self.pc;
+            self.pc = self.pc.wrapping_add(1);
+            return self.access(op.mnemonic, access, 0);
         }
+        if self.access_cycle != 0 {
+            return self.access(op.mnemonic, access, self.step_cycle - self.access_cycle);
+        }
+
+        let ready = match (op.mode, self.step_cycle) {
+            (_, 1) => {
+                self.pointer = self.fetch();
+                self.addr = self.pointer as u16;
+                op.mode == AddressingMode::ZeroPage
+            }
+            // Zero-page indexing reads the unindexed address while adding
+            (AddressingMode::ZeroPageX, _) => {
+                self.read(self.pointer as u16);
+                self.addr = self.pointer.wrapping_add(self.x) as u16;
+                true
+            }
+            (AddressingMode::ZeroPageY, _) => {
+                self.read(self.pointer as u16);
+                self.addr = self.pointer.wrapping_add(self.y) as u16;
+                true
+            }
+            (AddressingMode::Absolute, _) => {
+                self.addr |= (self.fetch() as u16) << 8;
+                true
+            }
+            (AddressingMode::AbsoluteX | AddressingMode::AbsoluteY, 2) => {
+                let index = if op.mode == AddressingMode::AbsoluteX { self.x } else { self.y };
+                let base = self.addr | (self.fetch() as u16) << 8;
+                self.index_address(base, index, access)
+            }
+            (AddressingMode::IndirectX, 2) => {
+                self.read(self.pointer as u16);
+                self.pointer = self.pointer.wrapping_add(self.x);
+                false
+            }
+            (AddressingMode::IndirectX, 3) => {
+                self.addr = self.read(self.pointer as u16) as u16;
+                false
+            }
+            (AddressingMode::IndirectX, _) => {
+                self.addr |= (self.read(self.pointer.wrapping_add(1) as u16) as u16) << 8;
+                true
+            }
+            (AddressingMode::IndirectY, 2) => {
+                self.addr = self.read(self.pointer as u16) as u16;
+                false
+            }
+            (AddressingMode::IndirectY, 3) => {
+                let base = self.addr | (self.read(self.pointer.wrapping_add(1) as u16) as u16) << 8;
+                self.index_address(base, self.y, access)
+            }
+            // Fix-up cycle for indexed modes: the CPU reads from the address
+            // with the high byte not yet carried, which is the real address
+            // when no page was crossed
+            _ => {
+                let unfixed = if self.crossed { self.addr.wrapping_sub(0x100) } else { self.addr };
+                self.read(unfixed);
+                true
+            }
+        };
 
-        if self.irq_pending && !self.get_flag(INTERRUPT_DISABLE) {
-            self.irq_pending = false;
-            return self.handle_interrupt(InterruptType::Irq);
+        if ready {
+            self.access_cycle = self.step_cycle + 1;
         }
+        false
+    }
 
-        // Fetch and execute instruction
-        let opcode = self.bus.read(self.pc);
-        self.pc += 1;
+    // Indexed address; reads that stay in the page skip the fix-up cycle
+    fn index_address(&mut self, base: u16, index: u8, access: Access) -> bool {
+        self.addr = base.wrapping_add(index as u16);
+        self.crossed = (base & 0xFF00) != (self.addr & 0xFF00);
+        access == Access::Read && !self.crossed
+    }
 
-        match opcode {
-            // LDA Immediate
-            0xA9 => {
-                let value = self.imm();
-                self.lda(value);
-                cycles = 2;
+    fn access(&mut self, mnemonic: Mnemonic, access: Access, cycle: u8) -> bool {
+        match (access, cycle) {
+            (Access::Read, _) => {
+                let value = self.read(self.addr);
+                self.read_op(mnemonic, value);
+                true
+            }
+            (Access::Write, _) => {
+                self.write_op(mnemonic);
+                true
+            }
+            (Access::ReadModifyWrite, 0) => {
+                self.value = self.read(self.addr);
+                false
             }
-            
-            // STA Absolute
-            0x8D => {
-                let addr = self.abs();
-                self.sta(addr);
-                cycles = 4;
+            // The unmodified value is written back while the ALU works
+            (Access::ReadModifyWrite, 1) => {
+                self.write(self.addr, self.value);
+                self.value = self.modify(mnemonic, self.value);
+                false
             }
-            
-            // TAX
-            0xAA => {
-                self.tax();
-                cycles = 2;
+            (Access::ReadModifyWrite, _) => {
+                self.write(self.addr, self.value);
+                self.after_modify(mnemonic, self.value);
+                true
             }
-            
-            // BRK
-            0x00 => {
-                self.pc += 1;
-                cycles = self.handle_interrupt(InterruptType::Brk) + 1;
+        }
+    }
+
+    fn read_op(&mut self, mnemonic: Mnemonic, value: u8) {
+        use Mnemonic::*;
+
+        match mnemonic {
+            Lda => self.lda(value),
+            Ldx => self.ldx(value),
+            Ldy => self.ldy(value),
+            Adc => self.adc(value),
+            Sbc => self.sbc(value),
+            And => self.and(value),
+            Ora => self.ora(value),
+            Eor => self.eor(value),
+            Bit => self.bit(value),
+            Cmp => self.compare(self.a, value),
+            Cpx => self.compare(self.x, value),
+            Cpy => self.compare(self.y, value),
+            Lax => self.lax(value),
+            Las => {
+                let value = value & self.sp;
+                self.sp = value;
+                self.lax(value);
+            }
+            Anc => self.anc(value),
+            Alr => self.alr(value),
+            Arr => self.arr(value),
+            Axs => self.axs(value),
+            // Unstable: A is ORed with a chip-dependent constant
+            Lxa => self.lax((self.a | self.unstable.lxa_magic) & value),
+            Ane => self.lda((self.a | self.unstable.ane_magic) & self.x & value),
+            // Unofficial NOPs still read their operand
+            _ => {}
+        }
+    }
+
+    fn write_op(&mut self, mnemonic: Mnemonic) {
+        use Mnemonic::*;
+
+        match mnemonic {
+            Sta => self.write(self.addr, self.a),
+            Stx => self.write(self.addr, self.x),
+            Sty => self.write(self.addr, self.y),
+            Sax => self.write(self.addr, self.a & self.x),
+            Shy => self.store_high_and(self.addr, self.crossed, self.y),
+            Shx => self.store_high_and(self.addr, self.crossed, self.x),
+            Sha => self.store_high_and(self.addr, self.crossed, self.a & self.x),
+            _ => {
+                self.sp = self.a & self.x;
+                self.store_high_and(self.addr, self.crossed, self.sp);
             }
-            
-            // Unimplemented opcode handler
-            _ => panic!("Unimplemented opcode: {:#04X}", opcode),
         }
+    }
+
+    // Shift/increment step shared by the plain and combined RMW opcodes
+    fn modify(&mut self, mnemonic: Mnemonic, value: u8) -> u8 {
+        use Mnemonic::*;
+
+        match mnemonic {
+            Asl | Slo => self.asl(value),
+            Lsr | Sre => self.lsr(value),
+            Rol | Rla => self.rol(value),
+            Ror | Rra => self.ror(value),
+            Inc | Isc => self.inc(value),
+            _ => self.dec(value),
+        }
+    }
+
+    // Second half of the undocumented combined RMW opcodes
+    fn after_modify(&mut self, mnemonic: Mnemonic, result: u8) {
+        use Mnemonic::*;
+
+        match mnemonic {
+            Slo => self.ora(result),
+            Rla => self.and(result),
+            Sre => self.eor(result),
+            Rra => self.adc(result),
+            Dcp => self.compare(self.a, result),
+            Isc => self.sbc(result),
+            _ => {}
+        }
+    }
+
+    // Single-byte instructions, executed on their second cycle
+    fn implied(&mut self, mnemonic: Mnemonic) {
+        use Mnemonic::*;
+
+        match mnemonic {
+            Tax => self.tax(),
+            Tay => self.tay(),
+            Tsx => self.tsx(),
+            Txa => self.txa(),
+            Txs => self.sp = self.x,
+            Tya => self.tya(),
+            Inx => self.x = self.inc(self.x),
+            Iny => self.y = self.inc(self.y),
+            Dex => self.x = self.dec(self.x),
+            Dey => self.y = self.dec(self.y),
+            Clc => self.set_flag(CARRY, false),
+            Sec => self.set_flag(CARRY, true),
+            Cli => self.set_flag(INTERRUPT_DISABLE, false),
+            Sei => self.set_flag(INTERRUPT_DISABLE, true),
+            Cld => self.set_flag(DECIMAL, false),
+            Sed => self.set_flag(DECIMAL, true),
+            Clv => self.set_flag(OVERFLOW, false),
+            Asl | Lsr | Rol | Ror => self.a = self.modify(mnemonic, self.a),
+            Nop => {}
+            // Locks up the real CPU until reset
+            _ => self.jammed = true,
+        }
+    }
+}
+
+fn access_kind(mnemonic: Mnemonic) -> Access {
+    use Mnemonic::*;
 
-        cycles
+    match mnemonic {
+        Sta | Stx | Sty | Sax | Shy | Shx | Sha | Tas => Access::Write,
+        Asl | Lsr | Rol | Ror | Inc | Dec | Slo | Rla | Sre | Rra | Dcp | Isc => Access::ReadModifyWrite,
+        _ => Access::Read,
     }
 }
@@ -0,0 +1,107 @@
+// src/nes/cpu/trace.rs
+// Per-instruction trace lines in the nestest.log format, for diffing against golden logs
+
+use super::opcodes::{AddressingMode, Mnemonic, OPCODES};
+use super::ricoh_2a03_cpu::{Bus, Cpu2A03};
+
+/// Trace line for the instruction about to execute at PC, e.g.
+/// `C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7`.
+/// Call it at an instruction boundary; memory is inspected with `Bus::peek`.
+/// `ppu` is the (scanline, dot) position of the PPU.
+pub fn trace_line<B: Bus>(cpu: &mut Cpu2A03<B>, ppu: (i16, usize)) -> String {
+    let pc = cpu.pc;
+    let opcode = cpu.bus.peek(pc);
+    let op = OPCODES[opcode as usize];
+    let operands: Vec<u8> = (1..=op.mode.operand_len())
+        .map(|offset| cpu.bus.peek(pc.wrapping_add(offset)))
+        .collect();
+
+    let bytes = std::iter::once(opcode)
+        .chain(operands.iter().copied())
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let marker = if op.official { ' ' } else { '*' };
+    let disassembly = format!("{} {}", mnemonic_name(op.mnemonic), operand_text(cpu, op.mnemonic, op.mode, &operands));
+
+    format!(
+        "{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        pc,
+        bytes,
+        marker,
+        disassembly.trim_end(),
+        cpu.a,
+        cpu.x,
+        cpu.y,
+        cpu.status,
+        cpu.sp,
+        ppu.0,
+        ppu.1,
+        cpu.cycles(),
+    )
+}
+
+// nestest spells ISC as ISB; everything else is the upper-cased variant name
+fn mnemonic_name(mnemonic: Mnemonic) -> String {
+    match mnemonic {
+        Mnemonic::Isc => "ISB".to_string(),
+        _ => format!("{:?}", mnemonic).to_uppercase(),
+    }
+}
+
+// Operand as nestest prints it, including the effective address and the
+// value currently stored there
+fn operand_text<B: Bus>(cpu: &mut Cpu2A03<B>, mnemonic: Mnemonic, mode: AddressingMode, operands: &[u8]) -> String {
+    let lo = operands.first().copied().unwrap_or(0);
+    let hi = operands.get(1).copied().unwrap_or(0);
+    let word = ((hi as u16) << 8) | lo as u16;
+
+    match mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${:02X}", lo),
+        AddressingMode::ZeroPage => format!("${:02X} = {:02X}", lo, cpu.bus.peek(lo as u16)),
+        AddressingMode::ZeroPageX | AddressingMode::ZeroPageY => {
+            let (index, name) = if mode == AddressingMode::ZeroPageX { (cpu.x, 'X') } else { (cpu.y, 'Y') };
+            let addr = lo.wrapping_add(index);
+            format!("${:02X},{} @ {:02X} = {:02X}", lo, name, addr, cpu.bus.peek(addr as u16))
+        }
+        AddressingMode::Relative => {
+            let target = cpu.pc.wrapping_add(2).wrapping_add(lo as i8 as u16);
+            format!("${:04X}", target)
+        }
+        AddressingMode::Absolute => {
+            if matches!(mnemonic, Mnemonic::Jmp | Mnemonic::Jsr) {
+                format!("${:04X}", word)
+            } else {
+                format!("${:04X} = {:02X}", word, cpu.bus.peek(word))
+            }
+        }
+        AddressingMode::AbsoluteX | AddressingMode::AbsoluteY => {
+            let (index, name) = if mode == AddressingMode::AbsoluteX { (cpu.x, 'X') } else { (cpu.y, 'Y') };
+            let addr = word.wrapping_add(index as u16);
+            format!("${:04X},{} @ {:04X} = {:02X}", word, name, addr, cpu.bus.peek(addr))
+        }
+        AddressingMode::Indirect => {
+            // JMP ($xxFF) wraps within the page
+            let target = peek_word(cpu, word, |addr| (addr & 0xFF00) | (addr.wrapping_add(1) & 0x00FF));
+            format!("(${:04X}) = {:04X}", word, target)
+        }
+        AddressingMode::IndirectX => {
+            let pointer = lo.wrapping_add(cpu.x);
+            let addr = peek_word(cpu, pointer as u16, |addr| (addr + 1) & 0x00FF);
+            format!("(${:02X},X) @ {:02X} = {:04X} = {:02X}", lo, pointer, addr, cpu.bus.peek(addr))
+        }
+        AddressingMode::IndirectY => {
+            let base = peek_word(cpu, lo as u16, |addr| (addr + 1) & 0x00FF);
+            let addr = base.wrapping_add(cpu.y as u16);
+            format!("(${:02X}),Y = {:04X} @ {:04X} = {:02X}", lo, base, addr, cpu.bus.peek(addr))
+        }
+    }
+}
+
+fn peek_word<B: Bus>(cpu: &mut Cpu2A03<B>, addr: u16, high_addr: impl Fn(u16) -> u16) -> u16 {
+    let lo = cpu.bus.peek(addr) as u16;
+    let hi = cpu.bus.peek(high_addr(addr)) as u16;
+    (hi << 8) | lo
+}
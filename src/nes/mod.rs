@@ -1,39 +1,129 @@
 // src/nes/mod.rs
+pub mod apu;
+pub mod bus;
+pub mod cart;
+pub mod clock;
+pub mod controller;
 pub mod cpu;
+pub mod nsf;
 pub mod ppu;
+pub mod vs;
 
+use bus::NesBus;
+use ppu::{FrameBuffer, PixelFormat};
+use cart::{ConsoleType, Rom, RomError};
+use clock::{MasterClock, Region};
+use controller::InputState;
+use vs::VsSystem;
+
+/// The console. The bus clocks everything besides the CPU at the start of
+/// each CPU cycle, so `step` runs the whole system in lockstep.
 pub struct Nes {
-    pub cpu: cpu::Cpu2A03<Bus>,
-    pub ppu: ppu::Ppu,
-    pub cycles: usize,
+    pub cpu: cpu::Cpu2A03<NesBus>,
+    pub frame: u64,
+    frame_callback: Option<(PixelFormat, FrameCallback)>,
 }
 
+/// Called with each finished frame; see `Nes::on_frame`
+pub type FrameCallback = Box<dyn FnMut(FrameBuffer<'_>)>;
+
 impl Nes {
-    pub fn new(rom: Rom) -> Self {
-        let bus = Bus::new(rom);
-        let ppu = ppu::Ppu::new(rom.mirroring);
-        Self {
-            cpu: cpu::Cpu2A03::new(bus),
-            ppu,
-            cycles: 0,
+    /// Console with `rom` inserted, powered on and reset. The timing
+    /// follows the region in the ROM header.
+    pub fn new(rom: Rom) -> Result<Self, RomError> {
+        let region = rom.region;
+        let vs = match rom.console_type {
+            ConsoleType::VsSystem(ppu) => Some(VsSystem::new(ppu)),
+            _ => None,
+        };
+        let cart = cart::new_mapper(rom)?;
+        let mut nes = Self {
+            cpu: cpu::Cpu2A03::new(NesBus::new(cart, vs, region)),
+            frame: 0,
+            frame_callback: None,
+        };
+        nes.reset();
+        Ok(nes)
+    }
+
+    pub fn region(&self) -> Region {
+        self.cpu.bus.clock.region()
+    }
+
+    /// Switch to NTSC or PAL timing, overriding the ROM header. Meant for
+    /// setup: the master clock restarts from zero.
+    pub fn set_region(&mut self, region: Region) {
+        self.cpu.bus.clock = MasterClock::new(region);
+        self.cpu.bus.ppu.region = region;
+        self.cpu.bus.apu.set_region(region);
+    }
+
+    /// Buttons held on the controller in `port` (0 or 1), normally set
+    /// once per frame before running it
+    pub fn set_input(&mut self, port: usize, buttons: InputState) {
+        if let Some(controller) = self.cpu.bus.controllers.get_mut(port) {
+            controller.set_buttons(buttons);
         }
     }
 
-    pub fn step(&mut self) {
-        let cpu_cycles = self.cpu.step();
-        self.cycles += cpu_cycles as usize;
-        
-        for _ in 0..cpu_cycles * 3 {
-            if self.ppu.step() {
-                // Handle frame completion
+    /// Run one CPU instruction. Returns the CPU cycles taken and whether a
+    /// frame completed during them.
+    pub fn step(&mut self) -> Result<(usize, bool), cpu::CpuError> {
+        let cycles = self.cpu.step()?;
+        Ok((cycles, self.check_frame()))
+    }
+
+    /// Press reset: the PPU's registers clear and it starts warming up, the
+    /// APU goes quiet, and the rest of the system runs through the CPU's
+    /// reset cycles
+    pub fn reset(&mut self) {
+        self.cpu.bus.ppu.reset();
+        self.cpu.bus.apu.reset();
+        self.cpu.reset();
+        self.check_frame();
+    }
+
+    /// Resample audio to `rate` Hz; see `Apu::set_sample_rate`
+    pub fn set_sample_rate(&mut self, rate: u32) {
+        self.cpu.bus.apu.set_sample_rate(rate);
+    }
+
+    /// Nudge the audio output rate; see `Apu::set_rate_adjust`
+    pub fn set_rate_adjust(&mut self, adjust: f64) {
+        self.cpu.bus.apu.set_rate_adjust(adjust);
+    }
+
+    /// Append the audio produced since the last call to `out`, as samples
+    /// at the configured rate: mono, or left/right pairs with stereo on
+    pub fn take_samples(&mut self, out: &mut Vec<f32>) {
+        self.cpu.bus.apu.take_samples(out);
+    }
+
+    /// The last finished frame in `format`
+    pub fn frame(&mut self, format: PixelFormat) -> FrameBuffer<'_> {
+        self.cpu.bus.ppu.frame(format)
+    }
+
+    /// Hand every finished frame to `callback` in `format` as `step` sees
+    /// it complete, replacing any earlier callback
+    pub fn on_frame(&mut self, format: PixelFormat, callback: impl FnMut(FrameBuffer<'_>) + 'static) {
+        self.frame_callback = Some((format, Box::new(callback)));
+    }
+
+    /// Run until the PPU finishes the current frame
+    pub fn run_frame(&mut self) -> Result<(), cpu::CpuError> {
+        while !self.step()?.1 {}
+        Ok(())
+    }
+
+    fn check_frame(&mut self) -> bool {
+        let frame_complete = self.cpu.bus.take_frame_complete();
+        if frame_complete {
+            self.frame += 1;
+            if let Some((format, callback)) = &mut self.frame_callback {
+                callback(self.cpu.bus.ppu.frame(*format));
             }
         }
-        
-        if self.ppu.nmi_occurred {
-            self.cpu.trigger_nmi();
-            self.ppu.nmi_occurred = false;
-        }
+        frame_complete
     }
 }
-// pub mod apu;
-// pub mod cart;
@@ -0,0 +1,216 @@
+// src/nes/nsf.rs
+// Headless NSF player: drives the CPU through INIT/PLAY calls without a PPU
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::nes::apu::Apu;
+use crate::nes::cart::{Mapper, Nsf, NsfHeader, NsfMapper};
+use crate::nes::clock::Region;
+use crate::nes::cpu::{Bus, Cpu2A03};
+
+const RAM_SIZE: usize = 2048;
+
+// INIT/PLAY are entered with this address (minus one) on the stack so their
+// final RTS lands somewhere the player can recognise and stop at.
+const RETURN_SENTINEL: u16 = 0x4F00;
+
+/// Audio-only system bus: work RAM, APU/I/O registers, and the NSF banks
+pub struct NsfBus {
+    ram: [u8; RAM_SIZE],
+    mapper: Rc<RefCell<NsfMapper>>,
+    pub apu: Apu,
+    open_bus: u8,
+}
+
+impl NsfBus {
+    fn new(mapper: NsfMapper, region: Region) -> Self {
+        Self {
+            ram: [0; RAM_SIZE],
+            mapper: Rc::new(RefCell::new(mapper)),
+            apu: Apu::new(region),
+            open_bus: 0,
+        }
+    }
+
+    // Cycles spent outside the CPU, waiting for the next PLAY call
+    fn handle_apu(&mut self, cycles: usize) {
+        for _ in 0..cycles {
+            self.tick();
+        }
+    }
+}
+
+impl Bus for NsfBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let data = match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize % RAM_SIZE],
+            0x4015 => self.apu.read_status(),
+            0x4000..=0x4017 => 0,
+            0x4020..=0xFFFF => self.mapper.borrow_mut().cpu_read(addr).unwrap_or(self.open_bus),
+            _ => 0,
+        };
+        self.open_bus = data;
+        data
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.open_bus = data;
+        match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize % RAM_SIZE] = data,
+            0x4000..=0x4013 | 0x4015 | 0x4017 => self.apu.write_register(addr, data),
+            0x4020..=0xFFFF => self.mapper.borrow_mut().cpu_write(addr, data),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self) {
+        self.mapper.borrow_mut().clock_expansion(&mut self.apu);
+        self.apu.clock();
+    }
+}
+
+pub struct NsfPlayer {
+    pub cpu: Cpu2A03<NsfBus>,
+    header: NsfHeader,
+    current_song: u8,
+    pal: bool,
+}
+
+impl NsfPlayer {
+    pub fn new(nsf: Nsf) -> Self {
+        let header = nsf.header.clone();
+        let pal = header.pal && !header.dual_region;
+        let region = if pal { Region::Pal } else { Region::Ntsc };
+        let mut player = Self {
+            cpu: Cpu2A03::new(NsfBus::new(NsfMapper::new(nsf), region)),
+            current_song: header.starting_song,
+            header,
+            pal,
+        };
+        player.start_song(player.current_song);
+        player
+    }
+
+    pub fn header(&self) -> &NsfHeader {
+        &self.header
+    }
+
+    /// 1-based index of the song currently playing
+    pub fn current_song(&self) -> u8 {
+        self.current_song
+    }
+
+    pub fn next_track(&mut self) {
+        let song = if self.current_song >= self.header.total_songs { 1 } else { self.current_song + 1 };
+        self.start_song(song);
+    }
+
+    pub fn previous_track(&mut self) {
+        let song = if self.current_song <= 1 { self.header.total_songs } else { self.current_song - 1 };
+        self.start_song(song);
+    }
+
+    /// Reset the sound hardware and run INIT for a 1-based song number. A
+    /// CPU jammed by the last song is reset first.
+    pub fn start_song(&mut self, song: u8) {
+        self.current_song = song.clamp(1, self.header.total_songs);
+        if self.cpu.is_jammed() {
+            self.cpu.reset();
+        }
+
+        let bus = &mut self.cpu.bus;
+        for addr in 0x0000..0x0800 {
+            bus.write(addr, 0);
+        }
+        for addr in 0x6000..0x8000 {
+            bus.write(addr, 0);
+        }
+        for addr in 0x4000..0x4014 {
+            bus.write(addr, 0);
+        }
+        bus.write(0x4015, 0x00);
+        bus.write(0x4015, 0x0F);
+        bus.write(0x4017, 0x40);
+
+        bus.mapper.borrow_mut().reset_expansion();
+        let banks = bus.mapper.borrow().initial_banks();
+        for (i, bank) in banks.into_iter().enumerate() {
+            bus.write(0x5FF8 + i as u16, bank);
+        }
+
+        self.cpu.a = self.current_song - 1;
+        self.cpu.x = self.pal as u8;
+        self.cpu.y = 0;
+        self.cpu.sp = 0xFD;
+        self.cpu.status = 0x24;
+
+        // INIT may take a while (decompression etc.), allow up to a second
+        let init_addr = self.header.init_addr;
+        self.call(init_addr, self.cpu_hz() as usize);
+    }
+
+    /// Run one PLAY call and idle the rest of the period. Returns CPU cycles
+    /// elapsed. Once the driver has jammed the CPU there's no PLAY to call,
+    /// so the APU just runs on until the next `start_song`.
+    pub fn play_frame(&mut self) -> usize {
+        let period = self.frame_cycles();
+        let used = if self.cpu.is_jammed() { 0 } else { self.call(self.header.play_addr, period) };
+
+        self.cpu.bus.handle_apu(period.saturating_sub(used));
+        period
+    }
+
+    /// Append the audio produced since the last call to `out`
+    pub fn take_samples(&mut self, out: &mut Vec<f32>) {
+        self.cpu.bus.apu.take_samples(out);
+    }
+
+    /// Real-time interval between PLAY calls
+    pub fn frame_period(&self) -> Duration {
+        let speed = if self.pal { self.header.pal_speed } else { self.header.ntsc_speed };
+        let default = if self.pal { 20_000 } else { 16_639 };
+        Duration::from_micros(if speed == 0 { default } else { speed as u64 })
+    }
+
+    fn cpu_hz(&self) -> u64 {
+        let region = if self.pal { Region::Pal } else { Region::Ntsc };
+        region.cpu_hz()
+    }
+
+    fn frame_cycles(&self) -> usize {
+        (self.frame_period().as_micros() as u64 * self.cpu_hz() / 1_000_000) as usize
+    }
+
+    // JSR-style call into a driver routine; runs until it returns or the cycle budget is spent
+    fn call(&mut self, addr: u16, max_cycles: usize) -> usize {
+        let ret = RETURN_SENTINEL.wrapping_sub(1);
+        let sp = self.cpu.sp;
+        self.cpu.bus.write(0x0100 | sp as u16, (ret >> 8) as u8);
+        self.cpu.bus.write(0x0100 | sp.wrapping_sub(1) as u16, ret as u8);
+        self.cpu.sp = sp.wrapping_sub(2);
+        self.cpu.pc = addr;
+
+        let mut cycles = 0;
+        while self.cpu.pc != RETURN_SENTINEL {
+            if cycles >= max_cycles {
+                warn!("NSF routine at {:04X} did not return within {} cycles", addr, max_cycles);
+                self.cpu.sp = sp;
+                break;
+            }
+            let step = match self.cpu.step() {
+                Ok(step) => step,
+                Err(err) => {
+                    warn!("NSF routine at {:04X} stopped: {}", addr, err);
+                    self.cpu.sp = sp;
+                    break;
+                }
+            };
+            cycles += step;
+        }
+        cycles
+    }
+}
@@ -0,0 +1,155 @@
+// src/nes/ppu/debug.rs
+// Debug views of PPU memory for debugger UIs and image dumps
+
+use super::registers::ControlRegister;
+use super::Ppu;
+
+/// The four nametables side by side: $2000 $2400 over $2800 $2C00
+pub const NAMETABLE_VIEW_WIDTH: usize = 512;
+pub const NAMETABLE_VIEW_HEIGHT: usize = 480;
+/// One pattern table as a 16x16 sheet of tiles
+pub const PATTERN_VIEW_SIZE: usize = 128;
+
+/// One OAM entry, decoded, with the sprite drawn as it would appear
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OamEntry {
+    pub index: u8,
+    pub x: u8,
+    pub y: u8,
+    pub tile: u8,
+    pub attributes: u8,
+    /// Drawn on the scanline being rendered; more than eight of these means
+    /// some are dropped
+    pub on_scanline: bool,
+    /// 8 pixels wide, 8 or 16 high with PPUCTRL's sprite size, flipped and
+    /// in the sprite's palette over the backdrop
+    pub thumbnail: Vec<u32>,
+}
+
+impl Ppu {
+    /// All four nametables as `0x00RRGGBB` pixels, drawn with the current
+    /// background pattern table and attribute palettes. The 256x240 window
+    /// t scrolls to is outlined in inverted pixels, wrapping at the edges.
+    pub fn nametable_view(&self) -> Vec<u32> {
+        let mut view = vec![0; NAMETABLE_VIEW_WIDTH * NAMETABLE_VIEW_HEIGHT];
+        let table = (self.registers.control.contains(ControlRegister::BACKGROUND_TABLE) as u16) << 12;
+
+        for nametable in 0..4u16 {
+            let base = 0x2000 + nametable * 0x400;
+            let (left, top) = ((nametable & 1) as usize * 256, (nametable >> 1) as usize * 240);
+            for row in 0..30u16 {
+                for column in 0..32u16 {
+                    let tile = self.memory.peek_vram(base + row * 32 + column) as u16;
+                    let attribute = self.memory.peek_vram(base + 0x3C0 + (row / 4) * 8 + column / 4);
+                    let shift = (row & 0x02) << 1 | (column & 0x02);
+                    let palette = (attribute >> shift) & 0x03;
+                    let origin = (top + row as usize * 8) * NAMETABLE_VIEW_WIDTH + left + column as usize * 8;
+                    self.draw_tile(&mut view[origin..], NAMETABLE_VIEW_WIDTH, table | tile << 4, palette);
+                }
+            }
+        }
+
+        self.outline_scroll(&mut view);
+        view
+    }
+
+    /// Pattern table `table` (0 for $0000, 1 for $1000) as it's banked in
+    /// right now, `PATTERN_VIEW_SIZE` square, colored with `palette` (0-3
+    /// background, 4-7 sprites)
+    pub fn pattern_table_view(&self, table: u8, palette: u8) -> Vec<u32> {
+        let mut view = vec![0; PATTERN_VIEW_SIZE * PATTERN_VIEW_SIZE];
+        let base = ((table & 1) as u16) << 12;
+        for tile in 0..256 {
+            let origin = (tile / 16) * 8 * PATTERN_VIEW_SIZE + (tile % 16) * 8;
+            self.draw_tile(&mut view[origin..], PATTERN_VIEW_SIZE, base | (tile as u16) << 4, palette & 0x07);
+        }
+        view
+    }
+
+    /// All 64 sprites in OAM order
+    pub fn oam_view(&self) -> Vec<OamEntry> {
+        let height = self.sprite_height();
+        self.memory
+            .oam
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(index, entry)| {
+                let (y, tile, attributes, x) = (entry[0], entry[1], entry[2], entry[3]);
+                // Sprites are evaluated a line ahead, so they show up one
+                // line below their Y
+                let row = self.scanline - 1 - y as i16;
+                let on_scanline = (0..240).contains(&self.scanline) && (0..height).contains(&row);
+
+                let mut thumbnail = vec![0; 8 * height as usize];
+                for row in 0..height as u16 {
+                    let source = if attributes & 0x80 != 0 { height as u16 - 1 - row } else { row };
+                    let addr = self.sprite_pattern_addr(tile, source);
+                    let (low, high) = (self.memory.peek_vram(addr), self.memory.peek_vram(addr + 8));
+                    for x in 0..8 {
+                        let bit = if attributes & 0x40 != 0 { x } else { 7 - x };
+                        let pixel = (high >> bit & 1) << 1 | (low >> bit & 1);
+                        thumbnail[row as usize * 8 + x] = self.debug_color(4 + (attributes & 0x03), pixel);
+                    }
+                }
+
+                OamEntry { index: index as u8, x, y, tile, attributes, on_scanline, thumbnail }
+            })
+            .collect()
+    }
+
+    /// The 32 palette RAM entries, $3F00-$3F1F, with $3F10/$3F14/$3F18/$3F1C
+    /// showing the backdrop entries they mirror
+    pub fn palette_ram(&self) -> [u8; 32] {
+        std::array::from_fn(|index| self.memory.peek_vram(0x3F00 + index as u16))
+    }
+
+    /// `palette_ram` as RGB, without the mask's grayscale or emphasis
+    pub fn palette_ram_view(&self) -> [u32; 32] {
+        self.palette_ram().map(|entry| self.palette.color((entry & 0x3F) as u16))
+    }
+
+    /// Set palette RAM entry `index` (0-31) without going through PPUADDR,
+    /// so the running program's v, t and write toggle are left alone
+    pub fn poke_palette(&mut self, index: u8, value: u8) {
+        self.memory.write_vram(0x3F00 + (index & 0x1F) as u16, value);
+    }
+
+    // The 8x8 tile at pattern address `addr` into `view`, whose rows are
+    // `stride` pixels apart
+    fn draw_tile(&self, view: &mut [u32], stride: usize, addr: u16, palette: u8) {
+        for y in 0..8 {
+            let low = self.memory.peek_vram(addr | y);
+            let high = self.memory.peek_vram(addr | y | 8);
+            for x in 0..8 {
+                let pixel = (high >> (7 - x) & 1) << 1 | (low >> (7 - x) & 1);
+                view[y as usize * stride + x] = self.debug_color(palette, pixel);
+            }
+        }
+    }
+
+    // Scroll position held in t and fine X, as the next frame will start
+    fn outline_scroll(&self, view: &mut [u32]) {
+        let t = self.tram_addr as usize;
+        let scroll_x = (t >> 10 & 1) * 256 + (t & 0x1F) * 8 + self.fine_x as usize;
+        let scroll_y = (t >> 11 & 1) * 240 + (t >> 5 & 0x1F) * 8 + (t >> 12 & 0x07);
+        let mut invert = |x: usize, y: usize| {
+            let (x, y) = ((scroll_x + x) % NAMETABLE_VIEW_WIDTH, (scroll_y + y) % NAMETABLE_VIEW_HEIGHT);
+            view[y * NAMETABLE_VIEW_WIDTH + x] ^= 0xFFFFFF;
+        };
+        for x in 0..256 {
+            invert(x, 0);
+            invert(x, 239);
+        }
+        for y in 1..239 {
+            invert(0, y);
+            invert(255, y);
+        }
+    }
+
+    // Palette RAM color for `pixel` of background or sprite `palette`,
+    // without the mask's grayscale or emphasis
+    fn debug_color(&self, palette: u8, pixel: u8) -> u32 {
+        let addr = if pixel == 0 { 0x3F00 } else { 0x3F00 + palette as u16 * 4 + pixel as u16 };
+        self.palette.color((self.memory.peek_vram(addr) & 0x3F) as u16)
+    }
+}
@@ -0,0 +1,54 @@
+// src/nes/ppu/events.rs
+// Timeline of register accesses and interrupts over a frame, for event viewers
+
+use super::Ppu;
+
+/// Something that happened at `scanline`, `dot`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PpuEvent {
+    pub scanline: i16,
+    pub dot: usize,
+    pub kind: PpuEventKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PpuEventKind {
+    /// CPU read of register 0-7 and the value it got
+    RegisterRead { register: u8, data: u8 },
+    RegisterWrite { register: u8, data: u8 },
+    Nmi,
+    SpriteZeroHit,
+    /// The cartridge or the APU raised the IRQ line
+    Irq,
+}
+
+/// Events for the frame being drawn and the one before it, from the
+/// pre-render line on
+#[derive(Default)]
+pub(super) struct EventLog {
+    current: Vec<PpuEvent>,
+    last: Vec<PpuEvent>,
+}
+
+impl Ppu {
+    /// Everything logged over the last complete frame, in order. Empty
+    /// unless `record_events` is on.
+    pub fn events(&self) -> &[PpuEvent] {
+        &self.events.last
+    }
+
+    /// Note `kind` at the current dot; the bus logs IRQs here too
+    pub fn log_event(&mut self, kind: PpuEventKind) {
+        if self.record_events {
+            let (scanline, dot) = (self.scanline, self.cycle);
+            self.events.current.push(PpuEvent { scanline, dot, kind });
+        }
+    }
+
+    // The frame just finished becomes the one `events` shows
+    pub(super) fn end_event_frame(&mut self) {
+        let log = &mut self.events;
+        std::mem::swap(&mut log.current, &mut log.last);
+        log.current.clear();
+    }
+}
@@ -1,49 +1,88 @@
+use crate::nes::cart::SharedMapper;
+
 pub struct PpuMemory {
     pub vram: [u8; 2048],
     pub palette: [u8; 32],
     pub oam: [u8; 256],
     pub temp_oam: [u8; 32],
-    pub mirroring: Mirroring,
+    mapper: SharedMapper,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Mirroring {
     Horizontal,
     Vertical,
     FourScreen,
+    SingleScreenLow,
+    SingleScreenHigh,
 }
 
 impl PpuMemory {
-    pub fn new(mirroring: Mirroring) -> Self {
+    pub fn new(mapper: SharedMapper) -> Self {
         Self {
             vram: [0; 2048],
             palette: [0; 32],
             oam: [0; 256],
             temp_oam: [0; 32],
-            mirroring,
+            mapper,
         }
     }
 
     pub fn read_vram(&self, addr: u16) -> u8 {
-        let addr = match addr {
-            0x2000..=0x3EFF => self.mirror_vram_addr(addr),
-            0x3F00..=0x3FFF => self.palette_addr(addr),
-            _ => addr,
-        };
-        self.vram[(addr % 0x4000) as usize]
+        let addr = addr & 0x3FFF;
+        match addr {
+            0x0000..=0x1FFF => {
+                let mut mapper = self.mapper.borrow_mut();
+                let data = mapper.ppu_read(addr);
+                mapper.ppu_fetch(addr);
+                data
+            }
+            0x2000..=0x3EFF => self.vram[self.mirror_vram_addr(addr) as usize % 2048],
+            _ => self.palette[self.palette_addr(addr) as usize],
+        }
+    }
+
+    /// Read for debug views: no fetch notification, so latching mappers
+    /// like MMC2 don't see it
+    pub fn peek_vram(&self, addr: u16) -> u8 {
+        let addr = addr & 0x3FFF;
+        match addr {
+            0x0000..=0x1FFF => self.mapper.borrow_mut().ppu_read(addr),
+            _ => self.read_vram(addr),
+        }
+    }
+
+    pub fn write_vram(&mut self, addr: u16, data: u8) {
+        let addr = addr & 0x3FFF;
+        match addr {
+            0x0000..=0x1FFF => self.mapper.borrow_mut().ppu_write(addr, data),
+            0x2000..=0x3EFF => self.vram[self.mirror_vram_addr(addr) as usize % 2048] = data,
+            _ => self.palette[self.palette_addr(addr) as usize] = data,
+        }
+    }
+
+    pub fn a12_rise(&self) {
+        self.mapper.borrow_mut().ppu_a12_rise();
+    }
+
+    pub fn mirroring(&self) -> Mirroring {
+        self.mapper.borrow().mirroring()
     }
 
+    // Mirroring is queried per access since mappers may switch it mid-frame
     fn mirror_vram_addr(&self, addr: u16) -> u16 {
-        let addr = addr - 0x2000;
-        match self.mirroring {
-            Mirroring::Horizontal => addr & 0x7FF | (addr & 0x800) >> 1,
-            Mirroring::Vertical => addr & 0xBFF,
+        let addr = (addr - 0x2000) & 0xFFF;
+        match self.mirroring() {
+            Mirroring::Horizontal => (addr & 0x3FF) | ((addr & 0x800) >> 1),
+            Mirroring::Vertical => addr & 0x7FF,
             Mirroring::FourScreen => addr,
+            Mirroring::SingleScreenLow => addr & 0x3FF,
+            Mirroring::SingleScreenHigh => 0x400 | (addr & 0x3FF),
         }
     }
 
     fn palette_addr(&self, addr: u16) -> u16 {
-        let addr = addr - 0x3F00;
+        let addr = (addr - 0x3F00) & 0x1F;
         if addr == 0x10 || addr == 0x14 || addr == 0x18 || addr == 0x1C {
             addr - 0x10
         } else {
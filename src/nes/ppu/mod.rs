@@ -1,15 +1,42 @@
 mod registers;
+mod debug;
+mod events;
 mod memory;
+mod ntsc;
+mod palette;
+mod palettes;
 mod renderer;
+mod sprites;
+mod video;
 
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::nes::cart::SharedMapper;
+use crate::nes::clock::Region;
 use registers::{ControlRegister, MaskRegister, PpuRegisters};
+use events::EventLog;
 use memory::PpuMemory;
 use renderer::PpuRenderer;
+use sprites::SpriteState;
+use video::VideoOutput;
+
+pub use debug::{OamEntry, NAMETABLE_VIEW_HEIGHT, NAMETABLE_VIEW_WIDTH, PATTERN_VIEW_SIZE};
+pub use events::{PpuEvent, PpuEventKind};
+pub use memory::Mirroring;
+pub use ntsc::NTSC_WIDTH;
+pub use palette::{Palette, PaletteError};
+pub use video::{Frame, FrameBuffer, PixelFormat, VideoFilter};
+
+// A12 has to stay low for about three CPU cycles before the MMC3 counts a
+// rise, which hides the sprite fetches' brief drops within a line
+const A12_FILTER_DOTS: u64 = 10;
 
 pub struct Ppu {
     pub registers: PpuRegisters,
     pub memory: PpuMemory,
     renderer: PpuRenderer,
+    sprites: SpriteState,
     pub cycle: usize,
     pub scanline: i16,
     pub frame: u32,
@@ -17,14 +44,88 @@ pub struct Ppu {
     pub vram_addr: u16,
     pub tram_addr: u16,
     pub fine_x: u8,
+    read_buffer: u8,           // PPUDATA read buffer
+    suppress_vblank: bool,     // $2002 was read the dot before VBlank starts
+    dots: u64,                 // Dots since power-on
+    a12_high: bool,            // PPU address bus A12 as last driven
+    a12_low_since: u64,        // Dot A12 last fell
+    latch_refreshed: [u64; 8], // Dot each I/O latch bit was last driven
+    warm_up_until: u64,        // Dot register writes are taken again after a reset
+    /// Ignore PPUCTRL, PPUMASK, PPUSCROLL and PPUADDR writes after power-on
+    /// or reset until the next pre-render line, roughly a frame (29658 CPU
+    /// cycles measured on hardware), as the 2C02 does (the default)
+    pub warm_up: bool,
+    /// Reproduce the hardware's buggy sprite overflow scan (the default);
+    /// false sets the flag only for a genuine ninth sprite on a line
+    pub sprite_overflow_bug: bool,
+    /// Colors for the frame buffer, NTSC-generated unless a built-in one or
+    /// a .pal is chosen
+    pub palette: Palette,
+    /// NTSC 2C02 or PAL 2C07 timing
+    pub region: Region,
+    pub video_filter: VideoFilter,
+    video: VideoOutput,
+    /// Log register accesses and interrupts for `events`; off by default
+    pub record_events: bool,
+    events: EventLog,
+}
+
+/// Snapshot of everything the PPU holds apart from the cartridge, the
+/// finished frames and the output palette, for save states
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PpuState {
+    control: u8,
+    mask: u8,
+    status: u8,
+    oam_addr: u8,
+    scroll: (u8, u8),
+    addr: u16,
+    io_latch: u8,
+    write_toggle: bool,
+
+    vram: Vec<u8>,
+    palette: Vec<u8>,
+    oam: Vec<u8>,
+    secondary_oam: Vec<u8>,
+
+    next_tile: u8,
+    next_palette: u8,
+    next_pattern_low: u8,
+    next_pattern_high: u8,
+    pattern_shift_low: u16,
+    pattern_shift_high: u16,
+    attribute_shift_low: u8,
+    attribute_shift_high: u8,
+    attribute_latch: u8,
+    sprites: SpriteState,
+
+    cycle: usize,
+    scanline: i16,
+    frame: u32,
+    nmi_occurred: bool,
+    vram_addr: u16,
+    tram_addr: u16,
+    fine_x: u8,
+    read_buffer: u8,
+    suppress_vblank: bool,
+    dots: u64,
+    a12_high: bool,
+    a12_low_since: u64,
+    latch_refreshed: [u64; 8],
+    warm_up_until: u64,
+    warm_up: bool,
+    sprite_overflow_bug: bool,
+    region: Region,
 }
 
 impl Ppu {
-    pub fn new(mirroring: Mirroring) -> Self {
+    pub fn new(mapper: SharedMapper) -> Self {
         Self {
             registers: PpuRegisters::default(),
-            memory: PpuMemory::new(mirroring),
+            memory: PpuMemory::new(mapper),
             renderer: PpuRenderer::new(),
+            sprites: SpriteState::default(),
             cycle: 0,
             scanline: -1,
             frame: 0,
@@ -32,66 +133,410 @@ impl Ppu {
             vram_addr: 0,
             tram_addr: 0,
             fine_x: 0,
+            read_buffer: 0,
+            suppress_vblank: false,
+            dots: 0,
+            a12_high: false,
+            a12_low_since: 0,
+            latch_refreshed: [0; 8],
+            warm_up_until: 0,
+            warm_up: true,
+            sprite_overflow_bug: true,
+            palette: Palette::ntsc(),
+            region: Region::Ntsc,
+            video_filter: VideoFilter::Rgb,
+            video: VideoOutput::new(),
+            record_events: false,
+            events: EventLog::default(),
+        }
+    }
+
+    pub fn save_state(&self) -> PpuState {
+        let registers = &self.registers;
+        let renderer = &self.renderer;
+        PpuState {
+            control: registers.control.bits(),
+            mask: registers.mask.bits(),
+            status: registers.status,
+            oam_addr: registers.oam_addr,
+            scroll: registers.scroll,
+            addr: registers.addr,
+            io_latch: registers.data,
+            write_toggle: registers.write_toggle,
+            vram: self.memory.vram.to_vec(),
+            palette: self.memory.palette.to_vec(),
+            oam: self.memory.oam.to_vec(),
+            secondary_oam: self.memory.temp_oam.to_vec(),
+            next_tile: renderer.next_tile,
+            next_palette: renderer.next_palette,
+            next_pattern_low: renderer.next_pattern_low,
+            next_pattern_high: renderer.next_pattern_high,
+            pattern_shift_low: renderer.pattern_shift_low,
+            pattern_shift_high: renderer.pattern_shift_high,
+            attribute_shift_low: renderer.attribute_shift_low,
+            attribute_shift_high: renderer.attribute_shift_high,
+            attribute_latch: renderer.attribute_latch,
+            sprites: self.sprites.clone(),
+            cycle: self.cycle,
+            scanline: self.scanline,
+            frame: self.frame,
+            nmi_occurred: self.nmi_occurred,
+            vram_addr: self.vram_addr,
+            tram_addr: self.tram_addr,
+            fine_x: self.fine_x,
+            read_buffer: self.read_buffer,
+            suppress_vblank: self.suppress_vblank,
+            dots: self.dots,
+            a12_high: self.a12_high,
+            a12_low_since: self.a12_low_since,
+            latch_refreshed: self.latch_refreshed,
+            warm_up_until: self.warm_up_until,
+            warm_up: self.warm_up,
+            sprite_overflow_bug: self.sprite_overflow_bug,
+            region: self.region,
         }
     }
 
+    /// Restore a snapshot, possibly taken mid-scanline. Memory sizes that
+    /// don't match (a corrupt state) leave that memory as it was.
+    pub fn load_state(&mut self, state: &PpuState) {
+        self.registers.control = ControlRegister::from_bits_truncate(state.control);
+        self.registers.mask = MaskRegister::from_bits_truncate(state.mask);
+        self.registers.status = state.status;
+        self.registers.oam_addr = state.oam_addr;
+        self.registers.scroll = state.scroll;
+        self.registers.addr = state.addr;
+        self.registers.data = state.io_latch;
+        self.registers.write_toggle = state.write_toggle;
+        restore(&mut self.memory.vram, &state.vram);
+        restore(&mut self.memory.palette, &state.palette);
+        restore(&mut self.memory.oam, &state.oam);
+        restore(&mut self.memory.temp_oam, &state.secondary_oam);
+        let renderer = &mut self.renderer;
+        renderer.next_tile = state.next_tile;
+        renderer.next_palette = state.next_palette;
+        renderer.next_pattern_low = state.next_pattern_low;
+        renderer.next_pattern_high = state.next_pattern_high;
+        renderer.pattern_shift_low = state.pattern_shift_low;
+        renderer.pattern_shift_high = state.pattern_shift_high;
+        renderer.attribute_shift_low = state.attribute_shift_low;
+        renderer.attribute_shift_high = state.attribute_shift_high;
+        renderer.attribute_latch = state.attribute_latch;
+        self.sprites = state.sprites.clone();
+        self.cycle = state.cycle;
+        self.scanline = state.scanline;
+        self.frame = state.frame;
+        self.nmi_occurred = state.nmi_occurred;
+        self.vram_addr = state.vram_addr;
+        self.tram_addr = state.tram_addr;
+        self.fine_x = state.fine_x;
+        self.read_buffer = state.read_buffer;
+        self.suppress_vblank = state.suppress_vblank;
+        self.dots = state.dots;
+        self.a12_high = state.a12_high;
+        self.a12_low_since = state.a12_low_since;
+        self.latch_refreshed = state.latch_refreshed;
+        self.warm_up_until = state.warm_up_until;
+        self.warm_up = state.warm_up;
+        self.sprite_overflow_bug = state.sprite_overflow_bug;
+        self.region = state.region;
+    }
+
+    /// The console's reset line, also pulled at power-on: PPUCTRL, PPUMASK,
+    /// the scroll and the write toggle clear, and the warm-up starts. The
+    /// frame timing carries on where it was.
+    pub fn reset(&mut self) {
+        self.registers.control = ControlRegister::empty();
+        self.registers.mask = MaskRegister::empty();
+        self.registers.scroll = (0, 0);
+        self.registers.write_toggle = false;
+        self.fine_x = 0;
+        self.read_buffer = 0;
+        // Released at the next pre-render line, a frame after power-on
+        self.warm_up_until = self.dots + self.region.scanlines() as u64 * 341;
+    }
+
+    fn warming_up(&self) -> bool {
+        self.warm_up && self.dots < self.warm_up_until
+    }
+
     pub fn step(&mut self) -> bool {
         let mut frame_complete = false;
         
         self.cycle += 1;
-        if self.cycle > 340 {
+        self.dots += 1;
+        // NTSC odd frames drop the pre-render line's last dot while rendering
+        // (background or sprites) is on, for 89341.5 dots a frame on average.
+        // PAL always runs the full 341 x 312.
+        let skip_dot = self.region == Region::Ntsc
+            && self.scanline == -1
+            && self.cycle == 340
+            && self.frame % 2 == 1
+            && self.rendering_enabled();
+        if self.cycle > 340 || skip_dot {
             self.cycle = 0;
             self.scanline += 1;
             
-            if self.scanline > 260 {
+            if self.scanline >= self.region.scanlines() as i16 - 1 {
                 self.scanline = -1;
                 self.frame += 1;
                 frame_complete = true;
+                self.end_event_frame();
             }
         }
         
         match self.scanline {
             -1 => self.pre_render_scanline(),
             0..=239 => self.visible_scanline(),
-            240 => {} // Post-render
-            241 => {
-                if self.scanline == 241 && self.cycle == 1 {
-                    self.registers.status |= 0x80; // VBlank
-                    if self.registers.control.contains(ControlRegister::NMI_ENABLE) {
-                        self.nmi_occurred = true;
-                    }
+            240 if self.cycle == 0 => self.renderer.swap_buffers(), // Post-render
+            241 if self.cycle == 1 && self.suppress_vblank => self.suppress_vblank = false,
+            241 if self.cycle == 1 => {
+                self.registers.status |= 0x80; // VBlank
+                if self.registers.control.contains(ControlRegister::NMI_ENABLE) {
+                    self.nmi_occurred = true;
+                    self.log_event(PpuEventKind::Nmi);
                 }
-            },
+            }
             _ => {}
         }
         
         frame_complete
     }
 
-    fn pre_render_scanline(&mut self) {
-        if self.cycle == 1 {
-            self.registers.status &= 0x1F; // Clear VBlank, sprite 0 hit, overflow
+    /// CPU read of register `reg` ($2000-$2007 mirrored down to 0-7).
+    /// Bits the register doesn't drive come from the I/O latch: the low
+    /// five of PPUSTATUS, the top two of palette reads, and all of them for
+    /// the write-only registers.
+    pub fn read_register(&mut self, reg: u16) -> u8 {
+        self.decay_latch();
+        let (data, driven) = match reg & 0x07 {
+            2 => (self.read_status(), 0xE0),
+            4 => (self.read_oam_data(), 0xFF),
+            7 if self.vram_addr & 0x3FFF >= 0x3F00 => (self.read_data(), 0x3F),
+            7 => (self.read_data(), 0xFF),
+            _ => (0, 0x00),
+        };
+        self.refresh_latch(data, driven);
+        let data = self.registers.data;
+        self.log_event(PpuEventKind::RegisterRead { register: (reg & 0x07) as u8, data });
+        data
+    }
+
+    /// CPU write of register `reg` ($2000-$2007 mirrored down to 0-7)
+    pub fn write_register(&mut self, reg: u16, data: u8) {
+        self.refresh_latch(data, 0xFF);
+        self.log_event(PpuEventKind::RegisterWrite { register: (reg & 0x07) as u8, data });
+        match reg & 0x07 {
+            // Still held in reset; the latch sees the write all the same
+            0 | 1 | 5 | 6 if self.warming_up() => {}
+            0 => self.write_control(data),
+            1 => self.registers.mask = MaskRegister::from_bits_truncate(data),
+            2 => {}
+            3 => self.write_oam_addr(data),
+            4 => self.write_oam_data(data),
+            5 => self.write_scroll(data),
+            6 => self.write_addr(data),
+            _ => self.write_data(data),
         }
     }
 
-    fn visible_scanline(&mut self) {
-        if self.cycle < 256 || (self.cycle >= 321 && self.cycle <= 336) {
-            self.increment_x();
+    /// PPUCTRL ($2000); the nametable bits also land in the temporary address.
+    /// /NMI is VBlank ANDed with the enable bit, so setting it during VBlank
+    /// raises another NMI, and clearing it drops one the CPU hasn't seen.
+    pub fn write_control(&mut self, data: u8) {
+        let was_enabled = self.registers.control.contains(ControlRegister::NMI_ENABLE);
+        self.registers.control = ControlRegister::from_bits_truncate(data);
+        self.tram_addr = (self.tram_addr & !0x0C00) | (((data & 0x03) as u16) << 10);
+
+        if !self.registers.control.contains(ControlRegister::NMI_ENABLE) {
+            self.nmi_occurred = false;
+        } else if !was_enabled && self.registers.status & 0x80 != 0 {
+            self.nmi_occurred = true;
+            self.log_event(PpuEventKind::Nmi);
         }
-        
-        if self.cycle == 256 {
+    }
+
+    /// PPUSTATUS ($2002); reading clears vblank and the write toggle. A read
+    /// racing the start of VBlank changes that frame: one dot early it never
+    /// sets, and on the dot or the one after it reads set but the NMI is lost.
+    pub fn read_status(&mut self) -> u8 {
+        match (self.scanline, self.cycle) {
+            (241, 0) => self.suppress_vblank = true,
+            (241, 1..=2) => self.nmi_occurred = false,
+            _ => {}
+        }
+        let status = self.registers.status;
+        self.registers.status &= !0x80;
+        self.registers.write_toggle = false;
+        status
+    }
+
+    /// OAMADDR ($2003)
+    pub fn write_oam_addr(&mut self, data: u8) {
+        self.registers.oam_addr = data;
+    }
+
+    /// OAMDATA ($2004), also the target of sprite DMA. While rendering, OAM
+    /// belongs to sprite evaluation: the write is dropped and OAMADDR takes
+    /// a glitchy step to the next sprite instead.
+    pub fn write_oam_data(&mut self, data: u8) {
+        if self.rendering() || self.oam_refresh() {
+            self.registers.oam_addr = self.registers.oam_addr.wrapping_add(4);
+            return;
+        }
+        self.memory.oam[self.registers.oam_addr as usize] = data;
+        self.registers.oam_addr = self.registers.oam_addr.wrapping_add(1);
+    }
+
+    /// OAMDATA read; doesn't advance OAMADDR. While rendering it returns
+    /// whatever sprite evaluation has on the OAM bus.
+    pub fn read_oam_data(&self) -> u8 {
+        if self.rendering() {
+            return self.sprite_oam_bus();
+        }
+        let data = self.memory.oam[self.registers.oam_addr as usize];
+        // Attribute bits 2-4 don't exist in OAM
+        if self.registers.oam_addr & 0x03 == 2 {
+            data & 0xE3
+        } else {
+            data
+        }
+    }
+
+    /// PPUSCROLL ($2005): X then Y, sharing the toggle with PPUADDR
+    pub fn write_scroll(&mut self, data: u8) {
+        if !self.registers.write_toggle {
+            self.tram_addr = (self.tram_addr & !0x001F) | (data >> 3) as u16;
+            self.fine_x = data & 0x07;
+            self.registers.scroll.0 = data;
+        } else {
+            self.tram_addr = (self.tram_addr & !0x73E0)
+                | (((data & 0x07) as u16) << 12)
+                | (((data >> 3) as u16) << 5);
+            self.registers.scroll.1 = data;
+        }
+        self.registers.write_toggle = !self.registers.write_toggle;
+    }
+
+    /// PPUADDR ($2006): high byte then low; the second write loads v
+    pub fn write_addr(&mut self, data: u8) {
+        if !self.registers.write_toggle {
+            self.tram_addr = (self.tram_addr & 0x00FF) | (((data & 0x3F) as u16) << 8);
+        } else {
+            self.tram_addr = (self.tram_addr & 0xFF00) | data as u16;
+            self.vram_addr = self.tram_addr;
+            self.drive_address(self.vram_addr);
+        }
+        self.registers.addr = self.vram_addr;
+        self.registers.write_toggle = !self.registers.write_toggle;
+    }
+
+    /// PPUDATA ($2007) write, advancing v by 1 or 32 (see `increment_vram_addr`)
+    pub fn write_data(&mut self, data: u8) {
+        self.memory.write_vram(self.vram_addr, data);
+        self.increment_vram_addr();
+    }
+
+    /// PPUDATA ($2007) read, advancing v by 1 or 32 (see `increment_vram_addr`).
+    /// Reads below the palette come from a buffer, one read behind; palette
+    /// reads are immediate but still refill the buffer from the nametable
+    /// underneath.
+    pub fn read_data(&mut self) -> u8 {
+        let addr = self.vram_addr & 0x3FFF;
+        let data = if addr >= 0x3F00 {
+            self.read_buffer = self.memory.read_vram(addr - 0x1000);
+            self.memory.read_vram(addr)
+        } else {
+            std::mem::replace(&mut self.read_buffer, self.memory.read_vram(addr))
+        };
+        self.increment_vram_addr();
+        data
+    }
+
+    // While rendering, v is the scroll counter: a $2007 access bumps coarse
+    // X and Y together instead of adding 1 or 32
+    fn increment_vram_addr(&mut self) {
+        if self.rendering() {
+            self.increment_x();
             self.increment_y();
+            self.registers.addr = self.vram_addr;
+            return;
         }
-        
-        if self.cycle == 257 {
-            self.transfer_x();
+        let step = if self.registers.control.contains(ControlRegister::VRAM_INCREMENT) { 32 } else { 1 };
+        self.vram_addr = self.vram_addr.wrapping_add(step) & 0x3FFF;
+        self.registers.addr = self.vram_addr;
+        self.drive_address(self.vram_addr);
+    }
+
+    // Rendering fetches go out on the address bus; outside rendering the
+    // bus holds v, so $2006 and $2007 can clock the mapper as well
+    pub(super) fn fetch(&mut self, addr: u16) -> u8 {
+        self.drive_address(addr);
+        self.memory.read_vram(addr)
+    }
+
+    // Tracks A12 on the PPU address bus and passes filtered rising edges
+    // to the mapper
+    fn drive_address(&mut self, addr: u16) {
+        let high = addr & 0x1000 != 0;
+        if high && !self.a12_high && self.dots - self.a12_low_since >= A12_FILTER_DOTS {
+            self.memory.a12_rise();
+        } else if !high && self.a12_high {
+            self.a12_low_since = self.dots;
         }
-        
-        if self.scanline == 0 && self.cycle >= 280 && self.cycle <= 304 {
-            self.transfer_y();
+        self.a12_high = high;
+    }
+
+    // The latch is just bus capacitance: a bit nothing has driven for about
+    // 600ms fades to 0
+    fn decay_latch(&mut self) {
+        let decay_dots = self.region.master_hz() / self.region.ppu_divider() * 6 / 10;
+        for (bit, refreshed) in self.latch_refreshed.iter().enumerate() {
+            if self.dots - refreshed >= decay_dots {
+                self.registers.data &= !(1 << bit);
+            }
         }
     }
 
+    fn refresh_latch(&mut self, data: u8, driven: u8) {
+        self.registers.data = (self.registers.data & !driven) | (data & driven);
+        for (bit, refreshed) in self.latch_refreshed.iter_mut().enumerate() {
+            if driven & (1 << bit) != 0 {
+                *refreshed = self.dots;
+            }
+        }
+    }
+
+    fn rendering_enabled(&self) -> bool {
+        self.registers.mask.intersects(MaskRegister::SHOW_BACKGROUND | MaskRegister::SHOW_SPRITES)
+    }
+
+    // The pre-render and visible lines with rendering on, when v, OAM and
+    // the VRAM bus are busy with fetches
+    fn rendering(&self) -> bool {
+        self.scanline < 240 && self.rendering_enabled()
+    }
+
+    // The 2C07 refreshes OAM for the last 46 lines of its long VBlank while
+    // rendering is on, locking out writes; PAL games upload sprites early
+    fn oam_refresh(&self) -> bool {
+        self.region == Region::Pal && (265..=310).contains(&self.scanline) && self.rendering_enabled()
+    }
+
+    fn pre_render_scanline(&mut self) {
+        if self.cycle == 1 {
+            self.registers.status &= 0x1F; // Clear VBlank, sprite 0 hit, overflow
+        }
+        self.render_dot();
+    }
+
+    fn visible_scanline(&mut self) {
+        if self.scanline == 0 && self.cycle == 0 {
+            self.renderer.start_frame(self.dots);
+        }
+        self.render_dot();
+    }
+
     fn increment_x(&mut self) {
         if (self.vram_addr & 0x001F) == 31 {
             self.vram_addr &= !0x001F;
@@ -106,14 +551,32 @@ impl Ppu {
             self.vram_addr += 0x1000;
         } else {
             self.vram_addr &= !0x7000;
-            let mut y = (self.vram_addr & 0x03E0) >> 5;
-            if y == 29 {
-                y = 0;
-                self.vram_addr ^= 0x0800;
-            } else {
-                y += 1;
-            }
+            // Row 29 is the last of a nametable; rows 30 and 31 are its
+            // attribute table, reachable by writing them, and wrap to 0
+            // without switching nametables
+            let y = match (self.vram_addr & 0x03E0) >> 5 {
+                29 => {
+                    self.vram_addr ^= 0x0800;
+                    0
+                }
+                31 => 0,
+                y => y + 1,
+            };
             self.vram_addr = (self.vram_addr & !0x03E0) | (y << 5);
         }
     }
+
+    fn transfer_x(&mut self) {
+        self.vram_addr = (self.vram_addr & !0x041F) | (self.tram_addr & 0x041F);
+    }
+
+    fn transfer_y(&mut self) {
+        self.vram_addr = (self.vram_addr & !0x7BE0) | (self.tram_addr & 0x7BE0);
+    }
+}
+
+fn restore(memory: &mut [u8], saved: &[u8]) {
+    if memory.len() == saved.len() {
+        memory.copy_from_slice(saved);
+    }
 }
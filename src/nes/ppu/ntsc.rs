@@ -0,0 +1,69 @@
+// src/nes/ppu/ntsc.rs
+// Composite video filter: re-encodes a frame as the PPU's NTSC signal and
+// decodes it the way a TV does, color fringing and dot crawl included
+
+use super::palette::{composite_level, phase_angle, yiq_to_rgb};
+
+/// Output width: two pixels per PPU pixel, enough to show the fringes
+pub const NTSC_WIDTH: usize = 512;
+
+const SAMPLES_PER_PIXEL: usize = 8; // Each dot spans 8 of the 12 subcarrier phases
+const LINE_SAMPLES: usize = 256 * SAMPLES_PER_PIXEL;
+const SAMPLES_PER_OUTPUT: usize = LINE_SAMPLES / NTSC_WIDTH;
+// Lines are 341 dots long, which moves the phase on by 4 each line
+const LINE_PHASE_STEP: usize = 341 * SAMPLES_PER_PIXEL % 12;
+
+pub struct NtscFilter {
+    signal: Vec<f64>, // One line of samples
+    output: Vec<u32>,
+    // Demodulation weights per subcarrier phase
+    cos: [f64; 12],
+    sin: [f64; 12],
+}
+
+impl NtscFilter {
+    pub fn new() -> Self {
+        Self {
+            signal: vec![0.0; LINE_SAMPLES],
+            output: vec![0; NTSC_WIDTH * 240],
+            cos: std::array::from_fn(|phase| phase_angle(phase).cos()),
+            sin: std::array::from_fn(|phase| phase_angle(phase).sin()),
+        }
+    }
+
+    /// Filter a frame of palette indices whose first line started at
+    /// subcarrier `phase`, returning `NTSC_WIDTH` x 240 RGB pixels
+    pub fn apply(&mut self, indices: &[u16], phase: usize) -> &[u32] {
+        for (line, row) in indices.chunks_exact(256).enumerate() {
+            let line_phase = (phase + line * LINE_PHASE_STEP) % 12;
+            for (x, &index) in row.iter().enumerate() {
+                let (color, emphasis) = ((index & 0x3F) as u8, (index >> 6) as u8);
+                for sample in x * SAMPLES_PER_PIXEL..(x + 1) * SAMPLES_PER_PIXEL {
+                    self.signal[sample] = composite_level(color, emphasis, (line_phase + sample) % 12);
+                }
+            }
+
+            // Luma and chroma are each averaged over one subcarrier cycle
+            // around the output pixel. Where the color changes within it,
+            // luma picks up chroma and chroma picks up luma edges.
+            for x in 0..NTSC_WIDTH {
+                let center = x * SAMPLES_PER_OUTPUT + SAMPLES_PER_OUTPUT / 2;
+                let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+                for sample in center.saturating_sub(6)..(center + 6).min(LINE_SAMPLES) {
+                    let signal = self.signal[sample] / 12.0;
+                    let phase = (line_phase + sample) % 12;
+                    y += signal;
+                    i += 2.0 * signal * self.cos[phase];
+                    q += 2.0 * signal * self.sin[phase];
+                }
+                self.output[line * NTSC_WIDTH + x] = yiq_to_rgb(y, i, q);
+            }
+        }
+        &self.output
+    }
+
+    /// The most recently filtered frame
+    pub fn output(&self) -> &[u32] {
+        &self.output
+    }
+}
@@ -0,0 +1,169 @@
+// src/nes/ppu/palette.rs
+// Master palette: 6-bit PPU colors under each emphasis setting to RGB
+
+use std::f64::consts::PI;
+
+use thiserror::Error;
+
+use super::palettes::BUILTIN;
+
+const COLORS: usize = 64;
+const EMPHASIS_SETTINGS: usize = 8;
+const PAL_SIZE: usize = COLORS * 3;
+const PAL_EMPHASIS_SIZE: usize = COLORS * EMPHASIS_SETTINGS * 3;
+
+// Composite output voltages for luma levels 0-3, low and high halves of the
+// chroma square wave, as measured on a 2C02
+const SIGNAL_LOW: [f64; 4] = [0.350, 0.518, 0.962, 1.550];
+const SIGNAL_HIGH: [f64; 4] = [1.094, 1.506, 1.962, 1.962];
+const BLACK: f64 = 0.518;
+const WHITE: f64 = 1.962;
+// Emphasis scales the signal down by this much during its colors' phases
+const ATTENUATION: f64 = 0.746;
+// Rotates the decoded hues to line up with a TV's color burst reference
+const HUE_OFFSET: f64 = 3.5;
+
+#[derive(Debug, Error)]
+pub enum PaletteError {
+    #[error(".pal file is {0} bytes; expected {PAL_SIZE} (64 colors) or {PAL_EMPHASIS_SIZE} (512 with emphasis)")]
+    InvalidSize(usize),
+}
+
+/// RGB (`0x00RRGGBB`) for every color the PPU can output, indexed by
+/// `emphasis << 6 | color` with the three PPUMASK emphasis bits
+#[derive(Clone)]
+pub struct Palette {
+    colors: Vec<u32>,
+}
+
+impl Palette {
+    /// Names `named` accepts: the generated palette, then the built-in ones
+    pub const NAMES: [&'static str; 5] = ["ntsc", BUILTIN[0].0, BUILTIN[1].0, BUILTIN[2].0, BUILTIN[3].0];
+
+    /// A palette by name (see `NAMES`), case-insensitively
+    pub fn named(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("ntsc") {
+            return Some(Self::ntsc());
+        }
+        BUILTIN
+            .iter()
+            .find(|(builtin, _)| name.eq_ignore_ascii_case(builtin))
+            .map(|(_, colors)| Self::from_colors(colors))
+    }
+
+    /// Generated from a model of the 2C02's composite signal, decoded the
+    /// way an NTSC television would
+    pub fn ntsc() -> Self {
+        let colors = (0..COLORS * EMPHASIS_SETTINGS)
+            .map(|index| ntsc_color((index & 0x3F) as u8, (index >> 6) as u8))
+            .collect();
+        Self { colors }
+    }
+
+    /// Load a .pal file: 64 RGB triplets, or 512 with the emphasis variants
+    /// following in order. Emphasis for a 64-color file is approximated by
+    /// dimming the channels that aren't emphasized.
+    pub fn from_pal(data: &[u8]) -> Result<Self, PaletteError> {
+        let rgb = |triplet: &[u8]| (triplet[0] as u32) << 16 | (triplet[1] as u32) << 8 | triplet[2] as u32;
+        let colors = match data.len() {
+            PAL_EMPHASIS_SIZE => data.chunks_exact(3).map(rgb).collect(),
+            PAL_SIZE => {
+                let colors: Vec<u32> = data.chunks_exact(3).map(rgb).collect();
+                return Ok(Self::from_colors(&colors));
+            }
+            len => return Err(PaletteError::InvalidSize(len)),
+        };
+        Ok(Self { colors })
+    }
+
+    // 64 colors, with emphasis approximated by dimming
+    fn from_colors(colors: &[u32]) -> Self {
+        let colors = (0..COLORS * EMPHASIS_SETTINGS)
+            .map(|index| emphasize(colors[index & 0x3F], (index >> 6) as u8))
+            .collect();
+        Self { colors }
+    }
+
+    /// RGB for a palette RAM value (low six bits) with emphasis bits 6-8
+    pub fn color(&self, index: u16) -> u32 {
+        self.colors[index as usize % (COLORS * EMPHASIS_SETTINGS)]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::ntsc()
+    }
+}
+
+// Whether the chroma square wave for `hue` is high during `phase` of the
+// 12-phase color subcarrier cycle
+fn in_color_phase(hue: u8, phase: usize) -> bool {
+    (hue as usize + phase) % 12 < 6
+}
+
+/// Composite signal for `color` under `emphasis` at one of the 12 phases
+/// of the color subcarrier, scaled so black is 0.0 and white 1.0
+pub(super) fn composite_level(color: u8, emphasis: u8, phase: usize) -> f64 {
+    let hue = color & 0x0F;
+    // Columns $E and $F output black at every luma level
+    let level = if hue >= 0x0E { 1 } else { (color >> 4 & 0x03) as usize };
+    let high = match hue {
+        0x00 => true,
+        0x01..=0x0C => in_color_phase(hue, phase),
+        _ => false,
+    };
+    let mut signal = if high { SIGNAL_HIGH[level] } else { SIGNAL_LOW[level] };
+    let attenuated = (emphasis & 0x01 != 0 && in_color_phase(0x0C, phase))
+        || (emphasis & 0x02 != 0 && in_color_phase(0x04, phase))
+        || (emphasis & 0x04 != 0 && in_color_phase(0x08, phase));
+    if attenuated && hue < 0x0E {
+        signal *= ATTENUATION;
+    }
+    (signal - BLACK) / (WHITE - BLACK)
+}
+
+/// Demodulation angle for a subcarrier phase, I on the cosine and Q on
+/// the sine
+pub(super) fn phase_angle(phase: usize) -> f64 {
+    PI * (phase as f64 + HUE_OFFSET) / 6.0
+}
+
+pub(super) fn yiq_to_rgb(y: f64, i: f64, q: f64) -> u32 {
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u32;
+    let r = channel(y + 0.956 * i + 0.621 * q);
+    let g = channel(y - 0.272 * i - 0.647 * q);
+    let b = channel(y - 1.106 * i + 1.703 * q);
+    r << 16 | g << 8 | b
+}
+
+// One pixel's signal sampled over a full subcarrier cycle and decoded to
+// YIQ, then RGB
+fn ntsc_color(color: u8, emphasis: u8) -> u32 {
+    let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+    for phase in 0..12 {
+        let signal = composite_level(color, emphasis, phase) / 12.0;
+        let angle = phase_angle(phase);
+        y += signal;
+        i += 2.0 * signal * angle.cos();
+        q += 2.0 * signal * angle.sin();
+    }
+    yiq_to_rgb(y, i, q)
+}
+
+// Each emphasis bit (red, green, blue) dims the other two channels
+fn emphasize(rgb: u32, emphasis: u8) -> u32 {
+    let mut scale = [1.0; 3];
+    for (bit, channel) in [(0x01, 0), (0x02, 1), (0x04, 2)] {
+        if emphasis & bit != 0 {
+            for (other, factor) in scale.iter_mut().enumerate() {
+                if other != channel {
+                    *factor *= ATTENUATION;
+                }
+            }
+        }
+    }
+    let [r, g, b] = [16, 8, 0].map(|shift| (rgb >> shift) & 0xFF);
+    let dim = |value: u32, factor: f64| (value as f64 * factor).round() as u32;
+    dim(r, scale[0]) << 16 | dim(g, scale[1]) << 8 | dim(b, scale[2])
+}
@@ -1,6 +1,7 @@
 use bitflags::bitflags;
 
 bitflags! {
+    #[derive(Clone, Copy, Default)]
     pub struct ControlRegister: u8 {
         const NAMETABLE_X      = 0b00000001;
         const NAMETABLE_Y      = 0b00000010;
@@ -14,14 +15,16 @@ bitflags! {
 }
 
 bitflags! {
+    #[derive(Clone, Copy, Default)]
     pub struct MaskRegister: u8 {
-        const GRAYSCALE        = 0b00000001;
-        const SHOW_BACKGROUND  = 0b00000010;
-        const SHOW_SPRITES     = 0b00000100;
-        const SHOW_EDGES       = 0b00010000;
-        const EMPHASIZE_RED    = 0b00100000;
-        const EMPHASIZE_GREEN  = 0b01000000;
-        const EMPHASIZE_BLUE   = 0b10000000;
+        const GRAYSCALE            = 0b00000001;
+        const SHOW_BACKGROUND_LEFT = 0b00000010;
+        const SHOW_SPRITES_LEFT    = 0b00000100;
+        const SHOW_BACKGROUND      = 0b00001000;
+        const SHOW_SPRITES         = 0b00010000;
+        const EMPHASIZE_RED        = 0b00100000;
+        const EMPHASIZE_GREEN      = 0b01000000;
+        const EMPHASIZE_BLUE       = 0b10000000;
     }
 }
 
@@ -1,19 +1,29 @@
+use super::events::PpuEventKind;
+use super::registers::{ControlRegister, MaskRegister};
+use super::Ppu;
+use crate::nes::clock::Region;
+
 pub struct PpuRenderer {
-    pub front_buffer: Vec<u32>,
-    back_buffer: Vec<u32>,
-    pub sprite_zero_hit: bool,
-    pub sprite_overflow: bool,
-    pub scanline_sprites: Vec<Sprite>,
-}
+    // Palette indices (color | emphasis << 6), turned into pixels on output
+    pub front_buffer: Vec<u16>,
+    back_buffer: Vec<u16>,
+    // Color subcarrier phase (0-11) at the start of each buffer's first line
+    pub front_phase: usize,
+    back_phase: usize,
+
+    // Background tile being fetched, one byte every two dots
+    pub(super) next_tile: u8,
+    pub(super) next_palette: u8,
+    pub(super) next_pattern_low: u8,
+    pub(super) next_pattern_high: u8,
 
-#[derive(Clone)]
-struct Sprite {
-    y: u8,
-    tile: u8,
-    attributes: u8,
-    x: u8,
-    data_low: u8,
-    data_high: u8,
+    // Two tiles of pattern bits; the high byte is the tile being drawn
+    pub(super) pattern_shift_low: u16,
+    pub(super) pattern_shift_high: u16,
+    // Palette bits, shifted in from the latch one pixel at a time
+    pub(super) attribute_shift_low: u8,
+    pub(super) attribute_shift_high: u8,
+    pub(super) attribute_latch: u8,
 }
 
 impl PpuRenderer {
@@ -21,89 +31,197 @@ impl PpuRenderer {
         Self {
             front_buffer: vec![0; 256 * 240],
             back_buffer: vec![0; 256 * 240],
-            sprite_zero_hit: false,
-            sprite_overflow: false,
-            scanline_sprites: Vec::with_capacity(8),
+            front_phase: 0,
+            back_phase: 0,
+            next_tile: 0,
+            next_palette: 0,
+            next_pattern_low: 0,
+            next_pattern_high: 0,
+            pattern_shift_low: 0,
+            pattern_shift_high: 0,
+            attribute_shift_low: 0,
+            attribute_shift_high: 0,
+            attribute_latch: 0,
         }
     }
 
-    pub fn render_scanline(&mut self, ppu: &mut Ppu, scanline: i16) {
-        if scanline < 0 || scanline > 239 { return; }
+    /// Show the finished frame
+    pub fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.front_buffer, &mut self.back_buffer);
+        self.front_phase = self.back_phase;
+    }
+
+    /// Note the subcarrier phase as the frame's first line starts. Each
+    /// dot is 8 of the 12 phases, so `dots` pins it down.
+    pub fn start_frame(&mut self, dots: u64) {
+        self.back_phase = (dots % 3 * 8 % 12) as usize;
+    }
+
+    fn shift(&mut self) {
+        self.pattern_shift_low <<= 1;
+        self.pattern_shift_high <<= 1;
+        self.attribute_shift_low = (self.attribute_shift_low << 1) | (self.attribute_latch & 0x01);
+        self.attribute_shift_high = (self.attribute_shift_high << 1) | (self.attribute_latch >> 1);
+    }
+
+    // Fetched tile into the low byte, ready to shift up after the current one
+    fn reload(&mut self) {
+        self.pattern_shift_low = (self.pattern_shift_low & 0xFF00) | self.next_pattern_low as u16;
+        self.pattern_shift_high = (self.pattern_shift_high & 0xFF00) | self.next_pattern_high as u16;
+        self.attribute_latch = self.next_palette;
+    }
 
-        // Background rendering
-        if ppu.registers.mask.contains(MaskRegister::SHOW_BACKGROUND) {
-            self.render_background(ppu, scanline);
+    // Pixel value and palette under the fine X scroll
+    fn background_pixel(&self, fine_x: u8) -> (u8, u8) {
+        let pattern_bit = 0x8000 >> fine_x;
+        let pixel = ((self.pattern_shift_high & pattern_bit != 0) as u8) << 1
+            | (self.pattern_shift_low & pattern_bit != 0) as u8;
+        let attribute_bit = 0x80 >> fine_x;
+        let palette = ((self.attribute_shift_high & attribute_bit != 0) as u8) << 1
+            | (self.attribute_shift_low & attribute_bit != 0) as u8;
+        (pixel, palette)
+    }
+}
+
+impl Ppu {
+    /// One dot of the pre-render or a visible scanline: background fetches
+    /// into the shift registers, sprite evaluation and fetches, scroll
+    /// updates and pixel output
+    pub(super) fn render_dot(&mut self) {
+        if !self.rendering_enabled() {
+            if self.scanline >= 0 && (1..=256).contains(&self.cycle) {
+                self.output_pixel();
+            }
+            return;
         }
 
-        // Sprite rendering
-        if ppu.registers.mask.contains(MaskRegister::SHOW_SPRITES) {
-            self.evaluate_sprites(ppu, scanline);
-            self.render_sprites(ppu, scanline);
+        let cycle = self.cycle;
+        let fetching = (1..=256).contains(&cycle) || (321..=336).contains(&cycle);
+
+        if (2..=257).contains(&cycle) || (322..=337).contains(&cycle) {
+            self.renderer.shift();
         }
 
-        // Swap buffers at end of frame
-        if scanline == 240 {
-            std::mem::swap(&mut self.front_buffer, &mut self.back_buffer);
+        if fetching {
+            // Each tile takes 8 dots: nametable, attribute, pattern low and
+            // high bytes, two dots apiece, then coarse X moves on
+            match (cycle - 1) % 8 {
+                0 => {
+                    // Load the tile fetched over the previous 8 dots. Dot
+                    // 257 loads the last one, leaving nothing new at 321.
+                    if cycle >= 9 && cycle != 321 {
+                        self.renderer.reload();
+                    }
+                    self.fetch_nametable_byte();
+                }
+                2 => self.fetch_attribute_byte(),
+                4 => self.renderer.next_pattern_low = self.fetch(self.background_pattern_addr()),
+                6 => self.renderer.next_pattern_high = self.fetch(self.background_pattern_addr() + 8),
+                7 => self.increment_x(),
+                _ => {}
+            }
         }
-    }
 
-    fn render_background(&mut self, ppu: &mut Ppu, scanline: i16) {
-        let fine_y = ((ppu.vram_addr >> 12) & 0x7) as u8;
-        let coarse_y = ((ppu.vram_addr >> 5) & 0x1F) as u8;
-        let nametable = ((ppu.vram_addr >> 10) & 0x3) as u8;
-
-        for x in 0..256 {
-            let coarse_x = (ppu.vram_addr & 0x1F) as u8;
-            let tile = ppu.memory.read_vram(0x2000 | (ppu.vram_addr & 0xFFF));
-            
-            // Fetch pattern data
-            let pattern_addr = ppu.registers.control.bits() << 12 
-                | (tile as u16) << 4 
-                | fine_y as u16;
-            
-            let pattern_low = ppu.memory.read_vram(pattern_addr);
-            let pattern_high = ppu.memory.read_vram(pattern_addr + 8);
-            
-            // Get palette
-            let attr_addr = 0x23C0 | (ppu.vram_addr & 0xC00) 
-                | ((ppu.vram_addr >> 4) & 0x38) 
-                | ((ppu.vram_addr >> 2) & 0x07);
-            let attr = ppu.memory.read_vram(attr_addr);
-            
-            // Calculate pixel color
-            let shift = 7 - (x % 8);
-            let palette = self.get_background_palette(ppu, attr, coarse_x, coarse_y);
-            let color = self.get_color(ppu, palette, pattern_low, pattern_high, shift);
-            
-            self.back_buffer[(scanline as usize * 256) + x as usize] = color;
+        match cycle {
+            256 => self.increment_y(),
+            257 => {
+                self.renderer.reload();
+                self.transfer_x();
+            }
+            280..=304 if self.scanline == -1 => self.transfer_y(),
+            // The second prefetched tile goes in behind the first, then
+            // unused nametable fetches close out the line
+            337 => {
+                self.renderer.reload();
+                self.fetch_nametable_byte();
+            }
+            339 => self.fetch_nametable_byte(),
+            _ => {}
         }
+
+        self.sprite_dot();
+
+        if self.scanline >= 0 && (1..=256).contains(&cycle) {
+            self.output_pixel();
+        }
+    }
+
+    fn fetch_nametable_byte(&mut self) {
+        self.renderer.next_tile = self.fetch(0x2000 | (self.vram_addr & 0x0FFF));
+    }
+
+    // One attribute byte covers a 4x4 tile area; pick the 2x2 quadrant v is in
+    fn fetch_attribute_byte(&mut self) {
+        let v = self.vram_addr;
+        let attr_addr = 0x23C0 | (v & 0x0C00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x07);
+        let attr = self.fetch(attr_addr);
+        let shift = ((v >> 4) & 0x04) | (v & 0x02);
+        self.renderer.next_palette = (attr >> shift) & 0x03;
+    }
+
+    fn background_pattern_addr(&self) -> u16 {
+        let table = (self.registers.control.contains(ControlRegister::BACKGROUND_TABLE) as u16) << 12;
+        let fine_y = (self.vram_addr >> 12) & 0x07;
+        table | (self.renderer.next_tile as u16) << 4 | fine_y
     }
 
-    fn evaluate_sprites(&mut self, ppu: &mut Ppu, scanline: i16) {
-        self.scanline_sprites.clear();
-        let sprite_height = if ppu.registers.control.contains(ControlRegister::SPRITE_SIZE) {
-            16
+    // Background and sprite pixels combined by priority, with sprite 0 hit
+    // detected where both are opaque. Either layer can be clipped from the
+    // leftmost 8 pixels, which keeps sprite 0 from hitting there too.
+    fn output_pixel(&mut self) {
+        let x = self.cycle - 1;
+        let mask = self.registers.mask;
+        let show_background = mask.contains(MaskRegister::SHOW_BACKGROUND)
+            && (x >= 8 || mask.contains(MaskRegister::SHOW_BACKGROUND_LEFT));
+        let show_sprites = mask.contains(MaskRegister::SHOW_SPRITES)
+            && (x >= 8 || mask.contains(MaskRegister::SHOW_SPRITES_LEFT));
+
+        let (bg_pixel, bg_palette) = if show_background {
+            self.renderer.background_pixel(self.fine_x)
         } else {
-            8
+            (0, 0)
         };
+        let sprite = if show_sprites { self.sprite_pixel(x) } else { None };
 
-        for sprite in (0..64).map(|i| &ppu.memory.oam[i*4..i*4+4]) {
-            let y = sprite[0] as i16 + 1;
-            if scanline >= y && scanline < y + sprite_height {
-                if self.scanline_sprites.len() < 8 {
-                    self.scanline_sprites.push(Sprite {
-                        y: sprite[0],
-                        tile: sprite[1],
-                        attributes: sprite[2],
-                        x: sprite[3],
-                        data_low: 0,
-                        data_high: 0,
-                    });
+        let (pixel, palette) = match sprite {
+            Some(sprite) => {
+                if sprite.sprite_zero && bg_pixel != 0 && x != 255 && self.registers.status & 0x40 == 0 {
+                    self.registers.status |= 0x40;
+                    self.log_event(PpuEventKind::SpriteZeroHit);
+                }
+                if bg_pixel != 0 && sprite.behind_background {
+                    (bg_pixel, bg_palette)
                 } else {
-                    ppu.registers.status |= 0x20; // Sprite overflow
-                    break;
+                    (sprite.pixel, sprite.palette)
                 }
             }
+            None => (bg_pixel, bg_palette),
+        };
+        self.renderer.back_buffer[self.scanline as usize * 256 + x] = self.palette_index(palette, pixel);
+    }
+
+    fn palette_index(&self, palette: u8, pixel: u8) -> u16 {
+        let entry = if pixel == 0 {
+            // With rendering off the backdrop comes from v when it points
+            // into palette RAM, which some games use to draw color bars
+            let addr = self.vram_addr & 0x3FFF;
+            if !self.rendering_enabled() && addr >= 0x3F00 {
+                self.memory.read_vram(addr)
+            } else {
+                self.memory.read_vram(0x3F00)
+            }
+        } else {
+            self.memory.read_vram(0x3F00 + (palette as u16) * 4 + pixel as u16)
+        };
+        // Grayscale keeps only the luma column; the emphasis bits select one
+        // of the palette's eight variants
+        let mask = self.registers.mask;
+        let color = if mask.contains(MaskRegister::GRAYSCALE) { entry & 0x30 } else { entry & 0x3F };
+        let mut emphasis = (mask.bits() & 0xE0) as u16;
+        // The 2C07 swaps the red and green emphasis bits
+        if self.region == Region::Pal {
+            emphasis = (emphasis & 0x80) | (emphasis & 0x20) << 1 | (emphasis & 0x40) >> 1;
         }
+        emphasis << 1 | color as u16
     }
 }
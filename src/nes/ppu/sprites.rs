@@ -0,0 +1,228 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use super::registers::ControlRegister;
+use super::Ppu;
+
+const SECONDARY_OAM_SIZE: usize = 32; // Eight sprites of four bytes
+
+/// One of the eight sprite output units, loaded during dots 257-320 for the
+/// next scanline
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sprite {
+    attributes: u8,
+    x: u8,
+    // Pattern row with horizontal flip already applied, leftmost pixel in bit 7
+    data_low: u8,
+    data_high: u8,
+}
+
+/// What a sprite contributes to the current pixel
+pub struct SpritePixel {
+    pub pixel: u8,
+    pub palette: u8,
+    pub behind_background: bool,
+    pub sprite_zero: bool,
+}
+
+/// State of the evaluation that scans OAM during dots 65-256, copying the
+/// sprites on the next scanline into secondary OAM
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpriteEvaluation {
+    n: usize,               // Sprite being examined in primary OAM
+    m: usize,               // Byte within it
+    secondary_index: usize, // Next free byte in secondary OAM
+    latch: u8,              // Byte read on the odd dot, written on the even one
+    done: bool,             // All 64 sprites examined
+    sprite_zero_found: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpriteState {
+    pub units: [Sprite; 8],
+    pub count: usize,
+    // Sprite 0 is in unit 0 for the line being drawn
+    pub sprite_zero_on_line: bool,
+    evaluation: SpriteEvaluation,
+}
+
+impl Ppu {
+    /// Sprite work for one dot of a visible or pre-render scanline, with
+    /// rendering enabled
+    pub(super) fn sprite_dot(&mut self) {
+        let cycle = self.cycle;
+        match cycle {
+            // Secondary OAM is cleared to $FF, one byte every two dots
+            1..=64 if self.scanline >= 0 && cycle.is_multiple_of(2) => {
+                self.memory.temp_oam[cycle / 2 - 1] = 0xFF;
+            }
+            65..=256 if self.scanline >= 0 => {
+                if cycle == 65 {
+                    self.sprites.evaluation = SpriteEvaluation::default();
+                }
+                if !cycle.is_multiple_of(2) {
+                    let eval = &mut self.sprites.evaluation;
+                    eval.latch = self.memory.oam[(eval.n * 4 + eval.m) & 0xFF];
+                } else {
+                    self.evaluate_sprite_byte();
+                }
+            }
+            257..=320 => {
+                self.registers.oam_addr = 0;
+                if (cycle - 257) % 8 == 7 {
+                    self.fetch_sprite((cycle - 257) / 8);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // OAMDATA as seen mid-render: $FF while secondary OAM is cleared, the
+    // byte evaluation just read, then the secondary OAM entry being fetched
+    pub(super) fn sprite_oam_bus(&self) -> u8 {
+        match self.cycle {
+            1..=64 => 0xFF,
+            65..=256 => self.sprites.evaluation.latch,
+            257..=320 => {
+                let dot = self.cycle - 257;
+                self.memory.temp_oam[dot / 8 * 4 + (dot % 8).min(3)]
+            }
+            _ => self.memory.temp_oam[0],
+        }
+    }
+
+    pub(super) fn sprite_height(&self) -> i16 {
+        if self.registers.control.contains(ControlRegister::SPRITE_SIZE) {
+            16
+        } else {
+            8
+        }
+    }
+
+    fn sprite_in_range(&self, y: u8) -> bool {
+        let row = self.scanline - y as i16;
+        (0..self.sprite_height()).contains(&row)
+    }
+
+    // Even dot of evaluation: act on the byte latched on the odd dot
+    fn evaluate_sprite_byte(&mut self) {
+        let latch = self.sprites.evaluation.latch;
+        let in_range = self.sprite_in_range(latch);
+        let eval = &mut self.sprites.evaluation;
+        if eval.done {
+            return;
+        }
+
+        if eval.secondary_index < SECONDARY_OAM_SIZE {
+            // The Y byte is copied whether or not the sprite is in range
+            self.memory.temp_oam[eval.secondary_index] = latch;
+            if eval.m == 0 {
+                if in_range {
+                    eval.sprite_zero_found |= eval.n == 0;
+                    eval.m = 1;
+                    eval.secondary_index += 1;
+                } else {
+                    eval.next_sprite();
+                }
+            } else {
+                eval.secondary_index += 1;
+                eval.m += 1;
+                if eval.m == 4 {
+                    eval.m = 0;
+                    eval.next_sprite();
+                }
+            }
+        } else {
+            // Secondary OAM is full: a ninth sprite in range sets overflow.
+            // The real PPU also bumps m on a miss, so it goes on to test
+            // tile, attribute and X bytes as Y coordinates, scanning OAM
+            // diagonally: false positives and missed overflows.
+            if in_range {
+                self.registers.status |= 0x20;
+                eval.done = true;
+            } else {
+                if self.sprite_overflow_bug {
+                    eval.m = (eval.m + 1) & 0x03;
+                }
+                eval.next_sprite();
+            }
+        }
+    }
+
+    // Dots 257-320 give each output unit eight dots to read its secondary
+    // OAM entry and fetch its pattern row. Empty units fetch tile $FF and
+    // stay transparent.
+    fn fetch_sprite(&mut self, unit: usize) {
+        if unit == 0 {
+            let found = self.sprites.evaluation.secondary_index / 4;
+            // Nothing is evaluated on the pre-render line, so line 0 has no sprites
+            self.sprites.count = if self.scanline >= 0 { found } else { 0 };
+            self.sprites.sprite_zero_on_line = self.scanline >= 0 && self.sprites.evaluation.sprite_zero_found;
+        }
+
+        let entry = &self.memory.temp_oam[unit * 4..unit * 4 + 4];
+        let (y, tile, attributes, x) = (entry[0], entry[1], entry[2], entry[3]);
+        let active = unit < self.sprites.count;
+
+        let mut row = if active { (self.scanline - y as i16) as u16 } else { 0 };
+        if attributes & 0x80 != 0 {
+            // Vertical flip covers the whole sprite, swapping 8x16 halves too
+            row = self.sprite_height() as u16 - 1 - row;
+        }
+        let tile = if active { tile } else { 0xFF };
+        let addr = self.sprite_pattern_addr(tile, row);
+        let mut data_low = self.fetch(addr);
+        let mut data_high = self.fetch(addr + 8);
+
+        if !active {
+            data_low = 0;
+            data_high = 0;
+        } else if attributes & 0x40 != 0 {
+            data_low = data_low.reverse_bits();
+            data_high = data_high.reverse_bits();
+        }
+        self.sprites.units[unit] = Sprite { attributes, x, data_low, data_high };
+    }
+
+    // 8x16 sprites ignore PPUCTRL's sprite table: bit 0 of the tile index
+    // picks the table and the top half uses the even tile, the bottom half
+    // the odd one
+    pub(super) fn sprite_pattern_addr(&self, tile: u8, row: u16) -> u16 {
+        if self.registers.control.contains(ControlRegister::SPRITE_SIZE) {
+            let table = ((tile & 0x01) as u16) << 12;
+            let tile = (tile & 0xFE) as u16 + (row >> 3);
+            table | tile << 4 | (row & 0x07)
+        } else {
+            let table = (self.registers.control.contains(ControlRegister::SPRITE_TABLE) as u16) << 12;
+            table | (tile as u16) << 4 | (row & 0x07)
+        }
+    }
+
+    /// The frontmost opaque sprite pixel at screen column `x`. Lower units
+    /// (lower OAM indices) win regardless of their background priority.
+    pub(super) fn sprite_pixel(&self, x: usize) -> Option<SpritePixel> {
+        self.sprites.units[..self.sprites.count].iter().enumerate().find_map(|(unit, sprite)| {
+            let offset = x.checked_sub(sprite.x as usize).filter(|&offset| offset < 8)?;
+            let shift = 7 - offset;
+            let pixel = ((sprite.data_high >> shift) & 1) << 1 | ((sprite.data_low >> shift) & 1);
+            (pixel != 0).then(|| SpritePixel {
+                pixel,
+                palette: 4 + (sprite.attributes & 0x03),
+                behind_background: sprite.attributes & 0x20 != 0,
+                sprite_zero: unit == 0 && self.sprites.sprite_zero_on_line,
+            })
+        })
+    }
+}
+
+impl SpriteEvaluation {
+    fn next_sprite(&mut self) {
+        self.n += 1;
+        if self.n == 64 {
+            self.done = true;
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// src/nes/ppu/video.rs
+// Turning finished frames of palette indices into pixels
+
+use super::ntsc::{NtscFilter, NTSC_WIDTH};
+use super::Ppu;
+
+/// How finished frames are turned into pixels; can change between frames
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VideoFilter {
+    /// Each PPU pixel straight through the palette, 256x240
+    #[default]
+    Rgb,
+    /// Composite NTSC artifacts, 512x240. Colors come from the signal
+    /// model, so a loaded .pal doesn't apply.
+    Ntsc,
+}
+
+/// A finished frame, `0x00RRGGBB` pixels in rows of `width`
+pub struct Frame<'a> {
+    pub pixels: &'a [u32],
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Byte layouts `Ppu::frame` can produce
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Four bytes a pixel: R, G, B, then A (always $FF)
+    #[default]
+    Rgba8888,
+    /// Native-endian `u16`s, 5 bits red, 6 green, 5 blue
+    Rgb565,
+    /// Native-endian `u16` palette indices (color | emphasis << 6), with
+    /// the 512 palette colors alongside as RGBA8888. Always the PPU's own
+    /// 256x240, whatever the video filter.
+    Indexed,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgba8888 => 4,
+            PixelFormat::Rgb565 | PixelFormat::Indexed => 2,
+        }
+    }
+}
+
+/// A finished frame as bytes, in rows of `width * format.bytes_per_pixel()`
+pub struct FrameBuffer<'a> {
+    pub format: PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub data: &'a [u8],
+    /// RGBA8888 colors for `PixelFormat::Indexed`
+    pub palette: Option<&'a [u8]>,
+}
+
+pub struct VideoOutput {
+    rgb: Vec<u32>,
+    ntsc: NtscFilter,
+    bytes: Vec<u8>,
+    palette: Vec<u8>,
+}
+
+impl VideoOutput {
+    pub fn new() -> Self {
+        Self {
+            rgb: vec![0; 256 * 240],
+            ntsc: NtscFilter::new(),
+            bytes: Vec::new(),
+            palette: Vec::new(),
+        }
+    }
+}
+
+fn rgba(rgb: u32) -> [u8; 4] {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    [r, g, b, 0xFF]
+}
+
+fn rgb565(rgb: u32) -> [u8; 2] {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    let pixel = (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3;
+    pixel.to_ne_bytes()
+}
+
+impl Ppu {
+    /// The last finished frame, run through `video_filter`
+    pub fn output_frame(&mut self) -> Frame<'_> {
+        let indices = &self.renderer.front_buffer;
+        match self.video_filter {
+            VideoFilter::Rgb => {
+                let rgb = &mut self.video.rgb;
+                for (pixel, &index) in rgb.iter_mut().zip(indices) {
+                    *pixel = self.palette.color(index);
+                }
+                Frame { pixels: rgb, width: 256, height: 240 }
+            }
+            VideoFilter::Ntsc => {
+                let pixels = self.video.ntsc.apply(indices, self.renderer.front_phase);
+                Frame { pixels, width: NTSC_WIDTH, height: 240 }
+            }
+        }
+    }
+
+    /// The last finished frame converted to `format`
+    pub fn frame(&mut self, format: PixelFormat) -> FrameBuffer<'_> {
+        if format == PixelFormat::Indexed {
+            let video = &mut self.video;
+            video.bytes.clear();
+            video.bytes.extend(self.renderer.front_buffer.iter().flat_map(|index| index.to_ne_bytes()));
+            video.palette.clear();
+            video.palette.extend((0..512).flat_map(|index| rgba(self.palette.color(index))));
+            return FrameBuffer {
+                format,
+                width: 256,
+                height: 240,
+                data: &video.bytes,
+                palette: Some(&video.palette),
+            };
+        }
+
+        let Frame { width, height, .. } = self.output_frame();
+        let video = &mut self.video;
+        let pixels = match self.video_filter {
+            VideoFilter::Rgb => &video.rgb,
+            VideoFilter::Ntsc => video.ntsc.output(),
+        };
+        video.bytes.clear();
+        match format {
+            PixelFormat::Rgb565 => video.bytes.extend(pixels.iter().flat_map(|&pixel| rgb565(pixel))),
+            _ => video.bytes.extend(pixels.iter().flat_map(|&pixel| rgba(pixel))),
+        }
+        FrameBuffer { format, width, height, data: &video.bytes, palette: None }
+    }
+}
@@ -0,0 +1,94 @@
+// src/nes/vs.rs
+// Vs. System mainboard: coin slots, DIP switches, extra work RAM and PPU register quirks
+
+use crate::nes::cart::VsPpu;
+
+const WORK_RAM_SIZE: usize = 0x0800; // 2KB at $6000-$7FFF, mirrored
+const FRAME_CPU_CYCLES: usize = 29780;
+// Coin switches stay closed long enough for a once-per-frame poll to see them
+const COIN_PULSE_CYCLES: usize = FRAME_CPU_CYCLES * 4;
+
+pub struct VsSystem {
+    ppu: VsPpu,
+    ram: [u8; WORK_RAM_SIZE],
+    /// DIP switches 1-8, switch 1 in bit 0
+    pub dip_switches: u8,
+    service: bool,
+    coin_timers: [usize; 2],
+}
+
+impl VsSystem {
+    pub fn new(ppu: VsPpu) -> Self {
+        Self {
+            ppu,
+            ram: [0; WORK_RAM_SIZE],
+            dip_switches: 0,
+            service: false,
+            coin_timers: [0; 2],
+        }
+    }
+
+    pub fn ppu(&self) -> VsPpu {
+        self.ppu
+    }
+
+    /// Drop a coin into slot 0 or 1
+    pub fn insert_coin(&mut self, slot: usize) {
+        if let Some(timer) = self.coin_timers.get_mut(slot) {
+            *timer = COIN_PULSE_CYCLES;
+        }
+    }
+
+    pub fn set_service(&mut self, pressed: bool) {
+        self.service = pressed;
+    }
+
+    /// Advance the coin switch timers by CPU cycles
+    pub fn clock(&mut self, cycles: usize) {
+        for timer in &mut self.coin_timers {
+            *timer = timer.saturating_sub(cycles);
+        }
+    }
+
+    pub fn read_ram(&self, addr: u16) -> u8 {
+        self.ram[addr as usize % WORK_RAM_SIZE]
+    }
+
+    pub fn write_ram(&mut self, addr: u16, data: u8) {
+        self.ram[addr as usize % WORK_RAM_SIZE] = data;
+    }
+
+    /// Mainboard bits of $4016: service (2), DIP 1-2 (3-4), coins (5-6)
+    pub fn read_4016(&self) -> u8 {
+        ((self.service as u8) << 2)
+            | ((self.dip_switches & 0x03) << 3)
+            | (((self.coin_timers[0] > 0) as u8) << 5)
+            | (((self.coin_timers[1] > 0) as u8) << 6)
+    }
+
+    /// Mainboard bits of $4017: DIP 3-8 (2-7)
+    pub fn read_4017(&self) -> u8 {
+        self.dip_switches & 0xFC
+    }
+
+    /// RC2C05 parts swap PPUCTRL and PPUMASK
+    pub fn ppu_register(&self, reg: u16) -> u16 {
+        match (self.ppu, reg) {
+            (VsPpu::Rc2c05(_), 0) => 1,
+            (VsPpu::Rc2c05(_), 1) => 0,
+            _ => reg,
+        }
+    }
+
+    /// RC2C05 parts report a revision ID in the low bits of PPUSTATUS,
+    /// which games check as copy protection
+    pub fn ppu_status(&self, status: u8) -> u8 {
+        let id = match self.ppu {
+            VsPpu::Rc2c05(1) | VsPpu::Rc2c05(4) => 0x1B,
+            VsPpu::Rc2c05(2) => 0x3D,
+            VsPpu::Rc2c05(3) => 0x1C,
+            _ => return status,
+        };
+        (status & 0xE0) | id
+    }
+}
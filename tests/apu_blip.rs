@@ -0,0 +1,144 @@
+// tests/apu_blip.rs
+// Band-limited synthesis: steps settle cleanly, ultrasonic edges don't alias,
+// and the output comes out at the rate asked for
+
+use alphanes::nes::apu::{Apu, BlipBuffer};
+use alphanes::nes::clock::Region;
+
+const CLOCK_RATE: f64 = 1_789_773.0;
+
+fn drain(blip: &mut BlipBuffer) -> Vec<f32> {
+    let mut out = vec![0.0; blip.samples_avail()];
+    let count = blip.read_samples(&mut out);
+    assert_eq!(count, out.len());
+    out
+}
+
+#[test]
+fn step_settles_on_its_delta() {
+    let mut blip = BlipBuffer::new(CLOCK_RATE, 48_000);
+    blip.add_delta(1000, 0.5);
+    blip.end_frame(10_000);
+    let samples = drain(&mut blip);
+    assert_eq!(samples.len(), 268);
+
+    // Quiet before the step, within a kernel's width of it, then flat
+    assert!(samples[..10].iter().all(|&s| s == 0.0));
+    assert!(samples[60..].iter().all(|&s| (s - 0.5).abs() < 1e-4), "{:?}", &samples[60..]);
+    let rise = samples.iter().position(|&s| s > 0.25).unwrap();
+    assert!((27..=60).contains(&rise), "step at {}", rise);
+}
+
+#[test]
+fn frames_continue_where_the_last_ended() {
+    let mut blip = BlipBuffer::new(CLOCK_RATE, 48_000);
+    let mut samples = Vec::new();
+    for _ in 0..100 {
+        blip.add_delta(0, 0.25);
+        blip.add_delta(500, -0.25);
+        blip.end_frame(1000);
+        samples.extend(drain(&mut blip));
+    }
+    // 100,000 clocks is 2681.8 samples; the fraction waits for the next frame
+    assert_eq!(samples.len(), 2681);
+    let mean = samples[100..].iter().sum::<f32>() / (samples.len() - 100) as f32;
+    assert!((mean - 0.125).abs() < 0.01, "mean {}", mean);
+}
+
+#[test]
+fn ultrasonic_square_averages_out_instead_of_aliasing() {
+    // A 35kHz square is past Nyquist at 48kHz. Point sampling would read
+    // only its two levels; the band-limited output keeps near the mean.
+    let mut blip = BlipBuffer::new(CLOCK_RATE, 48_000);
+    let half_period = CLOCK_RATE / 70_000.0;
+    let mut level = 0.0;
+    for edge in 0..7000 {
+        let delta = if level == 0.0 { 1.0 } else { -1.0 };
+        blip.add_delta((edge as f64 * half_period) as u32, delta);
+        level += delta;
+    }
+    blip.end_frame((7000.0 * half_period) as u32);
+    let samples = drain(&mut blip);
+    assert!(samples[50..samples.len() - 50].iter().all(|&s| (s - 0.5).abs() < 0.1));
+}
+
+#[test]
+fn apu_produces_samples_at_48khz() {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0xBF);
+    apu.write_register(0x4002, 0xFD);
+    apu.write_register(0x4003, 0x00);
+
+    let mut samples = Vec::new();
+    let mut out = [0.0; 256];
+    for _ in 0..178_977 {
+        apu.clock();
+        if apu.samples_avail() >= out.len() {
+            let count = apu.read_samples(&mut out);
+            samples.extend_from_slice(&out[..count]);
+        }
+    }
+    let count = apu.read_samples(&mut out);
+    samples.extend_from_slice(&out[..count]);
+
+    // A tenth of a second, less what's still in the last partial frame
+    assert!((4770..=4800).contains(&samples.len()), "{}", samples.len());
+    let peak = samples.iter().cloned().fold(0.0, f32::max);
+    assert!(peak > 0.1, "peak {}", peak);
+}
+
+#[rstest::rstest]
+#[case(Region::Ntsc, 44_100)]
+#[case(Region::Ntsc, 48_000)]
+#[case(Region::Pal, 44_100)]
+#[case(Region::Pal, 48_000)]
+fn take_samples_drains_at_the_configured_rate(#[case] region: Region, #[case] rate: u32) {
+    let mut apu = Apu::new(region);
+    apu.set_sample_rate(rate);
+    assert_eq!(apu.sample_rate(), rate);
+
+    // One emulated second in video-frame-sized slices
+    let cpu_hz = region.master_hz() as f64 / region.cpu_divider() as f64;
+    let frames = if region == Region::Ntsc { 60 } else { 50 };
+    let mut samples = Vec::new();
+    let mut clocked = 0;
+    for frame in 1..=frames {
+        let until = (cpu_hz * frame as f64 / frames as f64) as u64;
+        for _ in clocked..until {
+            apu.clock();
+        }
+        clocked = until;
+        let before = samples.len();
+        apu.take_samples(&mut samples);
+        let expected = rate as usize / frames;
+        assert!(samples.len() - before >= expected - 1 && samples.len() - before <= expected + 1);
+    }
+    assert!(samples.len() >= rate as usize - 1 && samples.len() <= rate as usize, "{}", samples.len());
+}
+
+#[test]
+fn changing_rate_keeps_the_output_level() {
+    // Unfiltered, so the level isn't high-passed away
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.set_filters(&[]);
+    apu.write_register(0x4015, 0x04);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400B, 0x08);
+    // Period 0 holds the triangle near mid-scale
+    for _ in 0..20_000 {
+        apu.clock();
+    }
+    let mut samples = Vec::new();
+    apu.take_samples(&mut samples);
+    let level = *samples.last().unwrap();
+    assert!(level > 0.1);
+
+    apu.set_sample_rate(44_100);
+    for _ in 0..20_000 {
+        apu.clock();
+    }
+    samples.clear();
+    apu.take_samples(&mut samples);
+    assert!((samples.last().unwrap() - level).abs() < 0.02, "{} vs {}", samples.last().unwrap(), level);
+}
@@ -0,0 +1,103 @@
+// tests/apu_expansion.rs
+// Expansion audio: the Sunsoft 5B on FME-7 boards and its mixing into the APU output
+
+use alphanes::nes::apu::AudioSource;
+use alphanes::nes::bus::NesBus;
+use alphanes::nes::cart::{self, Rom, SharedMapper};
+use alphanes::nes::clock::Region;
+use alphanes::nes::cpu::Bus;
+
+fn fme7() -> SharedMapper {
+    let mut image = vec![b'N', b'E', b'S', 0x1A, 2, 1, 0x50, 0x40];
+    image.resize(16 + 0x8000 + 0x2000, 0);
+    cart::new_mapper(Rom::from_bytes(&image).unwrap()).unwrap()
+}
+
+fn write_5b(cart: &SharedMapper, reg: u8, data: u8) {
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0xC000, reg);
+    cart.cpu_write(0xE000, data);
+}
+
+// The 5B's output after each of `cycles` CPU cycles
+fn outputs(cart: &SharedMapper, cycles: usize) -> Vec<f32> {
+    let mut cart = cart.borrow_mut();
+    let chip = cart.expansion_audio().unwrap();
+    (0..cycles)
+        .map(|_| {
+            chip.clock();
+            chip.output()
+        })
+        .collect()
+}
+
+#[test]
+fn tone_is_a_square_of_32_cpu_cycles_per_period_step() {
+    let cart = fme7();
+    assert_eq!(cart.borrow_mut().expansion_audio().unwrap().source(), AudioSource::Sunsoft5b);
+    write_5b(&cart, 0x00, 0x10);
+    write_5b(&cart, 0x07, 0x3E); // Tone A only
+    write_5b(&cart, 0x08, 0x0F);
+
+    // Edges every 16 prescaler ticks of 16 CPU cycles
+    let levels = outputs(&cart, 2048);
+    let edges: Vec<usize> = (1..levels.len()).filter(|&i| levels[i] != levels[i - 1]).collect();
+    assert_eq!(edges, [255, 511, 767, 1023, 1279, 1535, 1791, 2047]);
+    assert!((levels[300] - 0.15).abs() < 1e-6);
+    assert_eq!(levels[600], 0.0);
+}
+
+#[test]
+fn volume_steps_are_3db_apart() {
+    let cart = fme7();
+    write_5b(&cart, 0x07, 0x3F); // Everything disabled: the volume holds
+    write_5b(&cart, 0x08, 0x0F);
+    let loud = outputs(&cart, 1)[0];
+    write_5b(&cart, 0x08, 0x0E);
+    let softer = outputs(&cart, 1)[0];
+    assert!((20.0 * (loud / softer).log10() - 3.0).abs() < 0.01);
+    write_5b(&cart, 0x08, 0x00);
+    assert_eq!(outputs(&cart, 1)[0], 0.0);
+
+    // Out-of-range register numbers are ignored
+    write_5b(&cart, 0x18, 0x0F);
+    assert_eq!(outputs(&cart, 1)[0], 0.0);
+}
+
+#[test]
+fn attack_and_hold_envelope_ends_at_full_volume() {
+    let cart = fme7();
+    write_5b(&cart, 0x07, 0x3F);
+    write_5b(&cart, 0x08, 0x10);
+    write_5b(&cart, 0x0B, 0x01);
+    write_5b(&cart, 0x0D, 0x0D);
+
+    // 32 steps of one 16-cycle tick each, rising, then held
+    let levels = outputs(&cart, 16 * 40);
+    assert!(levels[..16 * 31].windows(2).all(|pair| pair[1] >= pair[0]));
+    assert!(levels[16 * 31..].iter().all(|&level| (level - 0.15).abs() < 1e-6));
+}
+
+#[test]
+fn bus_mixes_the_chip_in_at_its_volume() {
+    let cart = fme7();
+    let mut bus = NesBus::new(cart, None, Region::Ntsc);
+    bus.write(0xC000, 0x07);
+    bus.write(0xE000, 0x3F);
+    bus.write(0xC000, 0x08);
+    bus.write(0xE000, 0x0F);
+
+    // The idle triangle sits at 15, so measure against the 2A03 alone
+    bus.apu.set_expansion_volume(AudioSource::Sunsoft5b, 0.0);
+    bus.tick();
+    let internal = bus.apu.output();
+
+    bus.apu.set_expansion_volume(AudioSource::Sunsoft5b, 1.0);
+    bus.tick();
+    assert!((bus.apu.output() - internal - 0.15).abs() < 1e-6);
+
+    bus.apu.set_expansion_volume(AudioSource::Sunsoft5b, 0.5);
+    assert_eq!(bus.apu.expansion_volume(AudioSource::Sunsoft5b), 0.5);
+    bus.tick();
+    assert!((bus.apu.output() - internal - 0.075).abs() < 1e-6);
+}
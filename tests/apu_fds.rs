@@ -0,0 +1,135 @@
+// tests/apu_fds.rs
+// FDS sound unit: wavetable playback, envelopes, master volume and frequency modulation
+
+use alphanes::nes::apu::{AudioSource, ExpansionAudio};
+use alphanes::nes::cart::FdsAudio;
+
+// A rising ramp, 0-63 over the table
+fn ramp() -> FdsAudio {
+    let mut fds = FdsAudio::default();
+    fds.write(0x4089, 0x80);
+    for i in 0..64 {
+        fds.write(0x4040 + i, i as u8);
+    }
+    fds.write(0x4089, 0x00);
+    fds.write(0x4080, 0x80 | 0x20); // Gain 32, envelope off
+    fds
+}
+
+// Wave position after each of `cycles` CPU cycles, read back from the ramp
+fn positions(fds: &mut FdsAudio, cycles: usize) -> Vec<u8> {
+    (0..cycles)
+        .map(|_| {
+            fds.clock();
+            (fds.output() / fds_unit()).round() as u8
+        })
+        .collect()
+}
+
+// Output of one wave step at gain 32, master volume 2/2
+fn fds_unit() -> f32 {
+    let mut fds = ramp();
+    fds.write(0x4082, 0x00);
+    fds.write(0x4083, 0x01); // One step per 16 cycles
+    while fds.output() == 0.0 {
+        fds.clock();
+    }
+    fds.output()
+}
+
+#[test]
+fn wavetable_is_written_only_while_enabled_and_read_back() {
+    let mut fds = FdsAudio::default();
+    assert_eq!(fds.source(), AudioSource::Fds);
+    fds.write(0x4040, 0x3F);
+    assert_eq!(fds.read(0x4040), Some(0x00));
+    fds.write(0x4089, 0x80);
+    fds.write(0x4040, 0xFF);
+    assert_eq!(fds.read(0x4040), Some(0x3F));
+    assert_eq!(fds.read(0x4080), None);
+}
+
+#[test]
+fn wave_advances_at_frequency_over_65536_per_cycle() {
+    let mut fds = ramp();
+    fds.write(0x4082, 0x00);
+    fds.write(0x4083, 0x02); // $200: a step every 128 cycles
+    let steps = positions(&mut fds, 128 * 5);
+    let edges: Vec<usize> = (1..steps.len()).filter(|&i| steps[i] != steps[i - 1]).collect();
+    assert_eq!(edges, [127, 255, 383, 511, 639]);
+    assert_eq!(steps[128 * 5 - 2], 4);
+}
+
+#[test]
+fn halting_the_wave_resets_its_position() {
+    let mut fds = ramp();
+    fds.write(0x4083, 0x0F);
+    positions(&mut fds, 1000);
+    fds.write(0x4083, 0x80);
+    assert!(positions(&mut fds, 100).iter().all(|&step| step == 0));
+}
+
+#[test]
+fn volume_envelope_ramps_gain_and_reports_it() {
+    let mut fds = ramp();
+    fds.write(0x408A, 0x01);
+    fds.write(0x4080, 0x40); // Increase, speed 0: a step every 8 cycles
+    fds.write(0x4083, 0x00);
+    assert_eq!(fds.read(0x4090), Some(0x20));
+    fds.write(0x4080, 0x00); // Decrease
+    for _ in 0..8 * 4 {
+        fds.clock();
+    }
+    assert_eq!(fds.read(0x4090), Some(0x1C));
+
+    // Halting envelopes through $4083 freezes it
+    fds.write(0x4083, 0x40);
+    for _ in 0..100 {
+        fds.clock();
+    }
+    assert_eq!(fds.read(0x4090), Some(0x1C));
+}
+
+#[test]
+fn master_volume_scales_output() {
+    let mut fds = FdsAudio::default();
+    fds.write(0x4089, 0x80);
+    for i in 0..64 {
+        fds.write(0x4040 + i, 0x3F);
+    }
+    fds.write(0x4080, 0x80 | 0x3F); // Gain 63 plays as 32
+    fds.write(0x4083, 0x00);
+
+    // The table is still writable, so the output holds its power-on 0
+    fds.clock();
+    assert_eq!(fds.output(), 0.0);
+
+    for (volume, scale) in [(0, 1.0), (1, 2.0 / 3.0), (2, 0.5), (3, 0.4)] {
+        fds.write(0x4089, volume);
+        fds.clock();
+        assert!((fds.output() - 0.36 * scale).abs() < 1e-4, "{}", fds.output());
+    }
+}
+
+#[test]
+fn modulation_bends_the_pitch() {
+    let mut plain = ramp();
+    plain.write(0x4082, 0x00);
+    plain.write(0x4083, 0x04);
+    let unmodulated = positions(&mut plain, 4096).last().copied().unwrap();
+
+    // A table of +1 steps raises the counter and with it the pitch
+    let mut fds = ramp();
+    fds.write(0x4087, 0x80);
+    for _ in 0..32 {
+        fds.write(0x4088, 0x01);
+    }
+    fds.write(0x4084, 0x80 | 0x3F); // Mod gain 63
+    fds.write(0x4085, 0x00);
+    fds.write(0x4086, 0x00);
+    fds.write(0x4087, 0x08);
+    fds.write(0x4082, 0x00);
+    fds.write(0x4083, 0x04);
+    let modulated = positions(&mut fds, 4096).last().copied().unwrap();
+    assert!(modulated > unmodulated, "{} vs {}", modulated, unmodulated);
+}
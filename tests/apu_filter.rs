@@ -0,0 +1,76 @@
+// tests/apu_filter.rs
+// Output filters: NES defaults, DC removal, low-pass attenuation and raw output
+
+use alphanes::nes::apu::{Apu, Filter};
+use alphanes::nes::clock::Region;
+
+// `seconds` of output after `setup`, with `filters` in place
+fn render(filters: &[Filter], seconds: f64, setup: impl Fn(&mut Apu)) -> Vec<f32> {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.set_filters(filters);
+    setup(&mut apu);
+    let mut samples = Vec::new();
+    for _ in 0..(1_789_773.0 * seconds) as usize {
+        apu.clock();
+    }
+    apu.take_samples(&mut samples);
+    samples
+}
+
+// The triangle parked at period 0 gives a constant level
+fn dc(apu: &mut Apu) {
+    apu.write_register(0x4015, 0x04);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400B, 0x08);
+}
+
+// Pulse 1 at about 11kHz, constant volume 15
+fn whine(apu: &mut Apu) {
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0xBF);
+    apu.write_register(0x4002, 0x09);
+    apu.write_register(0x4003, 0x08);
+}
+
+fn swing(samples: &[f32]) -> f32 {
+    let tail = &samples[samples.len() / 2..];
+    tail.iter().cloned().fold(f32::MIN, f32::max) - tail.iter().cloned().fold(f32::MAX, f32::min)
+}
+
+#[test]
+fn nes_filters_are_the_default_and_survive_rate_changes() {
+    let mut apu = Apu::new(Region::Ntsc);
+    assert_eq!(apu.filters(), Filter::NES);
+    apu.set_sample_rate(44_100);
+    assert_eq!(apu.filters(), Filter::NES);
+    apu.set_filters(&Filter::FAMICOM);
+    apu.set_sample_rate(48_000);
+    assert_eq!(apu.filters(), Filter::FAMICOM);
+}
+
+#[test]
+fn high_pass_removes_a_constant_level() {
+    let raw = render(&[], 0.25, dc);
+    let filtered = render(&Filter::NES, 0.25, dc);
+    assert!(*raw.last().unwrap() > 0.1);
+    assert!(filtered.last().unwrap().abs() < 0.001, "{}", filtered.last().unwrap());
+}
+
+#[test]
+fn famicom_high_pass_lets_more_bass_through() {
+    // How much of the step into the constant level comes through over
+    // 20ms: the 37Hz stage decays over milliseconds, the NES's 440Hz stage
+    // in a fraction of one
+    let area = |samples: Vec<f32>| samples.iter().sum::<f32>().abs();
+    let nes = area(render(&Filter::NES, 0.02, dc));
+    let famicom = area(render(&Filter::FAMICOM, 0.02, dc));
+    assert!(famicom > nes * 5.0, "{} vs {}", famicom, nes);
+}
+
+#[test]
+fn low_pass_attenuates_high_frequencies() {
+    let raw = swing(&render(&[], 0.05, whine));
+    let filtered = swing(&render(&[Filter::LowPass(1_000.0)], 0.05, whine));
+    assert!(raw > 0.1);
+    assert!(filtered < raw / 5.0, "{} vs {}", filtered, raw);
+}
@@ -0,0 +1,163 @@
+// tests/apu_frame_counter.rs
+// 4-step and 5-step frame counter sequences, the clocks they hand out, the
+// frame IRQ and the $4017 write delay
+
+use alphanes::nes::apu::{Apu, FrameClock, FrameCounter};
+use alphanes::nes::clock::Region;
+
+// Write $4017 and run the cycle it takes effect on
+fn restart(counter: &mut FrameCounter, data: u8) -> FrameClock {
+    counter.write(data, 1);
+    counter.clock()
+}
+
+// CPU cycles (counted from the restart) on which a step landed, with
+// whether it was a half frame, over `cycles` cycles
+fn steps(counter: &mut FrameCounter, cycles: u32) -> Vec<(u32, bool)> {
+    (1..=cycles)
+        .filter_map(|cycle| {
+            let clock = counter.clock();
+            clock.quarter.then_some((cycle, clock.half))
+        })
+        .collect()
+}
+
+#[test]
+fn four_step_sequence_repeats_every_29830_cycles() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    assert_eq!(restart(&mut counter, 0x00), FrameClock::default());
+    assert_eq!(
+        steps(&mut counter, 29830 * 2),
+        [
+            (7457, false),
+            (14913, true),
+            (22371, false),
+            (29829, true),
+            (29830 + 7457, false),
+            (29830 + 14913, true),
+            (29830 + 22371, false),
+            (29830 + 29829, true),
+        ]
+    );
+}
+
+#[test]
+fn five_step_sequence_clocks_on_the_write_and_skips_the_fourth_step() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    assert_eq!(restart(&mut counter, 0x80), FrameClock { quarter: true, half: true });
+    assert!(counter.five_step());
+    assert_eq!(
+        steps(&mut counter, 37282 + 7457),
+        [(7457, false), (14913, true), (22371, false), (37281, true), (37282 + 7457, false)]
+    );
+}
+
+#[test]
+fn pal_steps_are_further_apart() {
+    let mut counter = FrameCounter::new(Region::Pal);
+    restart(&mut counter, 0x40);
+    assert!(counter.irq_inhibit());
+    assert_eq!(
+        steps(&mut counter, 33254),
+        [(8313, false), (16627, true), (24939, false), (33253, true)]
+    );
+}
+
+#[test]
+fn writing_restarts_the_sequence() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x00);
+    steps(&mut counter, 7000);
+    restart(&mut counter, 0x00);
+    assert_eq!(steps(&mut counter, 7457)[..], [(7457, false)]);
+}
+
+#[test]
+fn writes_take_effect_after_the_delay() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x00);
+    counter.write(0x80, 3);
+    assert_eq!(counter.clock(), FrameClock::default());
+    assert_eq!(counter.clock(), FrameClock::default());
+    assert!(!counter.five_step());
+    assert_eq!(counter.clock(), FrameClock { quarter: true, half: true });
+    assert!(counter.five_step());
+}
+
+#[test]
+fn four_step_sequence_raises_the_irq_over_its_last_three_cycles() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x00);
+    let raised: Vec<u32> = (1..=29831)
+        .filter(|_| {
+            counter.clock();
+            let flag = counter.irq_flag();
+            counter.clear_irq();
+            flag
+        })
+        .collect();
+    assert_eq!(raised, [29828, 29829, 29830]);
+
+    // Setting the inhibit bit drops a raised flag at once
+    steps(&mut counter, 29828);
+    assert!(counter.irq_flag());
+    counter.write(0x40, 3);
+    assert!(!counter.irq_flag());
+}
+
+#[test]
+fn five_step_sequence_never_raises_the_irq() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x80);
+    steps(&mut counter, 37282 * 2);
+    assert!(!counter.irq_flag());
+}
+
+#[test]
+fn five_step_write_on_a_step_clocks_once() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x00);
+    steps(&mut counter, 7454);
+    counter.write(0x80, 3);
+    assert_eq!(counter.clock(), FrameClock::default());
+    assert_eq!(counter.clock(), FrameClock::default());
+    // The quarter frame at 7457, and no extra clock from the write
+    assert_eq!(counter.clock(), FrameClock { quarter: true, half: false });
+    assert!(counter.five_step());
+    assert_eq!(steps(&mut counter, 7457)[..], [(7457, false)]);
+}
+
+#[test]
+fn five_step_write_just_after_a_step_clocks_nothing() {
+    let mut counter = FrameCounter::new(Region::Ntsc);
+    restart(&mut counter, 0x00);
+    steps(&mut counter, 7454);
+    counter.write(0x80, 4);
+    let clocks: Vec<FrameClock> = (0..4).map(|_| counter.clock()).collect();
+    assert_eq!(clocks[2], FrameClock { quarter: true, half: false });
+    assert_eq!(clocks[3], FrameClock::default());
+    assert!(counter.five_step());
+
+    // Two cycles on, a write clocks again
+    steps(&mut counter, 100);
+    assert_eq!(restart(&mut counter, 0x80), FrameClock { quarter: true, half: true });
+}
+
+#[test]
+fn write_delay_follows_the_apu_cycle() {
+    // 3 cycles for a write on the first half of an APU cycle, 4 on the second
+    for (lead_in, delay) in [(0, 3), (1, 4), (2, 3), (3, 4)] {
+        let mut apu = Apu::new(Region::Ntsc);
+        for _ in 0..lead_in {
+            apu.clock();
+        }
+        apu.write_register(0x4017, 0x80);
+        let cycles = (1..=5)
+            .find(|_| {
+                apu.clock();
+                apu.frame_counter.five_step()
+            })
+            .unwrap();
+        assert_eq!(cycles, delay, "after {} cycles", lead_in);
+    }
+}
@@ -0,0 +1,91 @@
+// tests/apu_mute.rs
+// Muting and soloing channels at the mixer
+
+use alphanes::nes::apu::{Apu, AudioSource, Channel};
+use alphanes::nes::clock::Region;
+
+// Pulse 1 and noise at constant volume 15 and the triangle stopped
+// on step 0, so every channel has a steady level to take away
+fn apu() -> Apu {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4015, 0x0F);
+    apu.write_register(0x4000, 0xFF); // 75% duty: high on most steps
+    apu.write_register(0x4002, 0xFF);
+    apu.write_register(0x4003, 0x01);
+    apu.write_register(0x400C, 0x3F);
+    apu.write_register(0x400F, 0x08);
+    apu
+}
+
+// Output with the pulse high and the noise shift register's bit 0 clear
+fn steady(apu: &mut Apu) -> f32 {
+    while apu.pulse[0].output() == 0 || apu.noise.output() == 0 {
+        apu.clock();
+    }
+    apu.output()
+}
+
+#[test]
+fn muted_channels_drop_out_of_the_mix() {
+    let mut apu = apu();
+    let all = steady(&mut apu);
+
+    apu.set_muted(Channel::Pulse1, true);
+    assert!(apu.muted(Channel::Pulse1));
+    assert!(!apu.audible(Channel::Pulse1));
+    let without_pulse = apu.output();
+    assert!(without_pulse < all);
+
+    for channel in [Channel::Triangle, Channel::Noise] {
+        apu.set_muted(channel, true);
+    }
+    assert_eq!(apu.output(), 0.0);
+
+    // The channels kept running and come straight back
+    apu.set_muted(Channel::Pulse1, false);
+    apu.set_muted(Channel::Triangle, false);
+    apu.set_muted(Channel::Noise, false);
+    assert_eq!(apu.output(), all);
+}
+
+#[test]
+fn solo_leaves_only_soloed_channels() {
+    let mut apu = apu();
+    steady(&mut apu);
+    apu.set_muted(Channel::Triangle, true);
+    apu.set_muted(Channel::Noise, true);
+    let pulse_only = apu.output();
+    apu.unmute_all();
+
+    apu.set_solo(Channel::Pulse1, true);
+    assert!(apu.soloed(Channel::Pulse1));
+    assert!(!apu.audible(Channel::Noise));
+    assert_eq!(apu.output(), pulse_only);
+
+    // A muted solo stays silent
+    apu.set_muted(Channel::Pulse1, true);
+    assert_eq!(apu.output(), 0.0);
+
+    apu.unmute_all();
+    assert!(Channel::INTERNAL.iter().all(|&channel| apu.audible(channel)));
+}
+
+#[test]
+fn expansion_sources_mute_and_solo_by_chip() {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.set_muted(Channel::Triangle, true);
+    apu.mix_expansion(AudioSource::Sunsoft5b, 0.2);
+    assert_eq!(apu.output(), 0.2);
+
+    apu.set_muted(Channel::Expansion(AudioSource::Sunsoft5b), true);
+    apu.mix_expansion(AudioSource::Sunsoft5b, 0.2);
+    assert_eq!(apu.output(), 0.0);
+
+    // Soloing another chip silences this one too
+    apu.unmute_all();
+    apu.set_muted(Channel::Triangle, true);
+    apu.set_solo(Channel::Expansion(AudioSource::Fds), true);
+    apu.mix_expansion(AudioSource::Sunsoft5b, 0.2);
+    assert_eq!(apu.output(), 0.0);
+    assert!(apu.audible(Channel::Expansion(AudioSource::Fds)));
+}
@@ -0,0 +1,74 @@
+// tests/apu_noise.rs
+// Noise channel LFSR modes, period tables and length counter
+
+use alphanes::nes::apu::Apu;
+use alphanes::nes::clock::Region;
+
+fn apu(region: Region) -> Apu {
+    let mut apu = Apu::new(region);
+    apu.write_register(0x4015, 0x08);
+    apu.write_register(0x400C, 0x3F); // Constant volume 15, length halted
+    apu.write_register(0x400F, 0x08);
+    apu
+}
+
+// One output sample per LFSR step at the fastest period (4 CPU cycles)
+fn steps(apu: &mut Apu, count: usize) -> Vec<u8> {
+    (0..count)
+        .map(|_| {
+            for _ in 0..4 {
+                apu.clock();
+            }
+            apu.noise.output()
+        })
+        .collect()
+}
+
+// Smallest shift at which `samples` repeats
+fn period(samples: &[u8]) -> usize {
+    (1..samples.len() / 2).find(|&shift| samples[shift..] == samples[..samples.len() - shift]).unwrap()
+}
+
+#[test]
+fn long_mode_repeats_every_32767_steps() {
+    let mut apu = apu(Region::Ntsc);
+    apu.write_register(0x400E, 0x00);
+    let samples = steps(&mut apu, 32767 * 2 + 10);
+    assert_eq!(period(&samples), 32767);
+    assert!(samples.iter().all(|&level| level == 0 || level == 15));
+}
+
+#[test]
+fn short_mode_repeats_every_93_steps() {
+    let mut apu = apu(Region::Ntsc);
+    apu.write_register(0x400E, 0x80);
+    // Let the register settle into the short loop first
+    steps(&mut apu, 200);
+    let samples = steps(&mut apu, 93 * 4);
+    assert_eq!(period(&samples), 93);
+}
+
+#[test]
+fn period_tables_follow_the_region() {
+    let mut ntsc = apu(Region::Ntsc);
+    let mut pal = apu(Region::Pal);
+    for apu in [&mut ntsc, &mut pal] {
+        apu.write_register(0x400E, 0x0D);
+    }
+    assert_eq!(ntsc.noise.period(), 1016);
+    assert_eq!(pal.noise.period(), 944);
+
+    ntsc.set_region(Region::Pal);
+    assert_eq!(ntsc.noise.period(), 944);
+}
+
+#[test]
+fn length_counter_silences_the_channel() {
+    let mut apu = apu(Region::Ntsc);
+    apu.write_register(0x400C, 0x1F); // Counting down this time
+    apu.write_register(0x400F, 0x18); // Two half frames
+    let samples = steps(&mut apu, 29830 / 4 + 1);
+    assert!(samples.contains(&15));
+    assert!(!apu.noise.active());
+    assert!(steps(&mut apu, 100).iter().all(|&level| level == 0));
+}
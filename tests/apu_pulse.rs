@@ -0,0 +1,99 @@
+// tests/apu_pulse.rs
+// Pulse channel duty, envelope, length counter and sweep behavior
+
+use alphanes::nes::apu::Apu;
+use alphanes::nes::clock::Region;
+
+fn apu() -> Apu {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4015, 0x03);
+    apu
+}
+
+// Pulse `n`'s output after each of `cycles` CPU cycles
+fn outputs(apu: &mut Apu, n: usize, cycles: usize) -> Vec<u8> {
+    (0..cycles)
+        .map(|_| {
+            apu.clock();
+            apu.pulse[n].output()
+        })
+        .collect()
+}
+
+#[test]
+fn duty_sequence_steps_every_period_plus_one_apu_cycles() {
+    let mut apu = apu();
+    apu.write_register(0x4000, 0xBF); // 50%, constant volume 15
+    apu.write_register(0x4002, 0x08);
+    apu.write_register(0x4003, 0x08);
+
+    // Eight steps of 9 APU cycles, 18 CPU cycles apiece: 0 1 1 1 1 0 0 0
+    let levels: Vec<u8> = outputs(&mut apu, 0, 8 * 18).into_iter().step_by(18).collect();
+    assert_eq!(levels.len(), 8);
+    assert_eq!(levels.iter().filter(|&&level| level == 15).count(), 4);
+    assert!(levels.iter().all(|&level| level == 0 || level == 15));
+}
+
+#[test]
+fn envelope_decays_each_quarter_frame() {
+    let mut apu = apu();
+    apu.write_register(0x4000, 0xC0); // 25% negated (mostly high), decaying, divider 0
+    apu.write_register(0x4002, 0x40);
+    apu.write_register(0x4003, 0x08);
+    let highest = |levels: Vec<u8>| levels.into_iter().max().unwrap();
+
+    assert_eq!(highest(outputs(&mut apu, 0, 7000)), 0);
+    // The first quarter frame starts the decay at 15, then it steps down
+    assert_eq!(highest(outputs(&mut apu, 0, 1000)), 15);
+    outputs(&mut apu, 0, 7000);
+    assert_eq!(highest(outputs(&mut apu, 0, 1000)), 14);
+}
+
+#[test]
+fn length_counter_silences_the_channel() {
+    let mut apu = apu();
+    apu.write_register(0x4004, 0xDF);
+    apu.write_register(0x4006, 0x40);
+    apu.write_register(0x4007, 0x18); // Length index 3: two half frames
+    assert!(apu.pulse[1].active());
+
+    outputs(&mut apu, 1, 29830);
+    assert!(!apu.pulse[1].active());
+    assert!(outputs(&mut apu, 1, 1000).iter().all(|&level| level == 0));
+
+    // Disabled channels don't load at all
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4007, 0x08);
+    assert!(!apu.pulse[1].active());
+}
+
+#[test]
+fn sweep_down_differs_between_the_two_pulses() {
+    let mut apu = apu();
+    for (base, n) in [(0x4000, 0), (0x4004, 1)] {
+        apu.write_register(base, 0xBF);
+        apu.write_register(base + 1, 0x89); // Enabled, period 0, negate, shift 1
+        apu.write_register(base + 2, 0x00);
+        apu.write_register(base + 3, 0x09); // Timer $100
+        assert_eq!(apu.pulse[n].period(), 0x100);
+    }
+
+    // Half frame at 14913
+    outputs(&mut apu, 0, 14913);
+    assert_eq!(apu.pulse[0].period(), 0x7F);
+    assert_eq!(apu.pulse[1].period(), 0x80);
+}
+
+#[test]
+fn sweep_overflow_mutes_even_with_sweeping_off() {
+    let mut apu = apu();
+    apu.write_register(0x4000, 0xFF);
+    apu.write_register(0x4001, 0x01); // Disabled, shift 1
+    apu.write_register(0x4002, 0x00);
+    apu.write_register(0x4003, 0x0E); // Timer $600: target $900
+    // A whole duty cycle is about 25000 CPU cycles
+    assert!(outputs(&mut apu, 0, 25000).iter().all(|&level| level == 0));
+
+    apu.write_register(0x4001, 0x08); // Negated, the target fits
+    assert!(outputs(&mut apu, 0, 25000).contains(&15));
+}
@@ -0,0 +1,73 @@
+// tests/apu_state.rs
+// APU save states: a restored APU carries on exactly as the original
+
+use alphanes::nes::apu::{Apu, Ultrasonic};
+use alphanes::nes::clock::Region;
+
+// Every channel sounding, a sweep running and the frame counter mid-sequence
+fn busy_apu() -> Apu {
+    let mut apu = Apu::new(Region::Ntsc);
+    for (addr, data) in [
+        (0x4015, 0x0F),
+        (0x4000, 0x9F),
+        (0x4001, 0x9A),
+        (0x4002, 0x80),
+        (0x4003, 0x02),
+        (0x4004, 0x47),
+        (0x4006, 0x40),
+        (0x4007, 0x01),
+        (0x4008, 0xC0),
+        (0x400A, 0x50),
+        (0x400B, 0x00),
+        (0x400C, 0x04),
+        (0x400E, 0x83),
+        (0x400F, 0x08),
+        (0x4017, 0x00),
+    ] {
+        apu.write_register(addr, data);
+    }
+    for _ in 0..12_345 {
+        apu.clock();
+    }
+    apu
+}
+
+#[test]
+fn restored_apu_runs_identically() {
+    let mut original = busy_apu();
+    let state = original.save_state();
+
+    let mut restored = Apu::new(Region::Ntsc);
+    restored.load_state(&state);
+    assert_eq!(restored.save_state(), state);
+
+    for _ in 0..100_000 {
+        original.clock();
+        restored.clock();
+        assert_eq!(restored.output(), original.output());
+        assert_eq!(restored.irq_asserted(), original.irq_asserted());
+    }
+    assert_eq!(restored.save_state(), original.save_state());
+    assert_eq!(restored.read_status(), original.read_status());
+}
+
+#[test]
+fn loading_keeps_the_output_settings() {
+    let state = busy_apu().save_state();
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.triangle.set_ultrasonic(Ultrasonic::Fade);
+    apu.set_sample_rate(44_100);
+    apu.set_filters(&[]);
+    apu.load_state(&state);
+    assert_eq!(apu.triangle.ultrasonic(), Ultrasonic::Fade);
+    assert_eq!(apu.sample_rate(), 44_100);
+    assert!(apu.filters().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn state_round_trips_through_serde() {
+    let state = busy_apu().save_state();
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<alphanes::nes::apu::ApuState>(&json).unwrap(), state);
+}
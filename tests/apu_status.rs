@@ -0,0 +1,63 @@
+// tests/apu_status.rs
+// $4015 reads: length counter status, the frame IRQ and its acknowledgement
+
+use alphanes::nes::apu::Apu;
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::clock::Region;
+use alphanes::nes::cpu::Bus;
+use alphanes::nes::bus::NesBus;
+
+fn run(apu: &mut Apu, cycles: usize) {
+    for _ in 0..cycles {
+        apu.clock();
+    }
+}
+
+#[test]
+fn status_reports_running_length_counters() {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4015, 0x0F);
+    apu.write_register(0x4003, 0x08);
+    apu.write_register(0x400B, 0x08);
+    assert_eq!(apu.read_status(), 0x05);
+
+    apu.write_register(0x4007, 0x08);
+    apu.write_register(0x400F, 0x08);
+    assert_eq!(apu.read_status(), 0x0F);
+
+    apu.write_register(0x4015, 0x0E);
+    assert_eq!(apu.read_status(), 0x0E);
+}
+
+#[test]
+fn reading_status_acknowledges_the_frame_irq() {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4017, 0x00);
+    run(&mut apu, 29834);
+    assert!(apu.irq_asserted());
+    assert_eq!(apu.peek_status(), 0x40);
+    assert!(apu.irq_asserted());
+
+    assert_eq!(apu.read_status(), 0x40);
+    assert!(!apu.irq_asserted());
+    assert_eq!(apu.read_status(), 0x00);
+}
+
+#[test]
+fn status_reads_keep_bit_5_of_the_open_bus() {
+    let mut image = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0];
+    image.resize(16 + 0x4000 + 0x2000, 0);
+    let cart = cart::new_mapper(Rom::from_bytes(&image).unwrap()).unwrap();
+    let mut bus = NesBus::new(cart, None, Region::Ntsc);
+    bus.write(0x4015, 0x01);
+    bus.write(0x4003, 0xFF);
+    assert_eq!(bus.read(0x4015), 0x21);
+    // The frame IRQ reaches the CPU's /IRQ line
+    bus.write(0x4017, 0x00);
+    for _ in 0..29834 {
+        bus.tick();
+    }
+    assert!(bus.irq_asserted());
+    assert_eq!(bus.read(0x4015) & 0x40, 0x40);
+    assert!(!bus.irq_asserted());
+}
@@ -0,0 +1,108 @@
+// tests/apu_stereo.rs
+// Stereo panning: per-channel pan positions and interleaved left/right output
+
+use alphanes::nes::apu::{Apu, AudioSource, Channel};
+use alphanes::nes::clock::Region;
+
+// Pulse 1 at full constant volume, everything else silent
+fn pulse_only() -> Apu {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.set_filters(&[]);
+    apu.write_register(0x4015, 0x01);
+    apu.write_register(0x4000, 0xBF);
+    apu.write_register(0x4002, 0xFD);
+    apu.write_register(0x4003, 0x01);
+    for channel in [Channel::Pulse2, Channel::Triangle, Channel::Noise] {
+        apu.set_muted(channel, true);
+    }
+    apu
+}
+
+fn run(apu: &mut Apu, cycles: u32) -> Vec<f32> {
+    for _ in 0..cycles {
+        apu.clock();
+    }
+    let mut samples = Vec::new();
+    apu.take_samples(&mut samples);
+    samples
+}
+
+#[test]
+fn mono_by_default_and_centered() {
+    let apu = Apu::new(Region::Ntsc);
+    assert!(!apu.stereo());
+    for channel in Channel::INTERNAL {
+        assert_eq!(apu.pan(channel), 0.0);
+    }
+}
+
+#[test]
+fn centered_stereo_duplicates_the_mono_mix() {
+    let mut mono = pulse_only();
+    let mut stereo = pulse_only();
+    stereo.set_stereo(true);
+
+    let mono = run(&mut mono, 30_000);
+    let stereo = run(&mut stereo, 30_000);
+    assert_eq!(stereo.len(), mono.len() * 2);
+    for (sample, frame) in mono.iter().zip(stereo.chunks(2)) {
+        assert_eq!(frame, [*sample, *sample]);
+    }
+}
+
+#[test]
+fn hard_left_is_silent_on_the_right() {
+    let mut apu = pulse_only();
+    apu.set_stereo(true);
+    apu.set_pan(Channel::Pulse1, -1.0);
+
+    let samples = run(&mut apu, 30_000);
+    let left = samples.iter().step_by(2).fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let right = samples.iter().skip(1).step_by(2).fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(left > 0.05, "left peak {}", left);
+    assert_eq!(right, 0.0);
+}
+
+#[test]
+fn partial_pan_attenuates_the_far_side() {
+    let mut apu = pulse_only();
+    apu.set_stereo(true);
+    apu.set_pan(Channel::Pulse1, 0.5);
+
+    let samples = run(&mut apu, 30_000);
+    let left = samples.iter().step_by(2).fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let right = samples.iter().skip(1).step_by(2).fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(left > 0.0 && left < right * 0.6, "left {} right {}", left, right);
+}
+
+#[test]
+fn pan_is_clamped_and_covers_expansion_audio() {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.set_pan(Channel::Noise, 3.0);
+    assert_eq!(apu.pan(Channel::Noise), 1.0);
+
+    let fds = Channel::Expansion(AudioSource::Fds);
+    apu.set_filters(&[]);
+    apu.set_stereo(true);
+    apu.set_pan(fds, 1.0);
+    for channel in Channel::INTERNAL {
+        apu.set_muted(channel, true);
+    }
+    for _ in 0..30_000 {
+        apu.mix_expansion(AudioSource::Fds, 0.2);
+        apu.clock();
+    }
+    let mut samples = Vec::new();
+    apu.take_samples(&mut samples);
+    let last = &samples[samples.len() - 2..];
+    assert_eq!(last[0], 0.0);
+    assert!((last[1] - 0.2).abs() < 0.01, "right {}", last[1]);
+}
+
+#[test]
+fn channels_by_config_name() {
+    assert_eq!(Channel::named("pulse1"), Some(Channel::Pulse1));
+    assert_eq!(Channel::named("Triangle"), Some(Channel::Triangle));
+    assert_eq!(Channel::named("5b"), Some(Channel::Expansion(AudioSource::Sunsoft5b)));
+    assert_eq!(Channel::named("dmc"), None);
+}
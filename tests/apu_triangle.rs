@@ -0,0 +1,110 @@
+// tests/apu_triangle.rs
+// Triangle channel sequencer, linear counter and ultrasonic periods
+
+use alphanes::nes::apu::{Apu, Ultrasonic};
+use alphanes::nes::clock::Region;
+
+fn apu() -> Apu {
+    let mut apu = Apu::new(Region::Ntsc);
+    apu.write_register(0x4015, 0x04);
+    apu
+}
+
+fn outputs(apu: &mut Apu, cycles: usize) -> Vec<u8> {
+    (0..cycles)
+        .map(|_| {
+            apu.clock();
+            apu.triangle.output()
+        })
+        .collect()
+}
+
+#[test]
+fn sequencer_waits_for_the_linear_counter() {
+    let mut apu = apu();
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x03);
+    apu.write_register(0x400B, 0x08);
+    assert!(outputs(&mut apu, 7456).iter().all(|&level| level == 15));
+
+    // Loaded on the first quarter frame, then a step every 4 CPU cycles
+    let levels = outputs(&mut apu, 4 * 32);
+    let steps: Vec<u8> = levels.into_iter().step_by(4).collect();
+    assert_eq!(steps[..17], [15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, 0]);
+    assert_eq!(steps[31], 15);
+}
+
+#[test]
+fn expired_linear_counter_holds_the_level() {
+    let mut apu = apu();
+    apu.write_register(0x4008, 0x02); // Reload 2, counting down
+    apu.write_register(0x400A, 0x40);
+    apu.write_register(0x400B, 0x08);
+
+    // Reloaded at 7457, counts down at 14913 and 22371
+    outputs(&mut apu, 22371);
+    let held = apu.triangle.output();
+    assert_ne!(held, 0);
+    assert!(outputs(&mut apu, 5000).iter().all(|&level| level == held));
+}
+
+#[test]
+fn ultrasonic_periods_step_every_cycle() {
+    let mut apu = apu();
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x00);
+    apu.write_register(0x400B, 0x08);
+    outputs(&mut apu, 7457);
+
+    let levels = outputs(&mut apu, 32 * 100);
+    let average = levels.iter().map(|&level| level as f32).sum::<f32>() / levels.len() as f32;
+    assert_eq!(average, 7.5);
+}
+
+#[test]
+fn disabling_clears_the_length_counter() {
+    let mut apu = apu();
+    apu.write_register(0x4008, 0x7F);
+    apu.write_register(0x400B, 0x08);
+    assert!(apu.triangle.active());
+    apu.write_register(0x4015, 0x00);
+    assert!(!apu.triangle.active());
+}
+
+#[test]
+fn ultrasonic_silence_holds_the_sequencer() {
+    let mut apu = apu();
+    apu.triangle.set_ultrasonic(Ultrasonic::Silence);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x00);
+    apu.write_register(0x400B, 0x08);
+    outputs(&mut apu, 7457);
+    assert!(outputs(&mut apu, 5000).iter().all(|&level| level == 15));
+
+    // Audible periods play as usual
+    apu.write_register(0x400A, 0x03);
+    let levels = outputs(&mut apu, 8);
+    assert_eq!(levels[7], 13);
+}
+
+#[test]
+fn ultrasonic_fade_eases_to_the_middle() {
+    let mut apu = apu();
+    apu.triangle.set_ultrasonic(Ultrasonic::Fade);
+    apu.write_register(0x4008, 0xFF);
+    apu.write_register(0x400A, 0x00);
+    apu.write_register(0x400B, 0x08);
+    outputs(&mut apu, 7457);
+
+    // One step at a time from 15 down to 7, then it stays
+    let levels = outputs(&mut apu, 256 * 10);
+    assert!(levels.windows(2).all(|pair| pair[0] - pair[1] <= 1));
+    assert_eq!(levels[255], 14);
+    assert_eq!(levels[256 * 8 - 1], 7);
+    assert_eq!(*levels.last().unwrap(), 7);
+}
+
+#[test]
+fn accurate_by_default() {
+    assert_eq!(apu().triangle.ultrasonic(), Ultrasonic::Accurate);
+}
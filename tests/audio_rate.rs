@@ -0,0 +1,65 @@
+// tests/audio_rate.rs
+// Dynamic rate control: the adjustment curve, and a queue kept from draining
+
+use alphanes::audio::{DynamicRate, SampleRing};
+use alphanes::nes::apu::Apu;
+use alphanes::nes::clock::Region;
+
+#[test]
+fn adjustment_follows_the_queue_level() {
+    let control = DynamicRate::default();
+    assert_eq!(control.adjust(0, 1000), 0.005);
+    assert_eq!(control.adjust(500, 1000), 0.0);
+    assert_eq!(control.adjust(1000, 1000), -0.005);
+    assert_eq!(control.adjust(5000, 1000), -0.005);
+    assert!((control.adjust(250, 1000) - 0.0025).abs() < 1e-12);
+    assert_eq!(control.adjust(0, 0), 0.0);
+}
+
+#[test]
+fn rate_adjust_stretches_the_output() {
+    let mut counts = [0.0; 2];
+    for (count, adjust) in counts.iter_mut().zip([0.0, 0.005]) {
+        let mut apu = Apu::new(Region::Ntsc);
+        apu.set_rate_adjust(adjust);
+        let mut samples = Vec::new();
+        for _ in 0..894_886 {
+            apu.clock();
+        }
+        apu.take_samples(&mut samples);
+        *count = samples.len() as f64;
+    }
+    assert!((counts[1] / counts[0] - 1.005).abs() < 0.0001, "{:?}", counts);
+}
+
+// A device draining 800 samples per frame from a console running 0.3% slow
+fn run(control: Option<DynamicRate>) -> u64 {
+    let ring = SampleRing::new(4800);
+    ring.push(&[0.0; 2400]);
+    let mut apu = Apu::new(Region::Ntsc);
+    let mut samples = Vec::new();
+    let mut out = [0.0; 800];
+    for _ in 0..1200 {
+        for _ in 0..29_740 {
+            apu.clock();
+        }
+        samples.clear();
+        apu.take_samples(&mut samples);
+        ring.push(&samples);
+        if let Some(control) = control {
+            apu.set_rate_adjust(control.adjust(ring.len(), ring.capacity()));
+        }
+        ring.pop(&mut out);
+    }
+    ring.underruns()
+}
+
+#[test]
+fn queue_runs_dry_without_rate_control() {
+    assert!(run(None) > 0);
+}
+
+#[test]
+fn rate_control_keeps_the_queue_from_draining() {
+    assert_eq!(run(Some(DynamicRate::default())), 0);
+}
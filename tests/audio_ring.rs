@@ -0,0 +1,70 @@
+// tests/audio_ring.rs
+// Sample queue between the emulator and the audio callback: overflow, underrun and hold
+
+use alphanes::audio::SampleRing;
+
+#[test]
+fn pops_in_order_what_was_pushed() {
+    let ring = SampleRing::new(8);
+    assert_eq!(ring.push(&[0.1, 0.2, 0.3]), 0);
+    assert_eq!(ring.len(), 3);
+
+    let mut out = [0.0; 2];
+    assert_eq!(ring.pop(&mut out), 2);
+    assert_eq!(out, [0.1, 0.2]);
+    assert_eq!(ring.len(), 1);
+    assert_eq!(ring.underruns(), 0);
+}
+
+#[test]
+fn full_ring_drops_the_oldest_samples() {
+    let ring = SampleRing::new(4);
+    ring.push(&[1.0, 2.0, 3.0]);
+    assert_eq!(ring.push(&[4.0, 5.0, 6.0]), 2);
+
+    let mut out = [0.0; 4];
+    assert_eq!(ring.pop(&mut out), 4);
+    assert_eq!(out, [3.0, 4.0, 5.0, 6.0]);
+
+    // A push bigger than the whole ring keeps only its own tail
+    assert_eq!(ring.push(&[7.0, 8.0, 9.0, 10.0, 11.0]), 1);
+    ring.pop(&mut out);
+    assert_eq!(out, [8.0, 9.0, 10.0, 11.0]);
+}
+
+#[test]
+fn underrun_holds_the_last_sample_and_counts_once() {
+    let ring = SampleRing::new(8);
+    ring.push(&[0.25, 0.5]);
+
+    let mut out = [0.0; 4];
+    assert_eq!(ring.pop(&mut out), 2);
+    assert_eq!(out, [0.25, 0.5, 0.5, 0.5]);
+    assert_eq!(ring.underruns(), 1);
+
+    // Still dry: the same underrun
+    assert_eq!(ring.pop(&mut out), 0);
+    assert_eq!(out, [0.5; 4]);
+    assert_eq!(ring.underruns(), 1);
+
+    // Recovered, then dry again
+    ring.push(&[0.0; 4]);
+    assert_eq!(ring.pop(&mut out), 4);
+    assert!(ring.is_empty());
+    ring.pop(&mut out);
+    assert_eq!(ring.underruns(), 2);
+}
+
+#[test]
+fn stereo_ring_drops_and_holds_whole_frames() {
+    let ring = SampleRing::with_channels(5, 2);
+    assert_eq!(ring.capacity(), 4);
+    ring.push(&[1.0, -1.0]);
+    // Three samples over means two whole frames out
+    assert_eq!(ring.push(&[2.0, -2.0, 3.0, -3.0, 4.0, -4.0]), 4);
+
+    let mut out = [0.0; 8];
+    assert_eq!(ring.pop(&mut out), 4);
+    assert_eq!(out, [3.0, -3.0, 4.0, -4.0, 4.0, -4.0, 4.0, -4.0]);
+    assert_eq!(ring.underruns(), 1);
+}
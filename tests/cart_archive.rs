@@ -0,0 +1,70 @@
+// tests/cart_archive.rs
+// ROM images read from plain files, .zip archives and .gz streams
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use alphanes::nes::cart::{read_image, split_inner_path, RomError};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+// `data` written to a file of this name in a per-process temp directory
+fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("alphanes-archive-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, data).unwrap();
+    path
+}
+
+fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for &(name, data) in entries {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn plain_files_are_read_as_is() {
+    let path = temp_file("plain.nes", b"NES\x1Aplain");
+    assert_eq!(read_image(&path, None).unwrap(), b"NES\x1Aplain");
+}
+
+#[test]
+fn gzip_streams_are_decompressed() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"NES\x1Agzipped").unwrap();
+    let path = temp_file("game.nes.gz", &encoder.finish().unwrap());
+    assert_eq!(read_image(&path, None).unwrap(), b"NES\x1Agzipped");
+}
+
+#[test]
+fn zip_picks_the_first_rom_entry() {
+    let data = zip(&[("readme.txt", b"hello"), ("b.nes", b"second"), ("a.NES", b"third")]);
+    let path = temp_file("first.zip", &data);
+    assert_eq!(read_image(&path, None).unwrap(), b"second");
+}
+
+#[test]
+fn zip_entry_is_selected_after_a_hash() {
+    let data = zip(&[("a.nes", b"first"), ("dir/b.nes", b"second")]);
+    let path = temp_file("select.zip", &data);
+    let arg = format!("{}#dir/b.nes", path.display());
+    let (outer, inner) = split_inner_path(&arg);
+    assert_eq!(outer, path);
+    assert_eq!(inner.as_deref(), Some("dir/b.nes"));
+    assert_eq!(read_image(&outer, inner.as_deref()).unwrap(), b"second");
+
+    assert!(matches!(read_image(&path, Some("missing.nes")), Err(RomError::Archive(_))));
+}
+
+#[test]
+fn zip_without_a_rom_is_rejected() {
+    let path = temp_file("empty.zip", &zip(&[("readme.txt", b"hello")]));
+    assert!(matches!(read_image(&path, None), Err(RomError::NoRomInArchive)));
+}
@@ -0,0 +1,183 @@
+// tests/cart_patch.rs
+// IPS and BPS patches applied to ROM images, including their error cases
+
+use alphanes::nes::cart::{apply_patch, PatchError};
+
+const SOURCE: &[u8] = b"ABCDEFGHIJ";
+
+// IPS patch from `(offset, data)` records, where an empty record is an RLE
+// run of `run` copies of `value`
+fn ips(records: &[(u32, &[u8])], truncate: Option<u32>) -> Vec<u8> {
+    let mut patch = b"PATCH".to_vec();
+    for &(offset, data) in records {
+        patch.extend(&offset.to_be_bytes()[1..]);
+        patch.extend((data.len() as u16).to_be_bytes());
+        patch.extend(data);
+    }
+    patch.extend(b"EOF");
+    if let Some(size) = truncate {
+        patch.extend(&size.to_be_bytes()[1..]);
+    }
+    patch
+}
+
+fn ips_rle(offset: u32, run: u16, value: u8) -> Vec<u8> {
+    let mut record = offset.to_be_bytes()[1..].to_vec();
+    record.extend([0, 0]);
+    record.extend(run.to_be_bytes());
+    record.push(value);
+    record
+}
+
+fn varint(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte | 0x80);
+            return;
+        }
+        out.push(byte);
+        value -= 1;
+    }
+}
+
+enum Action<'a> {
+    SourceRead(usize),
+    TargetRead(&'a [u8]),
+    SourceCopy(usize, isize),
+    TargetCopy(usize, isize),
+}
+
+// BPS patch turning `source` into `target` with `actions`, CRCs and all
+fn bps(source: &[u8], target: &[u8], actions: &[Action]) -> Vec<u8> {
+    let mut patch = b"BPS1".to_vec();
+    varint(&mut patch, source.len());
+    varint(&mut patch, target.len());
+    varint(&mut patch, 0);
+    let relative = |offset: isize| ((offset.unsigned_abs()) << 1) | (offset < 0) as usize;
+    for action in actions {
+        match *action {
+            Action::SourceRead(len) => varint(&mut patch, (len - 1) << 2),
+            Action::TargetRead(data) => {
+                varint(&mut patch, ((data.len() - 1) << 2) | 1);
+                patch.extend(data);
+            }
+            Action::SourceCopy(len, offset) => {
+                varint(&mut patch, ((len - 1) << 2) | 2);
+                varint(&mut patch, relative(offset));
+            }
+            Action::TargetCopy(len, offset) => {
+                varint(&mut patch, ((len - 1) << 2) | 3);
+                varint(&mut patch, relative(offset));
+            }
+        }
+    }
+    patch.extend(crc32fast::hash(source).to_le_bytes());
+    patch.extend(crc32fast::hash(target).to_le_bytes());
+    patch.extend(crc32fast::hash(&patch).to_le_bytes());
+    patch
+}
+
+#[test]
+fn ips_records_overwrite_and_extend() {
+    let patch = ips(&[(1, b"xy"), (11, b"z")], None);
+    assert_eq!(apply_patch(SOURCE, &patch).unwrap(), b"AxyDEFGHIJ\0z");
+}
+
+#[test]
+fn ips_rle_records_fill_a_run() {
+    let mut patch = b"PATCH".to_vec();
+    patch.extend(ips_rle(2, 4, b'-'));
+    patch.extend(b"EOF");
+    assert_eq!(apply_patch(SOURCE, &patch).unwrap(), b"AB----GHIJ");
+}
+
+#[test]
+fn ips_size_after_eof_truncates() {
+    let patch = ips(&[(0, b"a")], Some(4));
+    assert_eq!(apply_patch(SOURCE, &patch).unwrap(), b"aBCD");
+}
+
+#[test]
+fn ips_record_cut_short_is_truncated() {
+    let mut patch = ips(&[(0, b"abcd")], None);
+    patch.truncate(patch.len() - 5);
+    assert!(matches!(apply_patch(SOURCE, &patch), Err(PatchError::Truncated)));
+}
+
+#[test]
+fn bps_actions_build_the_target() {
+    // SourceRead "ABC", TargetRead "xy", SourceCopy "HIJ" from 7, SourceCopy
+    // "AB" back from 10, then TargetCopy of "xyH" from 3
+    let target = b"ABCxyHIJABxyH";
+    let patch = bps(
+        SOURCE,
+        target,
+        &[
+            Action::SourceRead(3),
+            Action::TargetRead(b"xy"),
+            Action::SourceCopy(3, 7),
+            Action::SourceCopy(2, -10),
+            Action::TargetCopy(3, 3),
+        ],
+    );
+    assert_eq!(apply_patch(SOURCE, &patch).unwrap(), target);
+}
+
+#[test]
+fn bps_target_copy_can_overlap_its_own_output() {
+    // Copying from one byte back repeats it, like an RLE run
+    let target = b"A=======";
+    let patch = bps(SOURCE, target, &[Action::SourceRead(1), Action::TargetRead(b"="), Action::TargetCopy(6, 1)]);
+    assert_eq!(apply_patch(SOURCE, &patch).unwrap(), target);
+}
+
+#[test]
+fn bps_for_another_rom_is_a_source_mismatch() {
+    let patch = bps(SOURCE, b"A", &[Action::SourceRead(1)]);
+    let other = b"KLMNOPQRST";
+    match apply_patch(other, &patch) {
+        Err(PatchError::SourceMismatch { expected, found }) => {
+            assert_eq!(expected, crc32fast::hash(SOURCE));
+            assert_eq!(found, crc32fast::hash(other));
+        }
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn bps_with_a_wrong_target_crc_fails() {
+    // Claims the output is "AB" but only produces "AC"
+    let mut patch = bps(SOURCE, b"AB", &[Action::SourceRead(1), Action::SourceCopy(1, 2)]);
+    let body = patch.len() - 4;
+    let crc = crc32fast::hash(&patch[..body]);
+    patch[body..].copy_from_slice(&crc.to_le_bytes());
+    assert!(matches!(apply_patch(SOURCE, &patch), Err(PatchError::TargetMismatch)));
+}
+
+#[test]
+fn bps_with_a_damaged_body_is_corrupt() {
+    let mut patch = bps(SOURCE, b"xy", &[Action::TargetRead(b"xy")]);
+    patch[8] ^= 0x01;
+    assert!(matches!(apply_patch(SOURCE, &patch), Err(PatchError::PatchCorrupt)));
+}
+
+#[test]
+fn bps_with_a_huge_target_size_fails_cleanly() {
+    // A target size near usize::MAX, with the patch CRC recomputed to match
+    let mut patch = b"BPS1".to_vec();
+    varint(&mut patch, SOURCE.len());
+    varint(&mut patch, usize::MAX >> 8);
+    varint(&mut patch, 0);
+    varint(&mut patch, usize::MAX >> 8);
+    patch.extend(crc32fast::hash(SOURCE).to_le_bytes());
+    patch.extend(0u32.to_le_bytes());
+    patch.extend(crc32fast::hash(&patch).to_le_bytes());
+    assert!(apply_patch(SOURCE, &patch).is_err());
+}
+
+#[test]
+fn unknown_magic_is_rejected() {
+    assert!(matches!(apply_patch(SOURCE, b"UPS1...."), Err(PatchError::UnknownFormat)));
+}
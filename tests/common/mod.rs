@@ -0,0 +1,63 @@
+// tests/common/mod.rs
+// Cartridge images, PPUs and steppers shared by the integration tests
+
+// Each test binary uses only some of these
+#![allow(dead_code)]
+
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::cpu::CpuError;
+use alphanes::nes::ppu::Ppu;
+use alphanes::nes::Nes;
+
+/// iNES image for `mapper` from whole 16KB banks of `prg` and 8KB banks of
+/// `chr`, with no CHR meaning 8KB of CHR RAM
+pub fn ines(mapper: u8, prg: &[u8], chr: &[u8]) -> Vec<u8> {
+    let prg_banks = (prg.len() / 0x4000) as u8;
+    let chr_banks = (chr.len() / 0x2000) as u8;
+    let mut image = vec![b'N', b'E', b'S', 0x1A, prg_banks, chr_banks, mapper << 4, mapper & 0xF0];
+    image.resize(16, 0);
+    image.extend(prg);
+    image.extend(chr);
+    image
+}
+
+/// 16KB PRG bank of NOPs starting with `program`, which reset runs from $8000
+pub fn prg(program: &[u8]) -> Vec<u8> {
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..program.len()].copy_from_slice(program);
+    prg[0x3FFC] = 0x00; // Reset vector $8000
+    prg[0x3FFD] = 0x80;
+    prg
+}
+
+/// 8KB CHR bank, blank apart from what `fill` writes into it
+pub fn chr(fill: impl FnOnce(&mut [u8])) -> Vec<u8> {
+    let mut chr = vec![0; 0x2000];
+    fill(&mut chr);
+    chr
+}
+
+/// PPU on an NROM cartridge whose CHR ROM `fill` draws
+pub fn ppu(fill: impl FnOnce(&mut [u8])) -> Ppu {
+    let rom = Rom::from_bytes(&ines(0, &prg(&[]), &chr(fill))).unwrap();
+    Ppu::new(cart::new_mapper(rom).unwrap())
+}
+
+/// Step until the PPU has finished `cycle` of `scanline`
+pub fn run_to(ppu: &mut Ppu, scanline: i16, cycle: usize) {
+    while !(ppu.scanline == scanline && ppu.cycle == cycle) {
+        ppu.step();
+    }
+}
+
+/// Run until the program hits its JAM
+pub fn run(nes: &mut Nes) {
+    for _ in 0..1000 {
+        match nes.step() {
+            Ok(_) => {}
+            Err(CpuError::Jammed { .. }) => return,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    panic!("program never finished");
+}
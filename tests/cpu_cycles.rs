@@ -0,0 +1,301 @@
+// tests/cpu_cycles.rs
+// Instruction timing checked against the published 6502 cycle tables
+
+use alphanes::nes::cpu::{AddressingMode, Bus, Cpu2A03, Mnemonic, OPCODES};
+
+// Base cycles per opcode, row = high nibble (0 = JAM)
+#[rustfmt::skip]
+const BASE_CYCLES: [usize; 256] = [
+    7, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 6, 0, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5,
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4,
+    2, 5, 0, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6,
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7,
+];
+
+// Extra cycle when an indexed read crosses a page (branches excluded)
+#[rustfmt::skip]
+const PAGE_PENALTY: [usize; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 1, 1,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0,
+];
+
+const PROGRAM: u16 = 0x0400;
+const ZP_POINTER: u8 = 0x10;
+
+struct FlatBus {
+    ram: Vec<u8>,
+}
+
+impl Bus for FlatBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.ram[addr as usize] = data;
+    }
+}
+
+// CPU about to execute `opcode` at $0400 with operand $0310 (and a zero-page
+// pointer to $0310), so an index of $FF crosses into page $04
+fn cpu_for(opcode: u8, index: u8, status: u8) -> Cpu2A03<FlatBus> {
+    let mut ram = vec![0; 0x10000];
+    ram[PROGRAM as usize] = opcode;
+    ram[PROGRAM as usize + 1] = ZP_POINTER;
+    ram[PROGRAM as usize + 2] = 0x03;
+    ram[ZP_POINTER as usize] = 0x10;
+    ram[ZP_POINTER as usize + 1] = 0x03;
+    ram[0xFFFC] = PROGRAM as u8;
+    ram[0xFFFD] = (PROGRAM >> 8) as u8;
+
+    let mut cpu = Cpu2A03::new(FlatBus { ram });
+    cpu.reset();
+    cpu.x = index;
+    cpu.y = index;
+    cpu.status = status;
+    cpu
+}
+
+fn testable(opcode: u8) -> bool {
+    let op = OPCODES[opcode as usize];
+    op.mnemonic != Mnemonic::Jam && op.mode != AddressingMode::Relative
+}
+
+#[test]
+fn opcode_table_matches_reference_cycles() {
+    for (opcode, op) in OPCODES.iter().enumerate() {
+        if op.mnemonic != Mnemonic::Jam {
+            assert_eq!(op.cycles as usize, BASE_CYCLES[opcode], "table cycles for opcode {:02X}", opcode);
+        }
+    }
+}
+
+#[test]
+fn base_cycles_without_page_cross() {
+    for opcode in (0..=255u8).filter(|&op| testable(op)) {
+        let mut cpu = cpu_for(opcode, 0, 0x24);
+        assert_eq!(cpu.step().unwrap(), BASE_CYCLES[opcode as usize], "opcode {:02X}", opcode);
+    }
+}
+
+#[test]
+fn page_cross_penalty_on_indexed_reads_only() {
+    for opcode in (0..=255u8).filter(|&op| testable(op)) {
+        let mut cpu = cpu_for(opcode, 0xFF, 0x24);
+        let expected = BASE_CYCLES[opcode as usize] + PAGE_PENALTY[opcode as usize];
+        assert_eq!(cpu.step().unwrap(), expected, "opcode {:02X} crossing a page", opcode);
+    }
+}
+
+#[test]
+fn branch_cycles() {
+    // BNE with Z clear is taken, with Z set falls through
+    let not_taken = 0x24 | 0x02;
+    let taken = 0x24;
+
+    let mut cpu = cpu_for(0xD0, 0, not_taken);
+    assert_eq!(cpu.step().unwrap(), 2);
+
+    // Offset $10 from $0402 stays in page $04
+    let mut cpu = cpu_for(0xD0, 0, taken);
+    assert_eq!(cpu.step().unwrap(), 3);
+    assert_eq!(cpu.pc, 0x0412);
+
+    // Offset $F0 (-16) from $0402 lands in page $03
+    let mut cpu = cpu_for(0xD0, 0, taken);
+    cpu.bus.ram[PROGRAM as usize + 1] = 0xF0;
+    assert_eq!(cpu.step().unwrap(), 4);
+    assert_eq!(cpu.pc, 0x03F2);
+}
+
+const SAMPLE_ADDR: u16 = 0xC000;
+const NMI_HANDLER: u16 = 0x0500;
+const IRQ_HANDLER: u16 = 0x0600;
+
+// Flat RAM with a DMC asking for a sample from the `dmc_from`th cycle on,
+// an OAM DMA page waiting, NMI and IRQ raised from given cycles, and a $4016
+// that counts its reads like a shift register would
+struct DmaBus {
+    ram: Vec<u8>,
+    ticks: usize,
+    dmc_from: Option<usize>,
+    nmi_from: Option<usize>,
+    irq_from: Option<usize>,
+    samples: Vec<u8>,
+    oam_page: Option<u8>,
+    reads: Vec<u16>,
+    port_reads: u8,
+}
+
+impl Bus for DmaBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.reads.push(addr);
+        if addr == 0x4016 {
+            self.port_reads += 1;
+            return self.port_reads;
+        }
+        self.ram[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.ram[addr as usize] = data;
+    }
+
+    fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    fn take_nmi(&mut self) -> bool {
+        let raised = self.nmi_from.is_some_and(|from| self.ticks >= from);
+        if raised {
+            self.nmi_from = None;
+        }
+        raised
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.irq_from.is_some_and(|from| self.ticks >= from)
+    }
+
+    fn take_dmc_dma(&mut self) -> Option<u16> {
+        match self.dmc_from {
+            Some(from) if self.ticks >= from => {
+                self.dmc_from = None;
+                Some(SAMPLE_ADDR)
+            }
+            _ => None,
+        }
+    }
+
+    fn dmc_dma_complete(&mut self, data: u8) {
+        self.samples.push(data);
+    }
+
+    fn take_oam_dma(&mut self) -> Option<u8> {
+        self.oam_page.take()
+    }
+}
+
+// CPU about to run `program` at $0400, with a DMC fetch due on the given
+// cycle of the first step
+fn dma_cpu(program: &[u8], dmc_cycle: Option<usize>) -> Cpu2A03<DmaBus> {
+    let mut ram = vec![0xEA; 0x10000];
+    ram[PROGRAM as usize..PROGRAM as usize + program.len()].copy_from_slice(program);
+    ram[SAMPLE_ADDR as usize] = 0x5A;
+    ram[0xFFFC] = PROGRAM as u8;
+    ram[0xFFFD] = (PROGRAM >> 8) as u8;
+    ram[0xFFFA..0xFFFC].copy_from_slice(&NMI_HANDLER.to_le_bytes());
+    ram[0xFFFE..].copy_from_slice(&IRQ_HANDLER.to_le_bytes());
+    let bus = DmaBus {
+        ram,
+        ticks: 0,
+        dmc_from: None,
+        nmi_from: None,
+        irq_from: None,
+        samples: Vec::new(),
+        oam_page: None,
+        reads: Vec::new(),
+        port_reads: 0,
+    };
+    let mut cpu = Cpu2A03::new(bus);
+    cpu.reset();
+    cpu.bus.dmc_from = dmc_cycle.map(|cycle| cpu.bus.ticks + cycle);
+    cpu.bus.reads.clear();
+    cpu
+}
+
+#[test]
+fn dmc_fetch_steals_three_or_four_cycles_by_parity() {
+    // The fetch has to land on an odd cycle. Reset leaves the count odd, so
+    // the opcode read halted is on an even cycle and needs realigning.
+    let mut even = dma_cpu(&[0xEA], Some(1));
+    assert_eq!(even.cycles() % 2, 1);
+    assert_eq!(even.step().unwrap(), 2 + 4);
+
+    // A 3-cycle JMP first puts the halted read on an odd cycle
+    let mut odd = dma_cpu(&[0x4C, 0x03, 0x04, 0xEA], None);
+    odd.step().unwrap();
+    assert_eq!(odd.cycles() % 2, 0);
+    odd.bus.dmc_from = Some(odd.bus.ticks + 1);
+    assert_eq!(odd.step().unwrap(), 2 + 3);
+
+    for cpu in [even, odd] {
+        assert_eq!(cpu.bus.samples, [0x5A]);
+        assert_eq!(cpu.bus.reads.iter().filter(|&&addr| addr == SAMPLE_ADDR).count(), 1);
+    }
+}
+
+#[test]
+fn dmc_fetch_repeats_the_halted_read() {
+    // LDA $4016 halted on its operand read: the port sees the halt and
+    // alignment reads too, so the value loaded has skipped ahead. Every
+    // stolen cycle but the fetch itself is one of them.
+    let mut cpu = dma_cpu(&[0xAD, 0x16, 0x40], Some(4));
+    let cycles = cpu.step().unwrap();
+    let port_reads = cpu.bus.reads.iter().filter(|&&addr| addr == 0x4016).count();
+    assert_eq!(cycles, 4 + port_reads);
+    assert!(port_reads == 3 || port_reads == 4, "{} reads of $4016", port_reads);
+    assert_eq!(cpu.a, port_reads as u8);
+    assert_eq!(cpu.bus.samples, [0x5A]);
+}
+
+#[test]
+fn dmc_fetch_during_oam_dma_steals_two_cycles() {
+    // Reset leaves the count odd, so the sprite DMA starts on an even cycle
+    // and takes 513
+    let mut plain = dma_cpu(&[0xEA], None);
+    plain.bus.oam_page = Some(0x02);
+    assert_eq!(plain.step().unwrap(), 513);
+
+    let mut with_dmc = dma_cpu(&[0xEA], Some(100));
+    with_dmc.bus.oam_page = Some(0x02);
+    assert_eq!(with_dmc.step().unwrap(), 513 + 2);
+    assert_eq!(with_dmc.bus.samples, [0x5A]);
+    assert_eq!(with_dmc.bus.reads.iter().filter(|&&addr| (addr >> 8) == 0x02).count(), 256);
+}
+
+#[test]
+fn interrupts_raised_during_oam_dma_are_taken_straight_after_it() {
+    // Either one is polled on the DMA's cycles, so the instruction after
+    // the DMA is the interrupt sequence rather than the next opcode
+    let mut nmi = dma_cpu(&[0xEA], None);
+    nmi.bus.oam_page = Some(0x02);
+    nmi.bus.nmi_from = Some(nmi.bus.ticks + 100);
+    assert_eq!(nmi.step().unwrap(), 513);
+    assert_eq!(nmi.step().unwrap(), 7);
+    assert_eq!(nmi.pc, NMI_HANDLER);
+
+    let mut irq = dma_cpu(&[0x58, 0xEA], None); // CLI
+    irq.step().unwrap();
+    irq.bus.oam_page = Some(0x02);
+    irq.bus.irq_from = Some(irq.bus.ticks + 100);
+    irq.step().unwrap();
+    assert_eq!(irq.step().unwrap(), 7);
+    assert_eq!(irq.pc, IRQ_HANDLER);
+}
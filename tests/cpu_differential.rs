@@ -0,0 +1,195 @@
+// tests/cpu_differential.rs
+// Random instruction streams run on Cpu2A03 and the emulator_6502 reference
+// core side by side, comparing registers, memory and cycles per instruction
+//
+// Only official opcodes are generated: the unofficial ones are covered by
+// ProcessorTests, and the reference core's versions aren't trustworthy.
+// BRK is left out too, since the reference core leaves B set in P.
+
+use alphanes::nes::cpu::{AddressingMode, Bus, Cpu2A03, Mnemonic, OPCODES};
+use emulator_6502::{Interface6502, MOS6502};
+use proptest::prelude::*;
+
+const PROGRAM_START: u16 = 0x0400;
+// Branches can loop back within the stream, so stop after this many
+const MAX_INSTRUCTIONS: usize = 256;
+// B and bit 5 aren't stored in P, so the cores may disagree about them
+const STATUS_MASK: u8 = !0x30;
+
+struct Memory(Vec<u8>);
+
+impl Bus for Memory {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.0[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.0[addr as usize] = data;
+    }
+}
+
+impl Interface6502 for Memory {
+    fn read(&mut self, address: u16) -> u8 {
+        self.0[address as usize]
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        self.0[address as usize] = data;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Registers {
+    a: u8,
+    x: u8,
+    y: u8,
+    sp: u8,
+    p: u8,
+}
+
+fn generated(opcode: u8) -> bool {
+    let op = OPCODES[opcode as usize];
+    op.official && op.mnemonic != Mnemonic::Brk
+}
+
+fn official_opcodes() -> Vec<u8> {
+    (0..=255u8).filter(|&opcode| generated(opcode)).collect()
+}
+
+// One instruction: an opcode and as many random operand bytes as it takes
+fn instruction() -> impl Strategy<Value = Vec<u8>> {
+    (prop::sample::select(official_opcodes()), any::<u8>(), any::<u8>()).prop_map(|(opcode, lo, hi)| {
+        let len = OPCODES[opcode as usize].mode.operand_len();
+        [opcode, lo, hi][..=len as usize].to_vec()
+    })
+}
+
+fn registers() -> impl Strategy<Value = Registers> {
+    any::<[u8; 5]>().prop_map(|[a, x, y, sp, p]| Registers { a, x, y, sp, p })
+}
+
+// Everything outside the program is pseudo-random, so zero page pointers
+// and stack contents vary between cases
+fn memory(seed: u64, program: &[u8]) -> Vec<u8> {
+    let mut state = seed | 1;
+    let mut ram: Vec<u8> = (0..0x10000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let start = PROGRAM_START as usize;
+    ram[start..start + program.len()].copy_from_slice(program);
+    ram
+}
+
+// The reference core charges the page-crossing cycle to indexed stores and
+// read-modify-writes too, though they always take the fixed longer timing
+fn reference_overcounts(opcode: u8) -> bool {
+    let op = OPCODES[opcode as usize];
+    let indexed = matches!(op.mode, AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectY);
+    let writes = matches!(
+        op.mnemonic,
+        Mnemonic::Sta | Mnemonic::Asl | Mnemonic::Lsr | Mnemonic::Rol | Mnemonic::Ror | Mnemonic::Inc | Mnemonic::Dec
+    );
+    indexed && writes
+}
+
+fn reference_registers(cpu: &MOS6502) -> (u16, Registers) {
+    let registers = Registers {
+        a: cpu.get_accumulator(),
+        x: cpu.get_x_register(),
+        y: cpu.get_y_register(),
+        sp: cpu.get_stack_pointer(),
+        p: cpu.get_status_register(),
+    };
+    (cpu.get_program_counter(), registers)
+}
+
+// Runs the stream until PC leaves it (or lands on an operand byte that
+// isn't an opcode we generate), returning the first divergence
+fn run(seed: u64, start: Registers, program: &[u8]) -> Result<(), String> {
+    let ram = memory(seed, program);
+
+    let mut cpu = Cpu2A03::new(Memory(ram.clone()));
+    cpu.pc = PROGRAM_START;
+    cpu.a = start.a;
+    cpu.x = start.x;
+    cpu.y = start.y;
+    cpu.sp = start.sp;
+    cpu.status = start.p;
+
+    let mut reference = MOS6502::new_start(PROGRAM_START);
+    let mut reference_ram = Memory(ram);
+    reference.set_accumulator(start.a);
+    reference.set_x_register(start.x);
+    reference.set_y_register(start.y);
+    reference.set_stack_pointer(start.sp);
+    reference.set_status_register(start.p);
+
+    let program_end = PROGRAM_START + program.len() as u16;
+    for _ in 0..MAX_INSTRUCTIONS {
+        if !(PROGRAM_START..program_end).contains(&cpu.pc) {
+            break;
+        }
+        let pc = cpu.pc;
+        let opcode = cpu.bus.0[pc as usize];
+        if !generated(opcode) {
+            break;
+        }
+        let cycles = cpu.step().map_err(|err| err.to_string())?;
+
+        reference.cycle(&mut reference_ram);
+        let mut reference_cycles = 1;
+        while reference.get_remaining_cycles() != 0 {
+            reference.cycle(&mut reference_ram);
+            reference_cycles += 1;
+        }
+        if reference_overcounts(opcode) && cycles + 1 == reference_cycles {
+            reference_cycles -= 1;
+        }
+
+        let context = format!("{:02X} at {:04X}", opcode, pc);
+        let (reference_pc, wanted) = reference_registers(&reference);
+        let checks = [
+            ("pc", cpu.pc as u64, reference_pc as u64),
+            ("a", cpu.a as u64, wanted.a as u64),
+            ("x", cpu.x as u64, wanted.x as u64),
+            ("y", cpu.y as u64, wanted.y as u64),
+            ("sp", cpu.sp as u64, wanted.sp as u64),
+            ("p", (cpu.status & STATUS_MASK) as u64, (wanted.p & STATUS_MASK) as u64),
+            ("cycles", cycles as u64, reference_cycles as u64),
+        ];
+        for (name, actual, expected) in checks {
+            if actual != expected {
+                return Err(format!("{}: {} {:02X}, expected {:02X}", context, name, actual, expected));
+            }
+        }
+        if cpu.bus.0 == reference_ram.0 {
+            continue;
+        }
+        if let Some(addr) = (0..0x10000).find(|&addr| cpu.bus.0[addr] != reference_ram.0[addr]) {
+            return Err(format!(
+                "{}: ram[{:04X}] {:02X}, expected {:02X}",
+                context, addr, cpu.bus.0[addr], reference_ram.0[addr]
+            ));
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn matches_reference_core(
+        seed in any::<u64>(),
+        start in registers(),
+        stream in prop::collection::vec(instruction(), 1..32),
+    ) {
+        let program = stream.concat();
+        if let Err(divergence) = run(seed, start, &program) {
+            prop_assert!(false, "{}", divergence);
+        }
+    }
+}
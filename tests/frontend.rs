@@ -0,0 +1,124 @@
+// tests/frontend.rs
+// Frontend pacing and defaults shared by the windowed builds
+
+use alphanes::frontend::{frame_period, Binding, FrontendOptions, Hotkey, HotkeyState, Key, KeyMap};
+use alphanes::nes::apu::{AudioSource, Channel};
+use alphanes::nes::cart::Rom;
+use alphanes::nes::clock::Region;
+use alphanes::nes::controller::InputState;
+use alphanes::nes::Nes;
+
+#[test]
+fn frames_pace_at_the_console_refresh_rate() {
+    let ntsc = 1.0 / frame_period(Region::Ntsc).as_secs_f64();
+    let pal = 1.0 / frame_period(Region::Pal).as_secs_f64();
+    assert!((ntsc - 60.1).abs() < 0.01, "NTSC {}Hz", ntsc);
+    assert!((pal - 50.01).abs() < 0.01, "PAL {}Hz", pal);
+}
+
+#[test]
+fn windows_open_at_triple_size() {
+    let options = FrontendOptions::default();
+    assert_eq!(options.scale, 3);
+    assert!(!options.fullscreen);
+}
+
+#[test]
+fn default_keys_are_arrows_z_x_enter_and_shift() {
+    let keys = KeyMap::default();
+    assert_eq!(keys.key(InputState::A), Some(Key::Letter('x')));
+    assert_eq!(keys.key(InputState::B), Some(Key::Letter('z')));
+    assert_eq!(keys.key(InputState::START), Some(Key::Enter));
+    assert_eq!(keys.key(InputState::SELECT), Some(Key::RightShift));
+    assert_eq!(keys.key(InputState::LEFT), Some(Key::Left));
+    assert_eq!(keys.key(InputState::empty()), None);
+    assert_eq!(FrontendOptions::default().keys, keys);
+}
+
+#[test]
+fn keys_and_buttons_parse_by_name() {
+    assert_eq!(Key::named("S"), Some(Key::Letter('s')));
+    assert_eq!(Key::named("7"), Some(Key::Digit(7)));
+    assert_eq!(Key::named("return"), Some(Key::Enter));
+    assert_eq!(Key::named("lctrl"), Some(Key::LeftCtrl));
+    assert_eq!(Key::named("f13"), None);
+    assert_eq!(KeyMap::button_named("start"), Some(InputState::START));
+    assert_eq!(KeyMap::button_named("Up"), Some(InputState::UP));
+    assert_eq!(KeyMap::button_named("turbo"), None);
+}
+
+#[test]
+fn held_keys_press_their_buttons() {
+    let mut keys = KeyMap::default();
+    keys.set(InputState::A, Key::Space);
+    keys.set(InputState::B, Key::Space);
+    let held = keys.buttons(|key| key == Key::Space || key == Key::Up);
+    assert_eq!(held, InputState::A | InputState::B | InputState::UP);
+    assert_eq!(keys.buttons(|key| key == Key::Letter('x')), InputState::empty());
+}
+
+#[test]
+fn hotkeys_default_to_5_6_and_9_and_rebind_by_name() {
+    let mut keys = KeyMap::default();
+    assert_eq!(keys.hotkey(Hotkey::Coin1), Some(Key::Digit(5)));
+    assert_eq!(keys.hotkey(Hotkey::Coin2), Some(Key::Digit(6)));
+    assert_eq!(keys.hotkey(Hotkey::Service), Some(Key::Digit(9)));
+    assert_eq!(KeyMap::binding_named("Coin2"), Some(Binding::Hotkey(Hotkey::Coin2)));
+    assert_eq!(KeyMap::binding_named("a"), Some(Binding::Button(InputState::A)));
+    assert_eq!(KeyMap::binding_named("coin3"), None);
+    keys.bind(Binding::Hotkey(Hotkey::Coin1), Key::Letter('c'));
+    assert_eq!(keys.hotkey(Hotkey::Coin1), Some(Key::Letter('c')));
+    assert_eq!(keys.hotkeys(|key| key == Key::Letter('c') || key == Key::Digit(5)), vec![Hotkey::Coin1]);
+}
+
+// NROM with an empty 16KB PRG and 8KB CHR, on a Vs. System board if `vs`
+fn nes(vs: bool) -> Nes {
+    let mut image = vec![0x4E, 0x45, 0x53, 0x1A, 1, 1, 0x00, vs as u8];
+    image.resize(16 + 0x4000 + 0x2000, 0);
+    Nes::new(Rom::from_bytes(&image).unwrap()).unwrap()
+}
+
+#[test]
+fn hotkeys_drop_coins_and_hold_service_on_a_vs_board() {
+    let mut nes = nes(true);
+    let vs = |nes: &Nes| nes.cpu.bus.vs.as_ref().unwrap().read_4016();
+    let mut hotkeys = HotkeyState::default();
+
+    hotkeys.update(&mut nes, vec![Hotkey::Coin1, Hotkey::Service]);
+    assert_eq!(vs(&nes) & 0x64, 0x24);
+    // Still holding: the coin has already dropped, the service button stays down
+    nes.cpu.bus.vs.as_mut().unwrap().clock(1_000_000);
+    hotkeys.update(&mut nes, vec![Hotkey::Coin1, Hotkey::Service]);
+    assert_eq!(vs(&nes) & 0x64, 0x04);
+    hotkeys.update(&mut nes, vec![Hotkey::Coin2]);
+    assert_eq!(vs(&nes) & 0x64, 0x40);
+}
+
+#[test]
+fn mute_and_solo_hotkeys_parse_by_channel_name() {
+    assert_eq!(Hotkey::named("mute-triangle"), Some(Hotkey::Mute(Channel::Triangle)));
+    assert_eq!(Hotkey::named("solo-VRC6"), Some(Hotkey::Solo(Channel::Expansion(AudioSource::Vrc6))));
+    assert_eq!(Hotkey::named("unmute"), Some(Hotkey::UnmuteAll));
+    assert_eq!(Hotkey::named("mute-pulse3"), None);
+    let keys = KeyMap::default();
+    assert_eq!(keys.hotkey(Hotkey::Mute(Channel::Noise)), Some(Key::Digit(4)));
+    assert_eq!(keys.hotkey(Hotkey::Solo(Channel::Pulse1)), Some(Key::Letter('q')));
+    assert_eq!(keys.hotkey(Hotkey::UnmuteAll), Some(Key::Digit(0)));
+}
+
+#[test]
+fn mute_and_solo_hotkeys_toggle_on_each_press() {
+    let mut nes = nes(false);
+    let mut hotkeys = HotkeyState::default();
+    let mute = Hotkey::Mute(Channel::Pulse2);
+    hotkeys.update(&mut nes, vec![mute]);
+    hotkeys.update(&mut nes, vec![mute]);
+    assert!(nes.cpu.bus.apu.muted(Channel::Pulse2));
+    hotkeys.update(&mut nes, vec![]);
+    hotkeys.update(&mut nes, vec![mute, Hotkey::Solo(Channel::Noise)]);
+    assert!(!nes.cpu.bus.apu.muted(Channel::Pulse2));
+    assert!(nes.cpu.bus.apu.soloed(Channel::Noise));
+    assert!(!nes.cpu.bus.apu.audible(Channel::Triangle));
+    hotkeys.update(&mut nes, vec![Hotkey::UnmuteAll]);
+    assert!(nes.cpu.bus.apu.audible(Channel::Triangle));
+}
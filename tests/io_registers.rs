@@ -0,0 +1,94 @@
+// tests/io_registers.rs
+// $4000-$401F reads: controller bits, APU status and open bus, as test ROMs see them
+
+use alphanes::nes::cart::Rom;
+use alphanes::nes::controller::InputState;
+use alphanes::nes::cpu::{Bus, CpuError};
+use alphanes::nes::Nes;
+
+// NROM-128 image running `program` from $8000, with CHR RAM
+fn nrom(program: &[u8]) -> Rom {
+    let mut image = vec![b'N', b'E', b'S', 0x1A, 1, 0, 0, 0];
+    image.resize(16, 0);
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..program.len()].copy_from_slice(program);
+    prg[0x3FFC] = 0x00; // Reset vector $8000
+    prg[0x3FFD] = 0x80;
+    image.extend(prg);
+    Rom::from_bytes(&image).unwrap()
+}
+
+// Run until the program hits its JAM
+fn run(nes: &mut Nes) {
+    for _ in 0..1000 {
+        match nes.step() {
+            Ok(_) => {}
+            Err(CpuError::Jammed { .. }) => return,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    panic!("program never finished");
+}
+
+#[test]
+fn controller_reads_shift_out_buttons_over_open_bus() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x01, 0x8D, 0x16, 0x40, // Strobe high
+        0xA9, 0x00, 0x8D, 0x16, 0x40, // Strobe low, latching the buttons
+        0xA2, 0x00,                   // LDX #0
+        0xAD, 0x16, 0x40,             // loop: LDA $4016
+        0x95, 0x00,                   // STA $00,X
+        0xE8,                         // INX
+        0xE0, 0x09,                   // CPX #9
+        0xD0, 0xF6,                   // BNE loop
+        0xAD, 0x17, 0x40,             // LDA $4017
+        0x85, 0x10,                   // STA $10
+        0x02,                         // JAM
+    ];
+    let mut nes = Nes::new(nrom(&program)).unwrap();
+    nes.set_input(0, InputState::A | InputState::START | InputState::RIGHT);
+    run(&mut nes);
+
+    // A, B, Select, Start, Up, Down, Left, Right, then 1s once empty. The
+    // upper bits are the $40 left on the bus by the operand's high byte.
+    let bits: Vec<u8> = (0..9).map(|addr| nes.cpu.bus.peek(addr)).collect();
+    assert_eq!(bits, [0x41, 0x40, 0x40, 0x41, 0x40, 0x40, 0x40, 0x41, 0x41]);
+    assert_eq!(nes.cpu.bus.peek(0x10), 0x40);
+}
+
+#[test]
+fn strobe_held_high_keeps_returning_a() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x01, 0x8D, 0x16, 0x40, // Strobe high
+        0xAD, 0x16, 0x40, 0x85, 0x00, // LDA $4016, STA $00
+        0xAD, 0x16, 0x40, 0x85, 0x01, // LDA $4016, STA $01
+        0x02,
+    ];
+    let mut nes = Nes::new(nrom(&program)).unwrap();
+    nes.set_input(0, InputState::A);
+    run(&mut nes);
+
+    assert_eq!(nes.cpu.bus.peek(0x00), 0x41);
+    assert_eq!(nes.cpu.bus.peek(0x01), 0x41);
+}
+
+#[test]
+fn status_and_unused_registers_float() {
+    #[rustfmt::skip]
+    let program = [
+        0xAD, 0x15, 0x40, 0x85, 0x00, // LDA $4015, STA $00
+        0xAD, 0x18, 0x40, 0x85, 0x01, // LDA $4018, STA $01
+        0xAD, 0x00, 0x40, 0x85, 0x02, // LDA $4000, STA $02
+        0x02,
+    ];
+    let mut nes = Nes::new(nrom(&program)).unwrap();
+    run(&mut nes);
+
+    // $4015 doesn't drive the bus: with no channels playing only bit 5 of
+    // the operand's $40 shows through, and it's clear
+    assert_eq!(nes.cpu.bus.peek(0x00), 0x00);
+    assert_eq!(nes.cpu.bus.peek(0x01), 0x40);
+    assert_eq!(nes.cpu.bus.peek(0x02), 0x40);
+}
@@ -0,0 +1,115 @@
+// tests/klaus.rs
+// Klaus Dormann's 6502 functional and interrupt tests run on Cpu2A03
+//
+// The binaries are assembled per configuration, so they aren't vendored.
+// Point KLAUS_TESTS_DIR at a directory holding 6502_functional_test.bin and
+// 6502_interrupt_test.bin (full 64KB images, as in the project's bin_files);
+// without it the tests are skipped. Builds with a different success trap
+// can override it with KLAUS_FUNCTIONAL_SUCCESS / KLAUS_INTERRUPT_SUCCESS,
+// given in hex.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use alphanes::nes::cpu::{Bus, Cpu2A03};
+
+// Both tests start right after their zero page and stack setup data
+const START: u16 = 0x0400;
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+const INTERRUPT_SUCCESS: u16 = 0x06F5;
+// Feedback port the interrupt test drives its own /IRQ and /NMI through
+const INTERRUPT_PORT: u16 = 0xBFFC;
+const IRQ_BIT: u8 = 0x01;
+const NMI_BIT: u8 = 0x02;
+// Far beyond the ~30M instructions the functional test needs
+const MAX_INSTRUCTIONS: usize = 100_000_000;
+
+// Flat 64KB memory, with the interrupt test's feedback port wired to the
+// CPU's interrupt inputs when enabled
+struct FlatBus {
+    ram: Vec<u8>,
+    port: Option<u16>,
+    nmi: bool,
+}
+
+impl Bus for FlatBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        if Some(addr) == self.port {
+            let old = self.ram[addr as usize];
+            self.nmi |= old & NMI_BIT == 0 && data & NMI_BIT != 0;
+        }
+        self.ram[addr as usize] = data;
+    }
+
+    fn irq_asserted(&self) -> bool {
+        self.port.is_some_and(|port| self.ram[port as usize] & IRQ_BIT != 0)
+    }
+
+    fn take_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi)
+    }
+}
+
+fn success_address(var: &str, default: u16) -> u16 {
+    match env::var(var) {
+        Ok(hex) => u16::from_str_radix(hex.trim_start_matches("0x").trim_start_matches('$'), 16)
+            .unwrap_or_else(|err| panic!("{}={}: {}", var, hex, err)),
+        Err(_) => default,
+    }
+}
+
+// Runs until the program traps in a jump or branch to itself, returning
+// the trap PC, or None if the binary isn't available
+fn run_to_trap(file: &str, port: Option<u16>) -> Option<u16> {
+    let Ok(dir) = env::var("KLAUS_TESTS_DIR") else {
+        eprintln!("KLAUS_TESTS_DIR not set; skipping {}", file);
+        return None;
+    };
+    let path = Path::new(&dir).join(file);
+    let Ok(image) = fs::read(&path) else {
+        eprintln!("{} not found; skipping", path.display());
+        return None;
+    };
+
+    let mut ram = vec![0; 0x10000];
+    let len = image.len().min(ram.len());
+    ram[..len].copy_from_slice(&image[..len]);
+    if let Some(port) = port {
+        ram[port as usize] = 0;
+    }
+
+    // The tests check decimal mode, which the 2A03 leaves out
+    let mut cpu = Cpu2A03::with_decimal_mode(FlatBus { ram, port, nmi: false }, true);
+    cpu.pc = START;
+    for _ in 0..MAX_INSTRUCTIONS {
+        let pc = cpu.pc;
+        if let Err(err) = cpu.step() {
+            panic!("{} at {:04X}", err, pc);
+        }
+        if cpu.pc == pc {
+            return Some(pc);
+        }
+    }
+    panic!("{} never trapped; last PC {:04X}", file, cpu.pc);
+}
+
+#[test]
+fn klaus_functional_test() {
+    let success = success_address("KLAUS_FUNCTIONAL_SUCCESS", FUNCTIONAL_SUCCESS);
+    if let Some(trap) = run_to_trap("6502_functional_test.bin", None) {
+        assert_eq!(trap, success, "trapped at {:04X}", trap);
+    }
+}
+
+#[test]
+fn klaus_interrupt_test() {
+    let success = success_address("KLAUS_INTERRUPT_SUCCESS", INTERRUPT_SUCCESS);
+    if let Some(trap) = run_to_trap("6502_interrupt_test.bin", Some(INTERRUPT_PORT)) {
+        assert_eq!(trap, success, "trapped at {:04X}", trap);
+    }
+}
@@ -0,0 +1,270 @@
+// tests/mappers.rs
+// Board behaviour seen through the cartridge buses: bank switching and IRQ counters
+
+use alphanes::nes::cart::{self, Mapper, Rom, SharedMapper};
+
+// NES 2.0 image for `mapper`/`submapper` with `prg_banks` 16KB PRG and
+// `chr_banks` 8KB CHR banks. Every PRG byte holds its 8KB bank number and
+// every CHR byte its 1KB bank number, so reads show what's mapped in.
+fn cart(mapper: u16, submapper: u8, prg_banks: u8, chr_banks: u8) -> SharedMapper {
+    let mut image = vec![
+        b'N',
+        b'E',
+        b'S',
+        0x1A,
+        prg_banks,
+        chr_banks,
+        (mapper as u8) << 4,
+        (mapper as u8 & 0xF0) | 0x08,
+        submapper << 4 | (mapper >> 8) as u8,
+        0,
+        0x07, // 8KB PRG RAM
+    ];
+    image.resize(16, 0);
+    for bank in 0..prg_banks as usize * 2 {
+        image.extend(std::iter::repeat_n(bank as u8, 0x2000));
+    }
+    for bank in 0..chr_banks as usize * 8 {
+        image.extend(std::iter::repeat_n(bank as u8, 0x0400));
+    }
+    cart::new_mapper(Rom::from_bytes(&image).unwrap()).unwrap()
+}
+
+// MMC1 register load: five writes to `addr`, LSB first
+fn mmc1_write(cart: &mut dyn Mapper, addr: u16, value: u8) {
+    for bit in 0..5 {
+        cart.cpu_write(addr, value >> bit);
+    }
+}
+
+#[test]
+fn mmc1_loads_registers_serially() {
+    let cart = cart(1, 0, 4, 2);
+    let mut cart = cart.borrow_mut();
+    // Power-on PRG mode 3: bank 0 at $8000, the last bank fixed at $C000
+    assert_eq!(cart.cpu_read(0x8000), Some(0));
+    assert_eq!(cart.cpu_read(0xC000), Some(6));
+
+    // Four bits in, nothing has changed yet
+    for bit in 0..4 {
+        cart.cpu_write(0xE000, 2 >> bit);
+    }
+    assert_eq!(cart.cpu_read(0x8000), Some(0));
+    cart.cpu_write(0xE000, 0);
+    assert_eq!(cart.cpu_read(0x8000), Some(4));
+    assert_eq!(cart.cpu_read(0xC000), Some(6));
+}
+
+#[test]
+fn mmc1_reset_bit_clears_the_shift_register_and_fixes_the_last_bank() {
+    let cart = cart(1, 0, 4, 2);
+    let mut cart = cart.borrow_mut();
+    // PRG mode 2 fixes the first bank at $8000 and switches $C000
+    mmc1_write(&mut *cart, 0x8000, 0x08);
+    mmc1_write(&mut *cart, 0xE000, 2);
+    assert_eq!(cart.cpu_read(0x8000), Some(0));
+    assert_eq!(cart.cpu_read(0xC000), Some(4));
+
+    // Two stray bits are thrown away by the reset, which also goes back to
+    // mode 3
+    cart.cpu_write(0xE000, 1);
+    cart.cpu_write(0xE000, 1);
+    cart.cpu_write(0x8000, 0x80);
+    assert_eq!(cart.cpu_read(0x8000), Some(4));
+    assert_eq!(cart.cpu_read(0xC000), Some(6));
+    mmc1_write(&mut *cart, 0xE000, 1);
+    assert_eq!(cart.cpu_read(0x8000), Some(2));
+}
+
+#[test]
+fn mmc1_switches_chr_in_4kb_halves() {
+    let cart = cart(1, 0, 2, 2);
+    let mut cart = cart.borrow_mut();
+    // 8KB mode ignores the low bank bit
+    mmc1_write(&mut *cart, 0xA000, 3);
+    assert_eq!(cart.ppu_read(0x0000), 8);
+    assert_eq!(cart.ppu_read(0x1000), 12);
+
+    mmc1_write(&mut *cart, 0x8000, 0x1C);
+    mmc1_write(&mut *cart, 0xC000, 1);
+    assert_eq!(cart.ppu_read(0x0000), 12);
+    assert_eq!(cart.ppu_read(0x1000), 4);
+}
+
+// Run the cartridge's CPU-cycle counters for `cycles`
+fn clock(cart: &mut dyn Mapper, cycles: usize) {
+    for _ in 0..cycles {
+        cart.cpu_clock();
+    }
+}
+
+#[test]
+fn vrc4_switches_prg_and_swaps_the_fixed_bank() {
+    // VRC4a: A1 and A2 select the registers
+    let cart = cart(21, 1, 4, 4);
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0x8000, 3);
+    cart.cpu_write(0xA000, 5);
+    assert_eq!(cart.cpu_read(0x8000), Some(3));
+    assert_eq!(cart.cpu_read(0xA000), Some(5));
+    assert_eq!(cart.cpu_read(0xC000), Some(6));
+    assert_eq!(cart.cpu_read(0xE000), Some(7));
+
+    // $9002 swaps $8000 with the second-last bank at $C000
+    cart.cpu_write(0x9004, 0x02);
+    assert_eq!(cart.cpu_read(0x8000), Some(6));
+    assert_eq!(cart.cpu_read(0xC000), Some(3));
+}
+
+#[test]
+fn vrc4_chr_banks_load_by_nibble() {
+    let cart = cart(21, 1, 4, 4);
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0xB000, 0x02); // $B000: bank 0 low nibble
+    cart.cpu_write(0xB002, 0x01); // $B001: bank 0 high bits
+    cart.cpu_write(0xE004, 0x07); // $E002: bank 7 low nibble
+    assert_eq!(cart.ppu_read(0x0000), 0x12);
+    assert_eq!(cart.ppu_read(0x03FF), 0x12);
+    assert_eq!(cart.ppu_read(0x1C00), 0x07);
+}
+
+#[test]
+fn vrc2a_drops_the_low_chr_bank_bit() {
+    let cart = cart(22, 0, 4, 4);
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0xB000, 0x08);
+    assert_eq!(cart.ppu_read(0x0000), 4);
+}
+
+#[test]
+fn vrc4_cycle_mode_irq_fires_when_the_counter_wraps() {
+    let cart = cart(21, 1, 4, 4);
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0xF000, 0x0D); // Latch $FD
+    cart.cpu_write(0xF002, 0x0F);
+    cart.cpu_write(0xF004, 0x06); // Enabled, counting CPU cycles
+    clock(&mut *cart, 2);
+    assert!(!cart.irq_asserted());
+    clock(&mut *cart, 1);
+    assert!(cart.irq_asserted());
+
+    cart.cpu_write(0xF006, 0); // Acknowledge
+    assert!(!cart.irq_asserted());
+}
+
+#[test]
+fn vrc4_scanline_mode_irq_counts_lines_of_113_2_3_cycles() {
+    let cart = cart(21, 1, 4, 4);
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0xF000, 0x0E); // Latch $FE: two lines
+    cart.cpu_write(0xF002, 0x0F);
+    cart.cpu_write(0xF004, 0x02);
+    clock(&mut *cart, 227);
+    assert!(!cart.irq_asserted());
+    clock(&mut *cart, 1);
+    assert!(cart.irq_asserted());
+}
+
+// FME-7 command then parameter
+fn fme7_write(cart: &mut dyn Mapper, command: u8, value: u8) {
+    cart.cpu_write(0x8000, command);
+    cart.cpu_write(0xA000, value);
+}
+
+#[test]
+fn fme7_switches_8kb_prg_and_1kb_chr_banks() {
+    let cart = cart(69, 0, 8, 4);
+    let mut cart = cart.borrow_mut();
+    fme7_write(&mut *cart, 0x8, 0x03); // ROM bank 3 at $6000
+    fme7_write(&mut *cart, 0x9, 0x05);
+    fme7_write(&mut *cart, 0xB, 0x0A);
+    fme7_write(&mut *cart, 0x7, 0x1B);
+    assert_eq!(cart.cpu_read(0x6000), Some(3));
+    assert_eq!(cart.cpu_read(0x8000), Some(5));
+    assert_eq!(cart.cpu_read(0xC000), Some(10));
+    assert_eq!(cart.cpu_read(0xE000), Some(15));
+    assert_eq!(cart.ppu_read(0x1C00), 0x1B);
+}
+
+#[test]
+fn fme7_irq_fires_when_the_counter_wraps() {
+    let cart = cart(69, 0, 8, 4);
+    let mut cart = cart.borrow_mut();
+    fme7_write(&mut *cart, 0xE, 0x05);
+    fme7_write(&mut *cart, 0xF, 0x00);
+    fme7_write(&mut *cart, 0xD, 0x81); // Count and raise IRQs
+    clock(&mut *cart, 5);
+    assert!(!cart.irq_asserted());
+    clock(&mut *cart, 1);
+    assert!(cart.irq_asserted());
+
+    // Writing the control register acknowledges
+    fme7_write(&mut *cart, 0xD, 0x80);
+    assert!(!cart.irq_asserted());
+    clock(&mut *cart, 0x10000);
+    assert!(!cart.irq_asserted(), "counting without IRQs enabled");
+}
+
+#[test]
+fn uxrom_bus_conflicts_and_writes_with_rom() {
+    // Without conflicts (submapper 1) the value goes through as written
+    let clean = cart(2, 1, 8, 0);
+    clean.borrow_mut().cpu_write(0xC000, 0x07);
+    assert_eq!(clean.borrow_mut().cpu_read(0x8000), Some(14));
+
+    // With them (submapper 2) the fixed bank's byte there, $0E, is ANDed in
+    let conflicted = cart(2, 2, 8, 0);
+    let mut cart = conflicted.borrow_mut();
+    cart.cpu_write(0xC000, 0x07);
+    assert_eq!(cart.cpu_read(0x8000), Some(12));
+    cart.cpu_write(0x8000, 0x07); // Bank 6's byte is $0C
+    assert_eq!(cart.cpu_read(0x8000), Some(8));
+}
+
+#[test]
+fn cnrom_bus_conflicts_mask_the_chr_bank() {
+    let clean = cart(3, 1, 2, 4);
+    clean.borrow_mut().cpu_write(0xC000, 0x03);
+    assert_eq!(clean.borrow_mut().ppu_read(0x0000), 24);
+
+    // $C000 holds $02 from PRG bank 2
+    let conflicted = cart(3, 2, 2, 4);
+    conflicted.borrow_mut().cpu_write(0xC000, 0x03);
+    assert_eq!(conflicted.borrow_mut().ppu_read(0x0000), 16);
+}
+
+#[test]
+fn vrc_submappers_pick_the_register_wiring() {
+    // $9008 is $9002 (PRG swap) on VRC4e, which decodes A2/A3, but $9000
+    // (mirroring) on VRC4f, which decodes A0/A1
+    let vrc4e = cart(23, 2, 4, 4);
+    vrc4e.borrow_mut().cpu_write(0x9008, 0x02);
+    assert_eq!(vrc4e.borrow_mut().cpu_read(0x8000), Some(6));
+
+    let vrc4f = cart(23, 1, 4, 4);
+    vrc4f.borrow_mut().cpu_write(0x9008, 0x02);
+    assert_eq!(vrc4f.borrow_mut().cpu_read(0x8000), Some(0));
+}
+
+#[test]
+fn vrc2_submapper_has_no_irq_counter() {
+    for (submapper, fires) in [(1, true), (3, false)] {
+        // VRC4f and VRC2b share mapper 23 and the A0/A1 wiring
+        let cart = cart(23, submapper, 4, 4);
+        let mut cart = cart.borrow_mut();
+        cart.cpu_write(0xF000, 0x0F);
+        cart.cpu_write(0xF001, 0x0F);
+        cart.cpu_write(0xF002, 0x06);
+        clock(&mut *cart, 1);
+        assert_eq!(cart.irq_asserted(), fires, "submapper {}", submapper);
+    }
+}
+
+#[test]
+fn mmc1_serom_submapper_ignores_prg_banking() {
+    let cart = cart(1, 5, 2, 2);
+    let mut cart = cart.borrow_mut();
+    mmc1_write(&mut *cart, 0xE000, 1);
+    assert_eq!(cart.cpu_read(0x8000), Some(0));
+    assert_eq!(cart.cpu_read(0xC000), Some(2));
+}
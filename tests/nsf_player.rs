@@ -0,0 +1,106 @@
+// tests/nsf_player.rs
+// NSF playback: INIT/PLAY calls and the expansion chips a file declares
+
+use alphanes::nes::cart::Nsf;
+use alphanes::nes::cpu::Bus;
+use alphanes::nes::nsf::NsfPlayer;
+
+// One-song NSF loaded at $8000 whose INIT runs `init` then returns, with a
+// PLAY that returns at once. `chips` is header byte $7B.
+fn nsf(init: &[u8], chips: u8) -> Nsf {
+    let mut image = vec![0; 0x80];
+    image[..5].copy_from_slice(b"NESM\x1A");
+    image[5] = 1;
+    image[6] = 1; // Songs
+    image[7] = 1;
+    image[0x08..0x0A].copy_from_slice(&0x8000u16.to_le_bytes()); // Load
+    image[0x0A..0x0C].copy_from_slice(&0x8000u16.to_le_bytes()); // INIT
+    image[0x0C..0x0E].copy_from_slice(&0x8100u16.to_le_bytes()); // PLAY
+    image[0x6E..0x70].copy_from_slice(&16_639u16.to_le_bytes());
+    image[0x7B] = chips;
+    let mut program = vec![0x60; 0x200];
+    program[..init.len()].copy_from_slice(init);
+    program[init.len()] = 0x60; // RTS
+    image.extend(program);
+    Nsf::from_bytes(&image).unwrap()
+}
+
+// Peak-to-peak level over a few frames of playback
+fn swing(player: &mut NsfPlayer) -> f32 {
+    let mut samples = Vec::new();
+    for _ in 0..4 {
+        player.play_frame();
+        player.take_samples(&mut samples);
+    }
+    let samples = &samples[samples.len() / 2..];
+    let max = samples.iter().copied().fold(f32::MIN, f32::max);
+    let min = samples.iter().copied().fold(f32::MAX, f32::min);
+    max - min
+}
+
+// INIT that starts a Sunsoft 5B tone on channel A through $C000/$E000
+#[rustfmt::skip]
+const SUNSOFT_5B_TONE: [u8; 30] = [
+    0xA9, 0x00, 0x8D, 0x00, 0xC0, 0xA9, 0x40, 0x8D, 0x00, 0xE0, // Period $040
+    0xA9, 0x07, 0x8D, 0x00, 0xC0, 0xA9, 0x3E, 0x8D, 0x00, 0xE0, // Tone A only
+    0xA9, 0x08, 0x8D, 0x00, 0xC0, 0xA9, 0x0F, 0x8D, 0x00, 0xE0, // Volume 15
+];
+
+#[test]
+fn declared_sunsoft_5b_is_heard() {
+    let mut player = NsfPlayer::new(nsf(&SUNSOFT_5B_TONE, 0x20));
+    assert!(swing(&mut player) > 0.05);
+}
+
+#[test]
+fn undeclared_chips_stay_off_the_bus() {
+    let mut player = NsfPlayer::new(nsf(&SUNSOFT_5B_TONE, 0x00));
+    assert!(swing(&mut player) < 0.001);
+}
+
+
+// INIT that fills the FDS wavetable with a ramp and plays it
+#[rustfmt::skip]
+const FDS_RAMP: [u8; 36] = [
+    0xA9, 0x80, 0x8D, 0x89, 0x40, // Wavetable writable
+    0xA2, 0x00,                   // LDX #0
+    0x8A, 0x9D, 0x40, 0x40,       // loop: TXA; STA $4040,X
+    0xE8, 0xE0, 0x40, 0xD0, 0xF7, // INX; CPX #64; BNE loop
+    0xA9, 0x00, 0x8D, 0x89, 0x40, // Playback, master volume 2/2
+    0xA9, 0xA0, 0x8D, 0x80, 0x40, // Gain 32, envelope off
+    0xA9, 0x40, 0x8D, 0x82, 0x40, // Frequency $040
+    0xA9, 0x00, 0x8D, 0x83, 0x40,
+];
+
+#[test]
+fn declared_fds_audio_is_heard_and_read_back() {
+    let mut player = NsfPlayer::new(nsf(&FDS_RAMP, 0x04));
+    assert!(swing(&mut player) > 0.05);
+    assert_eq!(player.cpu.bus.read(0x4090), 0x20);
+    assert_eq!(player.cpu.bus.read(0x4041), 1);
+
+    let mut without = NsfPlayer::new(nsf(&FDS_RAMP, 0x00));
+    assert!(swing(&mut without) < 0.001);
+}
+
+#[test]
+fn a_jam_in_one_song_doesnt_stop_the_next() {
+    // Song 1 jams in INIT; song 2 starts the 5B tone
+    #[rustfmt::skip]
+    let mut init = vec![
+        0xC9, 0x00, 0xD0, 0x01, // CMP #0; BNE +1
+        0x02,                   // JAM for song 1
+    ];
+    init.extend(SUNSOFT_5B_TONE);
+    let mut file = nsf(&init, 0x20);
+    file.header.total_songs = 2;
+    let mut player = NsfPlayer::new(file);
+    assert!(player.cpu.is_jammed());
+    assert!(swing(&mut player) < 0.001);
+    assert!(player.cpu.is_jammed());
+
+    player.next_track();
+    assert_eq!(player.current_song(), 2);
+    assert!(!player.cpu.is_jammed());
+    assert!(swing(&mut player) > 0.05);
+}
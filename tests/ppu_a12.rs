@@ -0,0 +1,127 @@
+// tests/ppu_a12.rs
+// Filtered PPU A12 rising edges as an MMC3-style mapper sees them
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use alphanes::nes::cart::Mapper;
+use alphanes::nes::ppu::{Mirroring, Ppu};
+
+// 8KB of CHR RAM that counts the A12 rises it's told about
+struct Counter {
+    chr: Vec<u8>,
+    rises: Rc<Cell<u32>>,
+}
+
+impl Mapper for Counter {
+    fn cpu_read(&mut self, _addr: u16) -> Option<u8> {
+        None
+    }
+
+    fn cpu_write(&mut self, _addr: u16, _data: u8) {}
+
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.chr[addr as usize & 0x1FFF]
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.chr[addr as usize & 0x1FFF] = data;
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        Mirroring::Vertical
+    }
+
+    fn ppu_a12_rise(&mut self) {
+        self.rises.set(self.rises.get() + 1);
+    }
+}
+
+fn ppu() -> (Ppu, Rc<Cell<u32>>) {
+    let rises = Rc::new(Cell::new(0));
+    let mapper = Counter { chr: vec![0; 0x2000], rises: rises.clone() };
+    (Ppu::new(Rc::new(RefCell::new(mapper))), rises)
+}
+
+fn run_to(ppu: &mut Ppu, scanline: i16, cycle: usize) {
+    while !(ppu.scanline == scanline && ppu.cycle == cycle) {
+        ppu.step();
+    }
+}
+
+#[test]
+fn one_rise_per_rendered_line_with_sprites_on_the_right_table() {
+    let (mut ppu, rises) = ppu();
+    ppu.write_register(0, 0x08);
+    ppu.write_register(1, 0x18);
+
+    // The first edge of each line comes from the sprite fetches
+    run_to(&mut ppu, 0, 0);
+    rises.set(0);
+    run_to(&mut ppu, 0, 270);
+    assert_eq!(rises.get(), 1);
+
+    // Lines 1-239 and the pre-render line, which fetches sprites too
+    rises.set(0);
+    run_to(&mut ppu, 0, 0);
+    assert_eq!(rises.get(), 240);
+}
+
+#[test]
+fn background_on_the_right_table_rises_after_the_sprite_fetches() {
+    let (mut ppu, rises) = ppu();
+    ppu.write_register(0, 0x10);
+    ppu.write_register(1, 0x18);
+
+    // The nametable fetches between tiles drop A12 too briefly to count
+    run_to(&mut ppu, 0, 0);
+    rises.set(0);
+    run_to(&mut ppu, 0, 320);
+    assert_eq!(rises.get(), 0);
+    run_to(&mut ppu, 0, 330);
+    assert_eq!(rises.get(), 1);
+}
+
+#[test]
+fn no_rises_with_rendering_off() {
+    let (mut ppu, rises) = ppu();
+    ppu.write_register(0, 0x08);
+    run_to(&mut ppu, 0, 0);
+    run_to(&mut ppu, 0, 0);
+    assert_eq!(rises.get(), 0);
+}
+
+#[test]
+fn ppuaddr_writes_drive_a12_outside_rendering() {
+    let (mut ppu, rises) = ppu();
+    run_to(&mut ppu, 241, 0);
+
+    ppu.write_register(6, 0x10);
+    ppu.write_register(6, 0x00);
+    assert_eq!(rises.get(), 1);
+
+    // Dropping and raising it right away is filtered out
+    ppu.write_register(6, 0x00);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(6, 0x10);
+    ppu.write_register(6, 0x00);
+    assert_eq!(rises.get(), 1);
+
+    // Held low long enough, the next rise counts
+    ppu.write_register(6, 0x00);
+    ppu.write_register(6, 0x00);
+    run_to(&mut ppu, 242, 0);
+    ppu.write_register(6, 0x10);
+    ppu.write_register(6, 0x00);
+    assert_eq!(rises.get(), 2);
+}
+
+#[test]
+fn ppudata_increments_can_cross_into_the_right_table() {
+    let (mut ppu, rises) = ppu();
+    run_to(&mut ppu, 241, 0);
+    ppu.write_register(6, 0x0F);
+    ppu.write_register(6, 0xFF);
+    ppu.write_register(7, 0x00);
+    assert_eq!(rises.get(), 1);
+}
@@ -0,0 +1,44 @@
+// tests/ppu_chr.rs
+// Pattern table accesses reach the cartridge's CHR ROM/RAM through the mapper
+
+mod common;
+
+use alphanes::nes::cart::{self, Rom, SharedMapper};
+use alphanes::nes::ppu::Ppu;
+
+// iNES image for `mapper` with `chr_banks` 8KB CHR banks, each filled with
+// its bank number (no CHR ROM means 8KB of CHR RAM)
+fn mapper(mapper: u8, chr_banks: u8) -> SharedMapper {
+    let chr: Vec<u8> = (0..chr_banks).flat_map(|bank| common::chr(|chr| chr.fill(bank))).collect();
+    let rom = Rom::from_bytes(&common::ines(mapper, &common::prg(&[]), &chr)).unwrap();
+    cart::new_mapper(rom).unwrap()
+}
+
+// PPUDATA read of `addr`, past the read buffer
+fn read(ppu: &mut Ppu, addr: u16) -> u8 {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    ppu.read_register(7);
+    ppu.read_register(7)
+}
+
+#[test]
+fn chr_ram_round_trips_through_ppudata() {
+    let mut ppu = Ppu::new(mapper(0, 0));
+    ppu.write_register(6, 0x12);
+    ppu.write_register(6, 0x34);
+    ppu.write_register(7, 0xA5);
+    assert_eq!(read(&mut ppu, 0x1234), 0xA5);
+}
+
+#[test]
+fn chr_bank_switches_are_seen_by_the_ppu() {
+    let cart = mapper(3, 4);
+    let mut ppu = Ppu::new(cart.clone());
+    assert_eq!(read(&mut ppu, 0x0000), 0);
+
+    // CNROM selects the 8KB CHR bank through writes to $8000-$FFFF
+    cart.borrow_mut().cpu_write(0x8000, 2);
+    assert_eq!(read(&mut ppu, 0x0000), 2);
+    assert_eq!(read(&mut ppu, 0x1FFF), 2);
+}
@@ -0,0 +1,184 @@
+// tests/ppu_debug.rs
+// Debug views of nametables, pattern tables, OAM and palette RAM
+
+mod common;
+
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::ppu::{Palette, Ppu, NAMETABLE_VIEW_WIDTH, PATTERN_VIEW_SIZE};
+
+// NROM with horizontal mirroring; tile 1 is solid color 1, tile 2 solid 3
+// and tile 3 a single color 1 pixel in its top-left corner
+fn ppu() -> Ppu {
+    common::ppu(|chr| {
+        chr[0x10..0x18].fill(0xFF);
+        chr[0x20..0x30].fill(0xFF);
+        chr[0x30] = 0x80;
+    })
+}
+
+fn poke(ppu: &mut Ppu, addr: u16, data: u8) {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    ppu.write_register(7, data);
+}
+
+fn color(index: u16) -> u32 {
+    Palette::ntsc().color(index)
+}
+
+#[test]
+fn nametable_view_applies_attributes_and_mirroring() {
+    let mut ppu = ppu();
+    poke(&mut ppu, 0x2000, 0x01);
+    poke(&mut ppu, 0x2001, 0x02);
+    poke(&mut ppu, 0x23C0, 0b0000_0010); // Top-left quadrant: palette 2
+    poke(&mut ppu, 0x3F00, 0x0F);
+    poke(&mut ppu, 0x3F09, 0x16);
+    poke(&mut ppu, 0x3F0B, 0x2A);
+    // Scroll back to the origin
+    ppu.write_register(0, 0x00);
+    ppu.write_register(5, 0x00);
+    ppu.write_register(5, 0x00);
+
+    let view = ppu.nametable_view();
+    let at = |x: usize, y: usize| view[y * NAMETABLE_VIEW_WIDTH + x];
+    assert_eq!(at(3, 3), color(0x16));
+    assert_eq!(at(11, 3), color(0x2A));
+    assert_eq!(at(19, 3), color(0x0F));
+    // $2400 mirrors $2000; $2800 is the other nametable
+    assert_eq!(at(256 + 3, 3), color(0x16));
+    assert_eq!(at(3, 243), color(0x0F));
+}
+
+#[test]
+fn nametable_view_outlines_the_scroll_window() {
+    let mut ppu = ppu();
+    poke(&mut ppu, 0x3F00, 0x0F);
+    ppu.write_register(0, 0x01); // Right-hand nametable
+    ppu.write_register(5, 0x10);
+    ppu.write_register(5, 0x08);
+
+    let view = ppu.nametable_view();
+    let at = |x: usize, y: usize| view[y * NAMETABLE_VIEW_WIDTH + x];
+    let inverted = color(0x0F) ^ 0xFFFFFF;
+    assert_eq!(at(256 + 16, 8), inverted);
+    assert_eq!(at(256 + 16, 100), inverted);
+    assert_eq!(at(256 + 17, 100), color(0x0F));
+    // The right edge wraps around to the left-hand nametable
+    assert_eq!(at(15, 100), inverted);
+    assert_eq!(at(15, 247), inverted);
+}
+
+#[test]
+fn pattern_table_view_uses_the_selected_palette() {
+    let mut ppu = ppu();
+    poke(&mut ppu, 0x3F00, 0x0F);
+    poke(&mut ppu, 0x3F15, 0x16);
+    poke(&mut ppu, 0x3F17, 0x2A);
+
+    let view = ppu.pattern_table_view(0, 5);
+    let at = |x: usize, y: usize| view[y * PATTERN_VIEW_SIZE + x];
+    assert_eq!(view.len(), PATTERN_VIEW_SIZE * PATTERN_VIEW_SIZE);
+    assert_eq!(at(3, 3), color(0x0F));
+    assert_eq!(at(11, 3), color(0x16));
+    assert_eq!(at(19, 7), color(0x2A));
+    // Nothing in the $1000 table
+    assert!(ppu.pattern_table_view(1, 5).iter().all(|&pixel| pixel == color(0x0F)));
+}
+
+#[test]
+fn pattern_table_view_follows_chr_bank_switches() {
+    // CNROM: bank 0 blank, bank 1 solid color 3
+    let chr = [common::chr(|_| {}), common::chr(|chr| chr.fill(0xFF))].concat();
+    let image = common::ines(3, &common::prg(&[]), &chr);
+    let cart = cart::new_mapper(Rom::from_bytes(&image).unwrap()).unwrap();
+    let mut ppu = Ppu::new(cart.clone());
+    poke(&mut ppu, 0x3F00, 0x0F);
+    poke(&mut ppu, 0x3F03, 0x30);
+    assert!(ppu.pattern_table_view(1, 0).iter().all(|&pixel| pixel == color(0x0F)));
+
+    cart.borrow_mut().cpu_write(0x8000, 1);
+    assert!(ppu.pattern_table_view(1, 0).iter().all(|&pixel| pixel == color(0x30)));
+}
+
+fn write_oam(ppu: &mut Ppu, index: u8, entry: [u8; 4]) {
+    ppu.write_register(3, index * 4);
+    for byte in entry {
+        ppu.write_register(4, byte);
+    }
+}
+
+#[test]
+fn oam_view_decodes_entries_and_draws_flipped_thumbnails() {
+    let mut ppu = ppu();
+    poke(&mut ppu, 0x3F00, 0x0F);
+    poke(&mut ppu, 0x3F19, 0x16);
+    write_oam(&mut ppu, 0, [10, 3, 0x02, 20]);
+    write_oam(&mut ppu, 1, [10, 3, 0xC2, 40]);
+
+    let oam = ppu.oam_view();
+    assert_eq!(oam.len(), 64);
+    assert_eq!((oam[1].index, oam[1].y, oam[1].tile, oam[1].attributes, oam[1].x), (1, 10, 3, 0xC2, 40));
+    assert_eq!(oam[0].thumbnail.len(), 64);
+    assert_eq!(oam[0].thumbnail[0], color(0x16));
+    assert_eq!(oam[0].thumbnail[63], color(0x0F));
+    // Flipped both ways, the pixel moves to the bottom-right corner
+    assert_eq!(oam[1].thumbnail[0], color(0x0F));
+    assert_eq!(oam[1].thumbnail[63], color(0x16));
+
+    // 8x16 sprites get taller thumbnails
+    ppu.write_register(0, 0x20);
+    assert_eq!(ppu.oam_view()[0].thumbnail.len(), 128);
+}
+
+#[test]
+fn oam_view_flags_sprites_on_the_current_scanline() {
+    let mut ppu = ppu();
+    write_oam(&mut ppu, 0, [10, 1, 0, 0]);
+    write_oam(&mut ppu, 1, [30, 1, 0, 0]);
+    // Everything else off the bottom of the screen
+    for index in 2..64 {
+        write_oam(&mut ppu, index, [0xEF, 0, 0, 0]);
+    }
+
+    while ppu.scanline != 11 {
+        ppu.step();
+    }
+    let oam = ppu.oam_view();
+    let on_scanline: Vec<u8> = oam.iter().filter(|entry| entry.on_scanline).map(|entry| entry.index).collect();
+    assert_eq!(on_scanline, [0]);
+
+    while ppu.scanline != 19 {
+        ppu.step();
+    }
+    assert!(ppu.oam_view().iter().all(|entry| !entry.on_scanline));
+}
+
+#[test]
+fn palette_ram_view_resolves_entries_and_mirrors() {
+    let mut ppu = ppu();
+    ppu.poke_palette(0x10, 0x16);
+    ppu.poke_palette(0x05, 0x2A);
+
+    let entries = ppu.palette_ram();
+    assert_eq!(entries[0x00], 0x16);
+    assert_eq!(entries[0x10], 0x16);
+    assert_eq!(entries[0x05], 0x2A);
+    let view = ppu.palette_ram_view();
+    assert_eq!(view[0x00], color(0x16));
+    assert_eq!(view[0x05], color(0x2A));
+}
+
+#[test]
+fn poking_the_palette_leaves_the_address_registers_alone() {
+    let mut ppu = ppu();
+    ppu.write_register(6, 0x21);
+    ppu.write_register(6, 0x08);
+    ppu.write_register(6, 0x3F);
+    // Halfway through a PPUADDR write
+    ppu.poke_palette(0x00, 0x16);
+    ppu.write_register(6, 0x00);
+
+    assert_eq!(ppu.vram_addr, 0x3F00);
+    assert_eq!(ppu.read_register(7) & 0x3F, 0x16);
+}
@@ -0,0 +1,112 @@
+// tests/ppu_events.rs
+// Per-frame timeline of register accesses, NMIs, sprite 0 hits and IRQs
+
+mod common;
+
+use alphanes::nes::cart::Rom;
+use alphanes::nes::ppu::{Ppu, PpuEvent, PpuEventKind};
+use alphanes::nes::Nes;
+use common::run_to;
+
+// NROM with tile 1 solid color 1
+fn ppu() -> Ppu {
+    let mut ppu = common::ppu(|chr| chr[0x10..0x18].fill(0xFF));
+    ppu.record_events = true;
+    ppu
+}
+
+fn finish_frame(ppu: &mut Ppu) {
+    while !ppu.step() {}
+}
+
+#[test]
+fn register_accesses_are_placed_on_the_frame_they_happened_in() {
+    let mut ppu = ppu();
+    run_to(&mut ppu, 100, 50);
+    ppu.write_register(0x2001, 0x00);
+    run_to(&mut ppu, 120, 7);
+    let data = ppu.read_register(2);
+
+    // Nothing shows until the frame is done
+    assert!(ppu.events().is_empty());
+    finish_frame(&mut ppu);
+    assert_eq!(
+        ppu.events(),
+        [
+            PpuEvent { scanline: 100, dot: 50, kind: PpuEventKind::RegisterWrite { register: 1, data: 0x00 } },
+            PpuEvent { scanline: 120, dot: 7, kind: PpuEventKind::RegisterRead { register: 2, data } },
+        ]
+    );
+
+    finish_frame(&mut ppu);
+    assert!(ppu.events().is_empty());
+}
+
+#[test]
+fn nothing_is_logged_unless_recording() {
+    let mut ppu = ppu();
+    ppu.record_events = false;
+    ppu.write_register(0, 0x80);
+    finish_frame(&mut ppu);
+    finish_frame(&mut ppu);
+    assert!(ppu.events().is_empty());
+}
+
+#[test]
+fn nmi_and_sprite_zero_hit_are_logged() {
+    let mut ppu = ppu();
+    // Tile 1 under sprite 0, which is tile 1 at (20, 10)
+    ppu.write_register(6, 0x20);
+    ppu.write_register(6, 0x22);
+    ppu.write_register(7, 0x01);
+    ppu.write_register(3, 0x00);
+    for byte in [10, 1, 0, 20] {
+        ppu.write_register(4, byte);
+    }
+    ppu.write_register(6, 0x00);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(0, 0x80);
+    ppu.write_register(1, 0x1E);
+    finish_frame(&mut ppu);
+    finish_frame(&mut ppu);
+
+    let events: Vec<_> = ppu
+        .events()
+        .iter()
+        .filter(|event| matches!(event.kind, PpuEventKind::Nmi | PpuEventKind::SpriteZeroHit))
+        .copied()
+        .collect();
+    assert_eq!(
+        events,
+        [
+            PpuEvent { scanline: 11, dot: 21, kind: PpuEventKind::SpriteZeroHit },
+            PpuEvent { scanline: 241, dot: 1, kind: PpuEventKind::Nmi },
+        ]
+    );
+}
+
+#[test]
+fn cartridge_irqs_are_logged_by_the_bus() {
+    // FME-7 with its CPU cycle counter set to fire shortly after reset
+    let program = [
+        0xA9, 0x0E, 0x8D, 0x00, 0x80, // LDA #$0E; STA $8000
+        0xA9, 0x00, 0x8D, 0x00, 0xA0, // LDA #$00; STA $A000
+        0xA9, 0x0F, 0x8D, 0x00, 0x80, // LDA #$0F; STA $8000
+        0xA9, 0x10, 0x8D, 0x00, 0xA0, // LDA #$10; STA $A000, counter $1000
+        0xA9, 0x0D, 0x8D, 0x00, 0x80, // LDA #$0D; STA $8000
+        0xA9, 0x81, 0x8D, 0x00, 0xA0, // LDA #$81; STA $A000, count and raise IRQs
+        0x4C, 0x1E, 0xE0, // JMP *
+    ];
+    let mut prg = common::prg(&[]);
+    prg[0x2000..0x2000 + program.len()].copy_from_slice(&program);
+    prg[0x3FFD] = 0xE0;
+    let image = common::ines(69, &prg, &common::chr(|_| {}));
+    let mut nes = Nes::new(Rom::from_bytes(&image).unwrap()).unwrap();
+    nes.cpu.bus.ppu.record_events = true;
+    nes.run_frame().unwrap();
+
+    let irqs: Vec<_> = nes.cpu.bus.ppu.events().iter().filter(|event| event.kind == PpuEventKind::Irq).collect();
+    assert_eq!(irqs.len(), 1);
+    // 4096 CPU cycles in, about 36 lines down
+    assert!((35..=37).contains(&irqs[0].scanline), "{:?}", irqs[0]);
+}
@@ -0,0 +1,66 @@
+// tests/ppu_nmi.rs
+// VBlank flag and NMI output around the start of VBlank, including $2002 races
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+use common::run_to;
+
+fn ppu() -> Ppu {
+    common::ppu(|_| {})
+}
+
+#[test]
+fn vblank_start_raises_nmi_when_enabled() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0x80);
+    run_to(&mut ppu, 241, 1);
+    assert!(ppu.nmi_occurred);
+    assert_eq!(ppu.read_register(2) & 0x80, 0x80);
+}
+
+#[test]
+fn status_read_one_dot_early_suppresses_the_frame() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0x80);
+    run_to(&mut ppu, 241, 0);
+    assert_eq!(ppu.read_register(2) & 0x80, 0x00);
+    ppu.step();
+    assert!(!ppu.nmi_occurred);
+    assert_eq!(ppu.read_register(2) & 0x80, 0x00);
+}
+
+#[test]
+fn status_read_on_the_set_dot_reads_set_but_cancels_nmi() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0x80);
+    run_to(&mut ppu, 241, 1);
+    assert_eq!(ppu.read_register(2) & 0x80, 0x80);
+    assert!(!ppu.nmi_occurred);
+}
+
+#[test]
+fn enabling_nmi_during_vblank_raises_it_again() {
+    let mut ppu = ppu();
+    run_to(&mut ppu, 241, 10);
+    assert!(!ppu.nmi_occurred);
+
+    ppu.write_register(0, 0x80);
+    assert!(ppu.nmi_occurred);
+    ppu.nmi_occurred = false;
+
+    // Rewriting with NMIs still enabled isn't a new edge
+    ppu.write_register(0, 0x80);
+    assert!(!ppu.nmi_occurred);
+    ppu.write_register(0, 0x00);
+    ppu.write_register(0, 0x80);
+    assert!(ppu.nmi_occurred);
+}
+
+#[test]
+fn pre_render_line_clears_vblank() {
+    let mut ppu = ppu();
+    run_to(&mut ppu, 260, 340);
+    run_to(&mut ppu, -1, 1);
+    assert_eq!(ppu.read_register(2) & 0x80, 0x00);
+}
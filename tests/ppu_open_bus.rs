@@ -0,0 +1,70 @@
+// tests/ppu_open_bus.rs
+// The PPU's I/O latch: what undriven register bits read back, and decay
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+
+fn ppu() -> Ppu {
+    common::ppu(|_| {})
+}
+
+// About 600ms of NTSC dots, plus a little margin
+fn decay(ppu: &mut Ppu) {
+    for _ in 0..3_300_000 {
+        ppu.step();
+    }
+}
+
+#[test]
+fn write_only_registers_read_the_last_value_written() {
+    let mut ppu = ppu();
+    ppu.write_register(3, 0xA5);
+    for reg in [0, 1, 3, 5, 6] {
+        assert_eq!(ppu.read_register(reg), 0xA5);
+    }
+}
+
+#[test]
+fn status_low_bits_come_from_the_latch() {
+    let mut ppu = ppu();
+    ppu.write_register(5, 0x1F);
+    assert_eq!(ppu.read_register(2) & 0x1F, 0x1F);
+    ppu.registers.status = 0xC0;
+    // Status drives only the top three bits
+    assert_eq!(ppu.read_register(2), 0xDF);
+    assert_eq!(ppu.read_register(0), 0xDF);
+}
+
+#[test]
+fn palette_reads_keep_the_latch_in_the_top_two_bits() {
+    let mut ppu = ppu();
+    ppu.write_register(6, 0x3F);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(7, 0x2A);
+    ppu.write_register(6, 0x3F);
+    ppu.write_register(6, 0x00);
+    ppu.write_register(3, 0xC0);
+    assert_eq!(ppu.read_register(7), 0xEA);
+}
+
+#[test]
+fn latch_bits_decay_unless_refreshed() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0xFF);
+    ppu.registers.status = 0xE0;
+    decay(&mut ppu);
+    assert_eq!(ppu.read_register(5), 0x00);
+
+    // Refreshed halfway through, the status bits outlast the rest
+    ppu.write_register(3, 0xFF);
+    for _ in 0..2_000_000 {
+        ppu.step();
+    }
+    ppu.registers.status = 0xE0;
+    assert_eq!(ppu.read_register(2), 0xFF);
+    for _ in 0..2_000_000 {
+        ppu.step();
+    }
+    assert_eq!(ppu.read_register(5), 0xE0);
+}
@@ -0,0 +1,80 @@
+// tests/ppu_palette.rs
+// Generated NTSC palette, built-in palettes and .pal file loading
+
+use alphanes::nes::ppu::{Palette, PaletteError};
+
+fn channels(rgb: u32) -> (u32, u32, u32) {
+    (rgb >> 16 & 0xFF, rgb >> 8 & 0xFF, rgb & 0xFF)
+}
+
+#[test]
+fn generated_palette_has_the_familiar_hues() {
+    let palette = Palette::ntsc();
+    assert_eq!(palette.color(0x0F), 0x000000);
+    assert_eq!(palette.color(0x30), 0xFFFFFF);
+
+    let (r, g, b) = channels(palette.color(0x12));
+    assert!(b > r && b > g, "$12 is blue");
+    let (r, g, b) = channels(palette.color(0x16));
+    assert!(r > g && r > b, "$16 is red");
+    let (r, g, b) = channels(palette.color(0x1A));
+    assert!(g > r && g > b, "$1A is green");
+
+    // Grays have no chroma
+    let (r, g, b) = channels(palette.color(0x10));
+    assert!(r == g && g == b);
+}
+
+#[test]
+fn emphasis_dims_the_other_channels() {
+    let palette = Palette::ntsc();
+    // Red emphasis on white
+    let (r, g, b) = channels(palette.color(0x01 << 6 | 0x30));
+    assert!(r > g && r > b);
+    // All three darken everything but the black columns
+    let (r, g, b) = channels(palette.color(0x07 << 6 | 0x30));
+    assert!(r < 0xFF && g < 0xFF && b < 0xFF);
+    assert_eq!(palette.color(0x07 << 6 | 0x0F), 0x000000);
+}
+
+#[test]
+fn loads_64_color_pal_files() {
+    let data: Vec<u8> = (0..64u8).flat_map(|color| [color, color * 2, color * 3]).collect();
+    let palette = Palette::from_pal(&data).unwrap();
+    assert_eq!(palette.color(0x21), 0x214263);
+    // Emphasis is derived from the base colors
+    let (r, g, b) = channels(palette.color(0x04 << 6 | 0x21));
+    assert!(r < 0x21 && g < 0x42 && b == 0x63);
+}
+
+#[test]
+fn loads_512_color_pal_files() {
+    let data: Vec<u8> = (0..512u32).flat_map(|index| [(index >> 6) as u8, index as u8 & 0x3F, 0x80]).collect();
+    let palette = Palette::from_pal(&data).unwrap();
+    assert_eq!(palette.color(0x05 << 6 | 0x2A), 0x052A80);
+}
+
+#[test]
+fn rejects_other_sizes() {
+    assert!(matches!(Palette::from_pal(&[0; 100]), Err(PaletteError::InvalidSize(100))));
+}
+
+#[test]
+fn built_in_palettes_are_found_by_name() {
+    for name in Palette::NAMES {
+        assert!(Palette::named(name).is_some(), "{}", name);
+    }
+    assert_eq!(Palette::named("ntsc").unwrap().color(0x16), Palette::ntsc().color(0x16));
+    let fceux = Palette::named("FCEUX").unwrap();
+    assert_eq!(fceux.color(0x00), 0x747474);
+    assert_eq!(fceux.color(0x30), 0xFCFCFC);
+    assert!(Palette::named("no-such-palette").is_none());
+}
+
+#[test]
+fn built_in_palettes_approximate_emphasis() {
+    let palette = Palette::named("sony-cxa2025as").unwrap();
+    // Blue emphasis on white
+    let (r, g, b) = channels(palette.color(0x04 << 6 | 0x30));
+    assert!(b == 0xFF && r < 0xFF && g < 0xFF);
+}
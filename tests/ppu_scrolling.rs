@@ -0,0 +1,80 @@
+// tests/ppu_scrolling.rs
+// Loopy v/t/fine-x updates from register writes and the rendering counters
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+use common::run_to;
+
+fn ppu() -> Ppu {
+    common::ppu(|_| {})
+}
+
+#[test]
+fn register_writes_follow_the_loopy_model() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0x00);
+    ppu.read_register(2);
+
+    ppu.write_register(5, 0x7D);
+    assert_eq!(ppu.tram_addr, 0x000F);
+    assert_eq!(ppu.fine_x, 0x05);
+
+    ppu.write_register(5, 0x5E);
+    assert_eq!(ppu.tram_addr, 0x616F);
+
+    // PPUADDR's first write clears bit 14; the second copies t to v
+    ppu.write_register(6, 0x3D);
+    assert_eq!(ppu.tram_addr, 0x3D6F);
+    ppu.write_register(6, 0xF0);
+    assert_eq!(ppu.tram_addr, 0x3DF0);
+    assert_eq!(ppu.vram_addr, 0x3DF0);
+    assert_eq!(ppu.fine_x, 0x05);
+}
+
+#[test]
+fn status_read_resets_the_shared_toggle() {
+    let mut ppu = ppu();
+    ppu.write_register(5, 0x08);
+    ppu.read_register(2);
+    ppu.write_register(5, 0x10);
+    // Landed as an X write again
+    assert_eq!(ppu.tram_addr, 0x0002);
+}
+
+#[test]
+fn vertical_scroll_reloads_on_the_pre_render_line() {
+    let mut ppu = ppu();
+    ppu.write_register(1, 0x08);
+    ppu.write_register(0, 0x02);
+    ppu.write_register(5, 0x00);
+    ppu.write_register(5, 0x48);
+
+    run_to(&mut ppu, -1, 304);
+    assert_eq!(ppu.vram_addr & 0x7BE0, ppu.tram_addr & 0x7BE0);
+    assert_eq!(ppu.vram_addr & 0x7BE0, 0x0920);
+}
+
+#[test]
+fn coarse_y_wraps_from_the_attribute_rows_without_switching_nametables() {
+    let mut ppu = ppu();
+    ppu.write_register(1, 0x08);
+    // Y scroll 255: coarse Y 31, fine Y 7
+    ppu.write_register(5, 0x00);
+    ppu.write_register(5, 0xFF);
+
+    run_to(&mut ppu, 0, 256);
+    assert_eq!(ppu.vram_addr & 0x7BE0, 0x0000);
+}
+
+#[test]
+fn coarse_y_29_moves_to_the_next_nametable() {
+    let mut ppu = ppu();
+    ppu.write_register(1, 0x08);
+    // Y scroll 239: coarse Y 29, fine Y 7
+    ppu.write_register(5, 0x00);
+    ppu.write_register(5, 0xEF);
+
+    run_to(&mut ppu, 0, 256);
+    assert_eq!(ppu.vram_addr & 0x7BE0, 0x0800);
+}
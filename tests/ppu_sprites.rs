@@ -0,0 +1,53 @@
+// tests/ppu_sprites.rs
+// Sprite 0 hit against the left-edge clipping bits in PPUMASK
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+
+// NROM whose tile 0 is solid color 3 in both pattern tables, so the blank
+// nametable and a tile 0 sprite are opaque everywhere
+fn ppu() -> Ppu {
+    common::ppu(|chr| {
+        chr[..16].fill(0xFF);
+        chr[0x1000..0x1010].fill(0xFF);
+    })
+}
+
+// Sprite 0 at column `x` on line 1, rendered through line 8 with `mask`
+fn sprite_zero_hit(mask: u8, x: u8) -> bool {
+    let mut ppu = ppu();
+    ppu.write_register(3, 0x00);
+    for byte in [0x00, 0x00, 0x00, x] {
+        ppu.write_register(4, byte);
+    }
+    ppu.write_register(1, mask);
+    while ppu.scanline != 9 {
+        ppu.step();
+    }
+    ppu.read_register(2) & 0x40 != 0
+}
+
+#[test]
+fn hit_inside_the_left_edge_when_both_layers_show_there() {
+    assert!(sprite_zero_hit(0x1E, 0));
+}
+
+#[test]
+fn clipping_either_layer_blocks_hits_in_the_left_edge() {
+    assert!(!sprite_zero_hit(0x1A, 0));
+    assert!(!sprite_zero_hit(0x1C, 0));
+    assert!(!sprite_zero_hit(0x18, 0));
+}
+
+#[test]
+fn clipping_leaves_the_rest_of_the_sprite() {
+    // Columns 8-11 are past the clipped area
+    assert!(sprite_zero_hit(0x18, 4));
+}
+
+#[test]
+fn no_hit_with_either_layer_off() {
+    assert!(!sprite_zero_hit(0x16, 20));
+    assert!(!sprite_zero_hit(0x0E, 20));
+}
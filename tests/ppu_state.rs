@@ -0,0 +1,52 @@
+// tests/ppu_state.rs
+// PPU save states: a restored PPU carries on exactly as the original
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+
+// NROM with a striped tile 0, so the shifters and sprite units hold data
+fn ppu() -> Ppu {
+    common::ppu(|chr| {
+        chr[..8].copy_from_slice(&[0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA]);
+        chr[8..16].copy_from_slice(&[0x0F, 0xF0, 0x0F, 0xF0, 0x0F, 0xF0, 0x0F, 0xF0]);
+    })
+}
+
+// Mid-line on a visible scanline, with sprites on it and scrolling set
+fn busy_ppu() -> Ppu {
+    let mut ppu = ppu();
+    ppu.write_register(3, 0x00);
+    for byte in [0x20, 0x00, 0x41, 0x30, 0x22, 0x00, 0x02, 0x80] {
+        ppu.write_register(4, byte);
+    }
+    ppu.write_register(5, 0x0B);
+    ppu.write_register(5, 0x21);
+    ppu.write_register(1, 0x1E);
+    common::run_to(&mut ppu, 40, 123);
+    ppu
+}
+
+#[test]
+fn restored_ppu_runs_identically() {
+    let mut original = busy_ppu();
+    let state = original.save_state();
+
+    let mut restored = ppu();
+    restored.load_state(&state);
+    assert_eq!(restored.save_state(), state);
+
+    for _ in 0..100_000 {
+        assert_eq!(original.step(), restored.step());
+    }
+    assert_eq!(restored.save_state(), original.save_state());
+    assert_eq!(restored.read_register(2), original.read_register(2));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn state_round_trips_through_serde() {
+    let state = busy_ppu().save_state();
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<alphanes::nes::ppu::PpuState>(&json).unwrap(), state);
+}
@@ -0,0 +1,156 @@
+// tests/ppu_video.rs
+// Finished frames through the RGB and NTSC video filters
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::ppu::{Palette, PixelFormat, Ppu, VideoFilter, NTSC_WIDTH};
+use alphanes::nes::Nes;
+
+// NROM that waits out the PPU warm-up, sets the backdrop to $16 and spins
+fn rom() -> Rom {
+    let program = [
+        0x2C, 0x02, 0x20, 0x10, 0xFB, // BIT $2002; BPL *-3
+        0x2C, 0x02, 0x20, 0x10, 0xFB, // BIT $2002; BPL *-3
+        0xA9, 0x3F, 0x8D, 0x06, 0x20, // LDA #$3F; STA $2006
+        0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00; STA $2006
+        0xA9, 0x16, 0x8D, 0x07, 0x20, // LDA #$16; STA $2007
+        0xA9, 0x20, 0x8D, 0x06, 0x20, // LDA #$20; STA $2006
+        0x8D, 0x06, 0x20, // STA $2006, parking v at $2020
+        0x4C, 0x21, 0x80, // JMP *
+    ];
+    let mut image = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0];
+    image.resize(16, 0);
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..program.len()].copy_from_slice(&program);
+    prg[0x3FFC] = 0x00;
+    prg[0x3FFD] = 0x80;
+    image.extend(prg);
+    image.resize(image.len() + 0x2000, 0);
+    Rom::from_bytes(&image).unwrap()
+}
+
+fn ppu() -> Ppu {
+    Ppu::new(cart::new_mapper(rom()).unwrap())
+}
+
+fn write_palette(ppu: &mut Ppu, addr: u16, color: u8) {
+    ppu.write_register(6, (addr >> 8) as u8);
+    ppu.write_register(6, addr as u8);
+    ppu.write_register(7, color);
+}
+
+// Point v away from the palette so the backdrop is $3F00
+fn park_v(ppu: &mut Ppu) {
+    ppu.write_register(6, 0x20);
+    ppu.write_register(6, 0x00);
+}
+
+fn run_frame(ppu: &mut Ppu) {
+    while !ppu.step() {}
+    while !(ppu.scanline == 241 && ppu.cycle == 0) {
+        ppu.step();
+    }
+}
+
+#[test]
+fn rgb_frames_go_through_the_palette() {
+    let mut ppu = ppu();
+    write_palette(&mut ppu, 0x3F00, 0x16);
+    park_v(&mut ppu);
+    ppu.write_register(1, 0x20); // Red emphasis
+    run_frame(&mut ppu);
+
+    let expected = Palette::ntsc().color(0x01 << 6 | 0x16);
+    let frame = ppu.output_frame();
+    assert_eq!((frame.width, frame.height), (256, 240));
+    assert!(frame.pixels.iter().all(|&pixel| pixel == expected));
+}
+
+#[test]
+fn forced_blank_shows_the_palette_entry_at_v() {
+    let mut ppu = ppu();
+    write_palette(&mut ppu, 0x3F00, 0x0F);
+    write_palette(&mut ppu, 0x3F05, 0x2A);
+    ppu.write_register(6, 0x3F);
+    ppu.write_register(6, 0x05);
+    run_frame(&mut ppu);
+
+    let frame = ppu.output_frame();
+    assert_eq!(frame.pixels[120 * 256 + 128], Palette::ntsc().color(0x2A));
+}
+
+#[test]
+fn ntsc_filter_decodes_flat_areas_to_the_palette_color() {
+    let mut ppu = ppu();
+    write_palette(&mut ppu, 0x3F00, 0x21);
+    park_v(&mut ppu);
+    ppu.video_filter = VideoFilter::Ntsc;
+    run_frame(&mut ppu);
+
+    let flat = Palette::ntsc().color(0x21);
+    let frame = ppu.output_frame();
+    assert_eq!((frame.width, frame.height), (NTSC_WIDTH, 240));
+    // Away from the line ends the signal decodes to the palette color,
+    // give or take rounding
+    for &pixel in &frame.pixels[100 * NTSC_WIDTH + 8..101 * NTSC_WIDTH - 8] {
+        for shift in [0, 8, 16] {
+            let (got, want) = ((pixel >> shift & 0xFF) as i32, (flat >> shift & 0xFF) as i32);
+            assert!((got - want).abs() <= 1, "{:06X} vs {:06X}", pixel, flat);
+        }
+    }
+
+    // Switching back is immediate
+    ppu.video_filter = VideoFilter::Rgb;
+    assert_eq!(ppu.output_frame().width, 256);
+}
+
+#[test]
+fn frames_convert_to_each_pixel_format() {
+    let mut ppu = ppu();
+    write_palette(&mut ppu, 0x3F00, 0x16);
+    park_v(&mut ppu);
+    run_frame(&mut ppu);
+    let rgb = Palette::ntsc().color(0x16);
+    let [_, r, g, b] = rgb.to_be_bytes();
+
+    let frame = ppu.frame(PixelFormat::Rgba8888);
+    assert_eq!(frame.data.len(), 256 * 240 * 4);
+    assert_eq!(frame.data[..4], [r, g, b, 0xFF]);
+    assert!(frame.palette.is_none());
+
+    let frame = ppu.frame(PixelFormat::Rgb565);
+    assert_eq!(frame.data.len(), 256 * 240 * 2);
+    let pixel = u16::from_ne_bytes([frame.data[0], frame.data[1]]);
+    assert_eq!(pixel, (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3);
+
+    // Indices ignore the filter and carry their own palette
+    ppu.video_filter = VideoFilter::Ntsc;
+    let frame = ppu.frame(PixelFormat::Indexed);
+    assert_eq!((frame.width, frame.height, frame.data.len()), (256, 240, 256 * 240 * 2));
+    assert_eq!(u16::from_ne_bytes([frame.data[0], frame.data[1]]), 0x16);
+    assert_eq!(frame.palette.unwrap()[0x16 * 4..0x16 * 4 + 4], [r, g, b, 0xFF]);
+
+    let frame = ppu.frame(PixelFormat::Rgba8888);
+    assert_eq!((frame.width, frame.data.len()), (NTSC_WIDTH, NTSC_WIDTH * 240 * 4));
+}
+
+#[test]
+fn nes_hands_finished_frames_to_the_callback() {
+    let mut nes = Nes::new(rom()).unwrap();
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    let seen = frames.clone();
+    nes.on_frame(PixelFormat::Rgba8888, move |frame| {
+        seen.borrow_mut().push(frame.data[120 * 256 * 4..120 * 256 * 4 + 4].to_vec());
+    });
+    for _ in 0..3 {
+        nes.run_frame().unwrap();
+    }
+
+    let [_, r, g, b] = Palette::ntsc().color(0x16).to_be_bytes();
+    let frames = frames.borrow();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[2], [r, g, b, 0xFF]);
+    assert_eq!(nes.frame(PixelFormat::Rgba8888).data[120 * 256 * 4..][..4], [r, g, b, 0xFF]);
+}
@@ -0,0 +1,75 @@
+// tests/ppu_warm_up.rs
+// PPU register writes ignored after power-on and reset until the pre-render line
+
+mod common;
+
+use alphanes::nes::ppu::Ppu;
+
+fn ppu() -> Ppu {
+    let mut ppu = common::ppu(|_| {});
+    ppu.reset();
+    ppu
+}
+
+#[test]
+fn control_mask_scroll_and_address_writes_are_ignored_while_warming_up() {
+    let mut ppu = ppu();
+    ppu.write_register(0, 0x80);
+    ppu.write_register(1, 0x1E);
+    ppu.write_register(5, 0x7D);
+    ppu.write_register(6, 0x3F);
+    assert_eq!(ppu.registers.control.bits(), 0x00);
+    assert_eq!(ppu.registers.mask.bits(), 0x00);
+    assert_eq!((ppu.tram_addr, ppu.fine_x), (0x0000, 0));
+    assert!(!ppu.registers.write_toggle);
+
+    // OAM and PPUDATA are live, and the I/O latch still sees every write
+    ppu.write_register(3, 0x10);
+    assert_eq!(ppu.registers.oam_addr, 0x10);
+    ppu.write_register(6, 0x3F);
+    assert_eq!(ppu.read_register(0), 0x3F);
+}
+
+#[test]
+fn writes_are_taken_from_the_pre_render_line() {
+    let mut ppu = ppu();
+    while ppu.scanline != 260 {
+        ppu.step();
+    }
+    ppu.write_register(1, 0x1E);
+    assert_eq!(ppu.registers.mask.bits(), 0x00);
+
+    while ppu.scanline != -1 {
+        ppu.step();
+    }
+    ppu.write_register(1, 0x1E);
+    assert_eq!(ppu.registers.mask.bits(), 0x1E);
+}
+
+#[test]
+fn warm_up_can_be_switched_off() {
+    let mut ppu = ppu();
+    ppu.warm_up = false;
+    ppu.write_register(0, 0x80);
+    ppu.write_register(5, 0x7D);
+    assert_eq!(ppu.registers.control.bits(), 0x80);
+    assert_eq!(ppu.fine_x, 5);
+}
+
+#[test]
+fn reset_clears_the_registers_and_warms_up_again() {
+    let mut ppu = ppu();
+    ppu.warm_up = false;
+    ppu.write_register(0, 0x80);
+    ppu.write_register(1, 0x1E);
+    ppu.write_register(5, 0x7D);
+    ppu.warm_up = true;
+
+    ppu.reset();
+    assert_eq!(ppu.registers.control.bits(), 0x00);
+    assert_eq!(ppu.registers.mask.bits(), 0x00);
+    assert_eq!(ppu.fine_x, 0);
+    assert!(!ppu.registers.write_toggle);
+    ppu.write_register(0, 0x80);
+    assert_eq!(ppu.registers.control.bits(), 0x00);
+}
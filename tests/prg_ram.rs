@@ -0,0 +1,69 @@
+// tests/prg_ram.rs
+// Cartridge work RAM at $6000-$7FFF: trainers, mapper enables and open bus when it's switched off
+
+mod common;
+
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::cpu::Bus;
+use alphanes::nes::Nes;
+use common::run;
+
+// Single-bank iNES image for `mapper` running `program` from $8000, with
+// 8KB of PRG RAM and CHR RAM
+fn image(mapper: u8, program: &[u8]) -> Vec<u8> {
+    let mut image = common::ines(mapper, &common::prg(program), &[]);
+    image[8] = 1;
+    image
+}
+
+// `image` with a trainer counting up from 1 between the header and PRG
+fn with_trainer(mut image: Vec<u8>) -> Rom {
+    image[6] |= 0x04;
+    let trainer: Vec<u8> = (1..=255).cycle().take(512).collect();
+    image.splice(16..16, trainer);
+    Rom::from_bytes(&image).unwrap()
+}
+
+#[test]
+fn disabled_mmc1_ram_reads_open_bus() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x5A, 0x8D, 0x00, 0x60, // STA $6000 while enabled
+        0xA9, 0x10,                   // PRG bank $10: bit 4 disables RAM
+        0x8D, 0x00, 0xE0, 0x4A,       // Five serial writes to $E000, LSB first
+        0x8D, 0x00, 0xE0, 0x4A,
+        0x8D, 0x00, 0xE0, 0x4A,
+        0x8D, 0x00, 0xE0, 0x4A,
+        0x8D, 0x00, 0xE0,
+        0xA9, 0xFF, 0x8D, 0x00, 0x60, // Dropped while disabled
+        0xAD, 0x00, 0x60, 0x85, 0x00, // LDA $6000: the $60 left by the operand
+        0xA9, 0x00,                   // PRG bank 0 enables it again
+        0x8D, 0x00, 0xE0, 0x8D, 0x00, 0xE0, 0x8D, 0x00, 0xE0, 0x8D, 0x00, 0xE0, 0x8D, 0x00, 0xE0,
+        0xAD, 0x00, 0x60, 0x85, 0x01, // LDA $6000
+        0x02,                         // JAM
+    ];
+    let mut nes = Nes::new(Rom::from_bytes(&image(1, &program)).unwrap()).unwrap();
+    run(&mut nes);
+    assert_eq!(nes.cpu.bus.peek(0x0000), 0x60);
+    assert_eq!(nes.cpu.bus.peek(0x0001), 0x5A);
+}
+
+#[test]
+fn trainer_is_preloaded_at_7000() {
+    let cart = cart::new_mapper(with_trainer(image(0, &[]))).unwrap();
+    let mut cart = cart.borrow_mut();
+    assert_eq!(cart.cpu_read(0x6FFF), Some(0));
+    assert_eq!(cart.cpu_read(0x7000), Some(1));
+    assert_eq!(cart.cpu_read(0x71FF), Some(2));
+    assert_eq!(cart.cpu_read(0x7200), Some(0));
+}
+
+#[test]
+fn fme7_preloads_the_trainer_into_its_ram() {
+    let cart = cart::new_mapper(with_trainer(image(69, &[]))).unwrap();
+    let mut cart = cart.borrow_mut();
+    cart.cpu_write(0x8000, 0x08);
+    cart.cpu_write(0xA000, 0xC0); // RAM bank 0, selected and enabled
+    assert_eq!(cart.cpu_read(0x7000), Some(1));
+    assert_eq!(cart.cpu_read(0x7001), Some(2));
+}
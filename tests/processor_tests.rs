@@ -0,0 +1,158 @@
+// tests/processor_tests.rs
+// Tom Harte's ProcessorTests (nes6502 set) run against Cpu2A03, checking
+// registers, memory and every bus cycle
+//
+// The vectors are too large to vendor. Point PROCESSOR_TESTS_DIR at the
+// `nes6502/v1` directory of a ProcessorTests checkout (00.json .. ff.json);
+// without it the test is skipped.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use alphanes::nes::cpu::{Bus, Cpu2A03, Mnemonic, OPCODES};
+use serde_json::Value;
+
+// Failures printed per opcode before moving on
+const MAX_REPORTED: usize = 5;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Cycle {
+    addr: u16,
+    value: u8,
+    write: bool,
+}
+
+// Flat 64KB memory that records every access
+struct RecordingBus {
+    ram: Vec<u8>,
+    cycles: Vec<Cycle>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = self.ram[addr as usize];
+        self.cycles.push(Cycle { addr, value, write: false });
+        value
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.ram[addr as usize] = data;
+        self.cycles.push(Cycle { addr, value: data, write: true });
+    }
+}
+
+fn field(state: &Value, name: &str) -> u64 {
+    state[name].as_u64().unwrap_or_else(|| panic!("missing field {}", name))
+}
+
+fn ram_entries(state: &Value) -> impl Iterator<Item = (u16, u8)> + '_ {
+    state["ram"]
+        .as_array()
+        .expect("missing ram")
+        .iter()
+        .map(|entry| (entry[0].as_u64().unwrap() as u16, entry[1].as_u64().unwrap() as u8))
+}
+
+// Runs one vector, returning a description of the first mismatch
+fn run_case(case: &Value) -> Result<(), String> {
+    let initial = &case["initial"];
+    let expected = &case["final"];
+
+    let mut ram = vec![0; 0x10000];
+    for (addr, value) in ram_entries(initial) {
+        ram[addr as usize] = value;
+    }
+    let mut cpu = Cpu2A03::new(RecordingBus { ram, cycles: Vec::new() });
+    cpu.pc = field(initial, "pc") as u16;
+    cpu.sp = field(initial, "s") as u8;
+    cpu.a = field(initial, "a") as u8;
+    cpu.x = field(initial, "x") as u8;
+    cpu.y = field(initial, "y") as u8;
+    cpu.status = field(initial, "p") as u8;
+
+    let cycles = cpu.step().map_err(|err| err.to_string())?;
+
+    let registers = [
+        ("pc", cpu.pc as u64),
+        ("s", cpu.sp as u64),
+        ("a", cpu.a as u64),
+        ("x", cpu.x as u64),
+        ("y", cpu.y as u64),
+        ("p", cpu.status as u64),
+    ];
+    for (name, actual) in registers {
+        let wanted = field(expected, name);
+        if actual != wanted {
+            return Err(format!("{}: {:02X}, expected {:02X}", name, actual, wanted));
+        }
+    }
+    for (addr, wanted) in ram_entries(expected) {
+        let actual = cpu.bus.ram[addr as usize];
+        if actual != wanted {
+            return Err(format!("ram[{:04X}]: {:02X}, expected {:02X}", addr, actual, wanted));
+        }
+    }
+
+    let wanted: Vec<Cycle> = case["cycles"]
+        .as_array()
+        .expect("missing cycles")
+        .iter()
+        .map(|cycle| Cycle {
+            addr: cycle[0].as_u64().unwrap() as u16,
+            value: cycle[1].as_u64().unwrap() as u8,
+            write: cycle[2].as_str() == Some("write"),
+        })
+        .collect();
+    if cycles != wanted.len() {
+        return Err(format!("took {} cycles, expected {}", cycles, wanted.len()));
+    }
+    if let Some(index) = (0..wanted.len()).find(|&i| cpu.bus.cycles.get(i) != Some(&wanted[i])) {
+        return Err(format!(
+            "cycle {}: {:?}, expected {:?}",
+            index,
+            cpu.bus.cycles.get(index),
+            wanted[index]
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn processor_tests_nes6502() {
+    let Ok(dir) = env::var("PROCESSOR_TESTS_DIR") else {
+        eprintln!("PROCESSOR_TESTS_DIR not set; skipping ProcessorTests");
+        return;
+    };
+
+    let mut failed_opcodes = Vec::new();
+    for opcode in 0..=255u8 {
+        // JAM never finishes an instruction, so it has nothing to compare
+        if OPCODES[opcode as usize].mnemonic == Mnemonic::Jam {
+            continue;
+        }
+        let path = Path::new(&dir).join(format!("{:02x}.json", opcode));
+        let Ok(text) = fs::read_to_string(&path) else {
+            eprintln!("{} not found; skipping", path.display());
+            continue;
+        };
+        let cases: Value = serde_json::from_str(&text).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        let cases = cases.as_array().expect("test file is not an array");
+
+        let failures: Vec<String> = cases
+            .iter()
+            .filter_map(|case| {
+                run_case(case).err().map(|err| format!("  {}: {}", case["name"].as_str().unwrap_or("?"), err))
+            })
+            .collect();
+        if !failures.is_empty() {
+            eprintln!("opcode {:02X}: {}/{} failed", opcode, failures.len(), cases.len());
+            for failure in failures.iter().take(MAX_REPORTED) {
+                eprintln!("{}", failure);
+            }
+            failed_opcodes.push(opcode);
+        }
+    }
+
+    assert!(failed_opcodes.is_empty(), "failing opcodes: {:02X?}", failed_opcodes);
+}
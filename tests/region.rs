@@ -0,0 +1,115 @@
+// tests/region.rs
+// NTSC and PAL timing: header detection, frame length and VBlank
+
+mod common;
+
+use alphanes::nes::cart::{self, Rom};
+use alphanes::nes::clock::Region;
+use alphanes::nes::ppu::Ppu;
+use alphanes::nes::Nes;
+use common::run_to;
+
+// NROM image spinning in a JMP loop at $8000, with `header` bytes 7-15
+fn rom(header: [u8; 9]) -> Rom {
+    let mut image = common::ines(0, &common::prg(&[0x4C, 0x00, 0x80]), &common::chr(|_| {}));
+    image[7..16].copy_from_slice(&header);
+    Rom::from_bytes(&image).unwrap()
+}
+
+fn ppu(region: Region) -> Ppu {
+    let mut ppu = Ppu::new(cart::new_mapper(rom([0; 9])).unwrap());
+    ppu.region = region;
+    ppu
+}
+
+// Dots from the start of the pre-render line to the next one
+fn frame_dots(ppu: &mut Ppu) -> usize {
+    run_to(ppu, -1, 0);
+    let mut dots = 0;
+    loop {
+        ppu.step();
+        dots += 1;
+        if ppu.scanline == -1 && ppu.cycle == 0 {
+            return dots;
+        }
+    }
+}
+
+#[test]
+fn region_comes_from_the_header() {
+    assert_eq!(rom([0; 9]).region, Region::Ntsc);
+    // iNES flags 9, bit 0
+    assert_eq!(rom([0, 0, 0x01, 0, 0, 0, 0, 0, 0]).region, Region::Pal);
+    // NES 2.0 byte 12: PAL, then multi-region
+    assert_eq!(rom([0x08, 0, 0, 0, 0, 0x01, 0, 0, 0]).region, Region::Pal);
+    assert_eq!(rom([0x08, 0, 0, 0, 0, 0x02, 0, 0, 0]).region, Region::Ntsc);
+}
+
+#[test]
+fn pal_frames_are_312_lines_without_a_skipped_dot() {
+    let mut ppu = ppu(Region::Pal);
+    ppu.write_register(1, 0x18);
+    assert_eq!(frame_dots(&mut ppu), 341 * 312);
+    assert_eq!(frame_dots(&mut ppu), 341 * 312);
+}
+
+#[test]
+fn ntsc_odd_frames_skip_a_dot_while_rendering() {
+    let mut ppu = ppu(Region::Ntsc);
+    ppu.write_register(1, 0x18);
+    let frames = [frame_dots(&mut ppu), frame_dots(&mut ppu)];
+    assert_eq!(frames[0] + frames[1], 341 * 262 * 2 - 1);
+}
+
+#[test]
+fn pal_vblank_lasts_70_lines() {
+    let mut ppu = ppu(Region::Pal);
+    run_to(&mut ppu, 241, 1);
+    let mut lines = 0;
+    while ppu.registers.status & 0x80 != 0 {
+        ppu.step();
+        if ppu.cycle == 1 {
+            lines += 1;
+        }
+    }
+    assert_eq!(lines, 70);
+}
+
+#[test]
+fn pal_oam_refresh_locks_out_late_writes() {
+    let mut ppu = ppu(Region::Pal);
+    ppu.write_register(1, 0x18);
+    while ppu.scanline != 250 {
+        ppu.step();
+    }
+    ppu.write_register(3, 0x00);
+    ppu.write_register(4, 0x11);
+    while ppu.scanline != 270 {
+        ppu.step();
+    }
+    ppu.write_register(4, 0x22);
+    assert_eq!(ppu.memory.oam[0..2], [0x11, 0x00]);
+}
+
+// CPU cycles between two frame completions, `frames` apart
+fn cpu_cycles_per(nes: &mut Nes, frames: usize) -> usize {
+    nes.run_frame().unwrap();
+    let start = nes.cpu.cycles();
+    for _ in 0..frames {
+        nes.run_frame().unwrap();
+    }
+    nes.cpu.cycles() - start
+}
+
+#[test]
+fn cpu_runs_at_the_region_ratio() {
+    // Rendering stays off, so both regions run whole frames
+    let mut nes = Nes::new(rom([0; 9])).unwrap();
+    let ntsc = cpu_cycles_per(&mut nes, 3);
+    assert!(ntsc.abs_diff(341 * 262) <= 3, "{} cycles", ntsc);
+
+    let mut nes = Nes::new(rom([0; 9])).unwrap();
+    nes.set_region(Region::Pal);
+    let pal = cpu_cycles_per(&mut nes, 2);
+    assert!((pal as f64 - 341.0 * 312.0 * 2.0 / 3.2).abs() <= 3.0, "{} cycles", pal);
+}